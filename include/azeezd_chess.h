@@ -0,0 +1,172 @@
+/* The C API of azeezd-chess, generated by cbindgen from src/ffi.rs. Do not edit by hand */
+
+#ifndef AZEEZD_CHESS_H
+#define AZEEZD_CHESS_H
+
+#include <stddef.h>
+
+/*
+ The most legal moves a position can have is 218, a buffer of this many `ChessMove` always fits them
+ */
+#define CHESS_MAX_MOVES 256
+
+/*
+ Results of the C API functions
+ */
+typedef enum ChessError {
+  CHESS_ERROR_OK = 0,
+  /*
+   A pointer argument was null
+   */
+  CHESS_ERROR_NULL_POINTER = 1,
+  /*
+   A string argument was not valid UTF-8
+   */
+  CHESS_ERROR_INVALID_UTF8 = 2,
+  /*
+   The FEN string could not be read
+   */
+  CHESS_ERROR_INVALID_FEN = 3,
+  /*
+   The move is not a UCI move such as `e2e4` or `e7e8q`
+   */
+  CHESS_ERROR_INVALID_MOVE = 4,
+  /*
+   The move is not legal in the position
+   */
+  CHESS_ERROR_ILLEGAL_MOVE = 5,
+  /*
+   The game has ended and no more moves can be played
+   */
+  CHESS_ERROR_GAME_OVER = 6,
+  /*
+   The buffer is too small for the result, the required size was still written
+   */
+  CHESS_ERROR_BUFFER_TOO_SMALL = 7,
+  /*
+   The library panicked, the game may be in an inconsistent state
+   */
+  CHESS_ERROR_PANIC = 8,
+} ChessError;
+
+/*
+ States of a game as seen from C, checkmate, stalemate and the ways a game ends are told apart
+ */
+typedef enum ChessState {
+  CHESS_STATE_IN_PROGRESS = 0,
+  CHESS_STATE_CHECK = 1,
+  CHESS_STATE_CHECKMATE = 2,
+  CHESS_STATE_STALEMATE = 3,
+  /*
+   Drawn by agreement, threefold repetition or the fifty move rule
+   */
+  CHESS_STATE_DRAW = 4,
+  CHESS_STATE_RESIGNED = 5,
+  CHESS_STATE_TIME_FORFEIT = 6,
+  /*
+   Won or drawn by a rule of the variant the game is played with
+   */
+  CHESS_STATE_VARIANT_END = 7,
+} ChessState;
+
+/*
+ An opaque game handle
+ */
+typedef struct ChessGame ChessGame;
+
+/*
+ ## ChessMove
+ ### Type: `struct`
+ A move in UCI notation as a NUL terminated string, e.g `"e2e4"` or `"e7e8q"`
+ ### Members:
+ - `uci`: The characters of the move followed by NUL bytes
+ */
+typedef struct ChessMove {
+  char uci[6];
+} ChessMove;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ ## `chess_game_new`
+ Creates a game from the standard starting position
+ ### Safety
+ `out` must be null or valid for writing a pointer. The handle written to it must be released with `chess_game_free`
+ */
+enum ChessError chess_game_new(struct ChessGame **out);
+
+/*
+ ## `chess_game_from_fen`
+ Creates a game from a FEN string
+ ### Safety
+ `fen` must be null or a NUL terminated string and `out` null or valid for writing a pointer.
+ The handle written to it must be released with `chess_game_free`
+ */
+enum ChessError chess_game_from_fen(const char *fen, struct ChessGame **out);
+
+/*
+ ## `chess_game_free`
+ Releases a game handle, null is ignored
+ ### Safety
+ `game` must be null or a handle from this library that was not released yet
+ */
+void chess_game_free(struct ChessGame *game);
+
+/*
+ ## `chess_game_make_move`
+ Plays a move given in UCI notation, e.g `"e2e4"` or `"e7e8q"`
+ ### Safety
+ `game` must be null or a live handle and `uci` null or a NUL terminated string
+ */
+enum ChessError chess_game_make_move(struct ChessGame *game, const char *uci);
+
+/*
+ ## `chess_game_legal_moves`
+ Writes the legal moves of the active colour into a buffer, none once the game has ended.
+ `count` receives the number of legal moves, if it is larger than `capacity` only the first `capacity`
+ moves are written and `CHESS_ERROR_BUFFER_TOO_SMALL` is returned
+ ### Safety
+ `game` must be null or a live handle, `moves` valid for writing `capacity` moves (or null if `capacity` is 0)
+ and `count` null or valid for writing
+ */
+enum ChessError chess_game_legal_moves(const struct ChessGame *game,
+                                       struct ChessMove *moves,
+                                       size_t capacity,
+                                       size_t *count);
+
+/*
+ ## `chess_game_state`
+ Writes the state of the game
+ ### Safety
+ `game` must be null or a live handle and `state` null or valid for writing
+ */
+enum ChessError chess_game_state(const struct ChessGame *game, enum ChessState *state);
+
+/*
+ ## `chess_game_fen`
+ Writes the FEN string of the current position as a NUL terminated string. `length` receives the length of the
+ FEN without the NUL, if the buffer cannot hold it and the NUL nothing is written and `CHESS_ERROR_BUFFER_TOO_SMALL`
+ is returned. 128 bytes always fit a FEN string
+ ### Safety
+ `game` must be null or a live handle, `buffer` valid for writing `capacity` bytes (or null if `capacity` is 0)
+ and `length` null or valid for writing
+ */
+enum ChessError chess_game_fen(const struct ChessGame *game,
+                               char *buffer,
+                               size_t capacity,
+                               size_t *length);
+
+/*
+ ## `chess_error_message`
+ Returns a static NUL terminated description of an error.
+ The error is taken as an `int` so that values outside of `ChessError` give a fallback message instead of undefined behaviour
+ */
+const char *chess_error_message(int error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AZEEZD_CHESS_H */
@@ -0,0 +1,236 @@
+use crate::chess::piece_data::Colour;
+use crate::raster::{BitWriter, Frame};
+use crate::{Game, GameState};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// ## Animation
+/// ### Type: `struct`
+/// Options for turning the moves of a game into frames, one for the starting position and one after every move.
+/// ### Members:
+/// - `size`: The width and height of the frames in pixels, rounded down to a multiple of 8
+/// - `orientation`: The colour at the bottom of the board
+/// - `coordinates`: Whether ranks and files are written on the board
+/// - `delay`: How long every frame is shown
+/// - `final_delay`: How long the last position is shown before the animation starts over
+#[derive(Clone, Debug)]
+pub struct Animation {
+    pub size: u32,
+    pub orientation: Colour,
+    pub coordinates: bool,
+    pub delay: Duration,
+    pub final_delay: Duration
+}
+
+impl Animation {
+    /// Creates the default options, 360 pixels from White's side with a second per move and three on the last position
+    pub fn new() -> Animation {
+        Animation {
+            size: 360,
+            orientation: Colour::White,
+            coordinates: true,
+            delay: Duration::from_secs(1),
+            final_delay: Duration::from_secs(3)
+        }
+    }
+
+    /// ## `render_frames`
+    /// Draws the starting position of a game and the position after every move, with the last move and a king in check highlighted
+    pub fn render_frames(&self, _game: &Game) -> Vec<Frame> {
+        let mut game = _game.get_start();
+        let mut frames = Vec::with_capacity(_game.get_history().len() + 1);
+        frames.push(self.render_position(&game));
+
+        for _move in _game.get_history() {
+            game.apply_move(*_move);
+            frames.push(self.render_position(&game));
+        }
+
+        frames
+    }
+
+    fn render_position(&self, _game: &Game) -> Frame {
+        let check = if _game.get_game_state() == GameState::Check {Some(_game.get_board().get_king(_game.get_active_colour()))} else {None};
+        Frame::from_board(_game.get_board(), self.size, self.orientation, self.coordinates, _game.get_history().last().copied(), check)
+    }
+
+    /// ## `write_gif`
+    /// Writes the game as an animated GIF that loops forever. The colours of all frames share one palette of the
+    /// 256 most used colours, the edges of the pieces take the nearest of them
+    pub fn write_gif<W: Write>(&self, _game: &Game, mut _writer: W) -> io::Result<()> {
+        let frames = self.render_frames(_game);
+        let palette = build_palette(&frames);
+        let (width, height) = (frames[0].width as u16, frames[0].height as u16);
+
+        _writer.write_all(b"GIF89a")?;
+        _writer.write_all(&width.to_le_bytes())?;
+        _writer.write_all(&height.to_le_bytes())?;
+        // A global colour table of 256 entries with 8 bits per channel, no background colour or aspect ratio
+        _writer.write_all(&[0xF7, 0, 0])?;
+        for index in 0..256 {
+            _writer.write_all(&palette.get(index).copied().unwrap_or([0, 0, 0]))?;
+        }
+        // The NETSCAPE2.0 extension makes the animation loop, 0 is forever
+        _writer.write_all(&[0x21, 0xFF, 0x0B])?;
+        _writer.write_all(b"NETSCAPE2.0")?;
+        _writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+        let mut nearest: HashMap<[u8; 3], u8> = palette.iter().enumerate().map(|(index, colour)| (*colour, index as u8)).collect();
+
+        for (index, frame) in frames.iter().enumerate() {
+            let delay = if index + 1 == frames.len() {self.final_delay} else {self.delay};
+            let centiseconds = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+
+            // The graphic control extension holds the delay of the frame
+            _writer.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+            _writer.write_all(&centiseconds.to_le_bytes())?;
+            _writer.write_all(&[0x00, 0x00])?;
+
+            // The image descriptor, the whole screen without a local colour table
+            _writer.write_all(&[0x2C, 0, 0, 0, 0])?;
+            _writer.write_all(&width.to_le_bytes())?;
+            _writer.write_all(&height.to_le_bytes())?;
+            _writer.write_all(&[0x00])?;
+
+            let indices: Vec<u8> = frame.pixels.chunks(3).map(|pixel| {
+                let colour = [pixel[0], pixel[1], pixel[2]];
+                *nearest.entry(colour).or_insert_with(|| nearest_colour(&palette, colour))
+            }).collect();
+
+            _writer.write_all(&[8])?;
+            for block in lzw_compress(&indices).chunks(255) {
+                _writer.write_all(&[block.len() as u8])?;
+                _writer.write_all(block)?;
+            }
+            _writer.write_all(&[0x00])?;
+        }
+
+        _writer.write_all(&[0x3B])
+    }
+
+    /// ## `write_png_sequence`
+    /// Writes every frame as a PNG file in a directory, named with a prefix and the number of the frame,
+    /// e.g `game_000.png`, `game_001.png`, ... The directory is created if needed
+    /// ### Returns
+    /// Returns the paths of the written files
+    pub fn write_png_sequence(&self, _game: &Game, _directory: &Path, _prefix: &str) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(_directory)?;
+
+        let frames = self.render_frames(_game);
+        let digits = frames.len().to_string().len().max(3);
+
+        let mut paths = Vec::with_capacity(frames.len());
+        for (index, frame) in frames.iter().enumerate() {
+            let path = _directory.join(format!("{}_{:0digits$}.png", _prefix, index, digits = digits));
+            fs::write(&path, frame.to_png())?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation::new()
+    }
+}
+
+/// Returns the 256 most used colours of the frames, or all of them if there are fewer
+fn build_palette(_frames: &[Frame]) -> Vec<[u8; 3]> {
+    let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+    for frame in _frames {
+        for pixel in frame.pixels.chunks(3) {
+            *counts.entry([pixel[0], pixel[1], pixel[2]]).or_insert(0) += 1;
+        }
+    }
+
+    let mut colours: Vec<([u8; 3], usize)> = counts.into_iter().collect();
+    // Ties are broken by the colour so the palette does not depend on the order of the map
+    colours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    colours.into_iter().take(256).map(|(colour, _)| colour).collect()
+}
+
+/// Returns the index of the palette colour closest to a colour
+fn nearest_colour(_palette: &[[u8; 3]], _colour: [u8; 3]) -> u8 {
+    let distance = |other: &[u8; 3]| -> i32 {
+        (0..3).map(|channel| (other[channel] as i32 - _colour[channel] as i32).pow(2)).sum()
+    };
+    _palette.iter().enumerate().min_by_key(|(_, other)| distance(other)).map_or(0, |(index, _)| index as u8)
+}
+
+/// ## `lzw_compress`
+/// Compresses 8 bit colour indices with the variable length LZW of GIF, starting with a clear code and
+/// clearing the table when it is full
+fn lzw_compress(_indices: &[u8]) -> Vec<u8> {
+    let mut encoder = LzwEncoder {
+        writer: BitWriter::new(),
+        table: HashMap::new(),
+        width: 9,
+        last_code: LZW_END
+    };
+    encoder.writer.write(LZW_CLEAR, encoder.width);
+
+    let mut current = match _indices.first() {
+        Some(first) => *first as u32,
+        None => {
+            encoder.writer.write(LZW_END, encoder.width);
+            return encoder.writer.finish();
+        }
+    };
+
+    for index in &_indices[1..] {
+        let key = (current, *index);
+        if let Some(code) = encoder.table.get(&key) {
+            current = *code;
+            continue;
+        }
+
+        encoder.writer.write(current, encoder.width);
+        current = *index as u32;
+        if encoder.next_code() {
+            encoder.table.insert(key, encoder.last_code);
+        }
+    }
+
+    encoder.writer.write(current, encoder.width);
+    encoder.next_code();
+    encoder.writer.write(LZW_END, encoder.width);
+    encoder.writer.finish()
+}
+
+const LZW_CLEAR: u32 = 256;
+const LZW_END: u32 = 257;
+const LZW_MAX_CODE: u32 = 4095;
+
+/// The state of `lzw_compress`, the table maps a code and the next index to the code of both
+struct LzwEncoder {
+    writer: BitWriter,
+    table: HashMap<(u32, u8), u32>,
+    width: u32,
+    last_code: u32
+}
+
+impl LzwEncoder {
+    /// Takes the next code, widening the codes once they no longer fit. When the last code is reached a clear code
+    /// is written and the table starts over, which returns false as the code cannot be used
+    fn next_code(&mut self) -> bool {
+        self.last_code += 1;
+        if self.last_code == 1 << self.width {
+            self.width += 1;
+        }
+
+        if self.last_code == LZW_MAX_CODE {
+            self.writer.write(LZW_CLEAR, self.width);
+            self.table.clear();
+            self.width = 9;
+            self.last_code = LZW_END;
+            return false;
+        }
+
+        true
+    }
+}
@@ -0,0 +1,74 @@
+//! Builds a Polyglot opening book from PGN game archives.
+//!
+//! Usage: `book-builder <output.bin> <games.pgn>... [--min-games <n>] [--max-ply <n>] [--min-rating <n>]`
+
+use azeezd_chess::book::BookBuilder;
+use azeezd_chess::pgn::Pgn;
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "Usage: book-builder <output.bin> <games.pgn>... [--min-games <n>] [--max-ply <n>] [--min-rating <n>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut builder = BookBuilder::new();
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = || args.get(i + 1).and_then(|value| value.parse().ok()).unwrap_or_else(|| exit_with(USAGE));
+        match args[i].as_str() {
+            "--min-games" => {
+                builder.min_games = value();
+                i += 1;
+            }
+            "--max-ply" => {
+                builder.max_ply = value() as usize;
+                i += 1;
+            }
+            "--min-rating" => {
+                builder.min_rating = value();
+                i += 1;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => paths.push(args[i].clone())
+        }
+        i += 1;
+    }
+
+    if paths.len() < 2 {
+        exit_with(USAGE);
+    }
+    let output = paths.remove(0);
+
+    let (mut added, mut skipped) = (0, 0);
+    for path in &paths {
+        let text = fs::read_to_string(path).unwrap_or_else(|error| exit_with(&format!("Could not read {}: {}", path, error)));
+
+        for pgn in Pgn::parse_all(&text) {
+            match builder.add_game(&pgn) {
+                Some(_) => added += 1,
+                None => skipped += 1
+            }
+        }
+    }
+
+    if let Err(error) = builder.write(&output) {
+        exit_with(&format!("Could not write {}: {}", output, error));
+    }
+
+    println!("Games used: {}, skipped: {}", added, skipped);
+    println!("Positions: {}, book entries: {}", builder.get_position_count(), builder.build().len());
+    println!("Written to {}", output);
+}
+
+/// Prints a message and exits with an error code
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
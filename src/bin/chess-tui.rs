@@ -0,0 +1,550 @@
+//! Plays chess in a full-screen terminal interface drawn with ANSI escape codes. Pieces are picked with the arrow keys
+//! or hjkl and Enter or Space, the legal targets, the last move and a checked king are highlighted.
+//!
+//! Usage: `chess-tui [--engine <white|black>] [--time <seconds>] [--clock <time control>] [--fen <fen>]`
+//!
+//! The time control is given as in the PGN `TimeControl` tag, e.g `300+2` or `40/5400+30:1800+30`.
+//! The terminal is switched to raw mode with `stty`, so this needs a Unix terminal.
+
+use azeezd_chess::chess::board::Board;
+use azeezd_chess::chess::moves::Move;
+use azeezd_chess::chess::piece_data::{Colour, PieceType};
+use azeezd_chess::clock::{Clock, TimeControl};
+use azeezd_chess::search::{SearchLimits, SearchResult};
+use azeezd_chess::{Game, GameState};
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::{self, Command};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: chess-tui [--engine <white|black>] [--time <seconds>] [--clock <time control>] [--fen <fen>]";
+
+const HELP: &str = "arrows/hjkl move  enter/space pick  x drop  u undo  f flip  d draw  r resign  n new  q quit";
+
+/// How often the clocks are redrawn while waiting for a key
+const TICK: Duration = Duration::from_millis(100);
+
+// 256 colour backgrounds of the squares, light then dark
+const LIGHT: u8 = 223;
+const DARK: u8 = 137;
+const LAST_MOVE: (u8, u8) = (187, 143);
+const TARGET: (u8, u8) = (151, 108);
+const SELECTED: u8 = 71;
+const CHECK: u8 = 167;
+const CURSOR: u8 = 75;
+
+/// Keys the interface reacts to
+#[derive(Copy, Clone, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pick,
+    Char(char)
+}
+
+/// Puts the terminal in raw mode and restores it when dropped, also when the program panics
+struct RawTerminal {
+    saved: String
+}
+
+impl RawTerminal {
+    fn enable() -> Option<RawTerminal> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush().ok();
+        Some(RawTerminal { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        io::stdout().flush().ok();
+        stty(&[&self.saved]);
+    }
+}
+
+/// Runs `stty` on the terminal, returns its output or `None` if it failed
+fn stty(_args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(_args).stdin(File::open("/dev/tty").ok()?).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Reads the keys on another thread so the clocks keep running while waiting for one
+fn spawn_key_reader() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut byte = [0u8; 1];
+        let mut read = move || stdin.read(&mut byte).ok().filter(|read| *read == 1).map(|_| byte[0]);
+
+        while let Some(first) = read() {
+            let key = match first {
+                // Arrow keys are sent as ESC [ A to ESC [ D
+                0x1b => match (read(), read()) {
+                    (Some(b'['), Some(b'A')) => Key::Up,
+                    (Some(b'['), Some(b'B')) => Key::Down,
+                    (Some(b'['), Some(b'C')) => Key::Right,
+                    (Some(b'['), Some(b'D')) => Key::Left,
+                    _ => continue
+                },
+                b'\r' | b'\n' | b' ' => Key::Pick,
+                0x03 => Key::Char('q'),
+                0x7f | 0x08 => Key::Char('x'),
+                byte => Key::Char((byte as char).to_ascii_lowercase())
+            };
+
+            if sender.send(key).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// The game on screen and what the player is doing with it
+struct Interface {
+    game: Game,
+    engine: Option<Colour>,
+    limits: SearchLimits,
+    flipped: bool,
+    cursor: (i8, i8),
+    selected: Option<(i8, i8)>,
+    targets: Vec<(i8, i8)>,
+    promotion: Option<Move>,
+    thinking: Option<(usize, Receiver<SearchResult>)>,
+    message: String
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut interface = Interface {
+        game: Game::new(),
+        engine: None,
+        limits: SearchLimits::time(Duration::from_secs(2)),
+        flipped: false,
+        cursor: (4, 6),
+        selected: None,
+        targets: Vec::new(),
+        promotion: None,
+        thinking: None,
+        message: String::new()
+    };
+    let mut control = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = || args.get(i + 1).cloned().unwrap_or_else(|| exit_with(USAGE));
+        match args[i].as_str() {
+            "--engine" => {
+                interface.engine = Some(match value().as_str() {
+                    "white" => Colour::White,
+                    "black" => Colour::Black,
+                    _ => exit_with(USAGE)
+                });
+                i += 1;
+            }
+            "--time" => {
+                let seconds: f64 = value().parse().ok().filter(|seconds: &f64| *seconds > 0.0).unwrap_or_else(|| exit_with(USAGE));
+                interface.limits = SearchLimits::time(Duration::from_secs_f64(seconds));
+                i += 1;
+            }
+            "--clock" => {
+                control = Some(TimeControl::from_pgn_tag(&value()).unwrap_or_else(|| exit_with("Invalid time control")));
+                i += 1;
+            }
+            "--fen" => {
+                interface.game = Game::from_fen(&value()).unwrap_or_else(|| exit_with("Invalid FEN"));
+                i += 1;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => exit_with(USAGE)
+        }
+        i += 1;
+    }
+
+    if let Some(control) = control {
+        interface.game.set_clock(Clock::new(control));
+    }
+    interface.flipped = interface.engine == Some(Colour::White);
+    if interface.flipped {
+        interface.cursor = (4, 1);
+    }
+
+    let terminal = RawTerminal::enable().unwrap_or_else(|| exit_with("chess-tui needs a terminal, stty could not set raw mode"));
+    let keys = spawn_key_reader();
+
+    loop {
+        interface.game.check_time();
+        interface.poll_engine();
+        interface.draw();
+
+        match keys.recv_timeout(TICK) {
+            Ok(Key::Char('q')) if interface.promotion.is_none() => break,
+            Ok(key) => interface.handle(key),
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break
+        }
+    }
+
+    drop(terminal);
+}
+
+impl Interface {
+    fn handle(&mut self, _key: Key) {
+        self.message.clear();
+
+        if let Some(_move) = self.promotion {
+            let piece = match _key {
+                Key::Char('q') | Key::Pick => Some(PieceType::Queen),
+                Key::Char('r') => Some(PieceType::Rook),
+                Key::Char('b') => Some(PieceType::Bishop),
+                Key::Char('n') => Some(PieceType::Knight),
+                _ => None
+            };
+            self.promotion = None;
+            match piece {
+                Some(piece) => self.play(Move { promotion: Some(piece), .._move }),
+                None => self.message = String::from("Promotion cancelled")
+            }
+            return;
+        }
+
+        let forward = if self.flipped {1} else {-1};
+        match _key {
+            Key::Up | Key::Char('k') => self.move_cursor(0, forward),
+            Key::Down | Key::Char('j') => self.move_cursor(0, -forward),
+            Key::Left | Key::Char('h') => self.move_cursor(forward, 0),
+            Key::Right | Key::Char('l') => self.move_cursor(-forward, 0),
+            Key::Pick => self.pick(),
+            Key::Char('x') => self.deselect(),
+            Key::Char('f') => self.flipped = !self.flipped,
+            Key::Char('u') => self.undo(),
+            Key::Char('n') => {
+                let clock = self.game.get_clock().map(|clock| Clock::new(clock.get_time_control().clone()));
+                self.game = Game::new();
+                if let Some(clock) = clock {
+                    self.game.set_clock(clock);
+                }
+                self.thinking = None;
+                self.deselect();
+            }
+            Key::Char('r') => {
+                let colour = self.game.get_active_colour();
+                self.message = match self.game.resign(colour) {
+                    Some(_) => format!("{} resigns", colour_name(colour)),
+                    None => String::from("The game is already over")
+                };
+            }
+            Key::Char('d') => self.draw_offer(),
+            _ => ()
+        }
+    }
+
+    /// Moves the cursor by files and rows, staying on the board
+    fn move_cursor(&mut self, _files: i8, _rows: i8) {
+        self.cursor = ((self.cursor.0 - _files).clamp(0, 7), (self.cursor.1 + _rows).clamp(0, 7));
+    }
+
+    /// Picks up the piece under the cursor, or moves the picked up piece to the cursor
+    fn pick(&mut self) {
+        if self.game.is_over() || self.is_engine_turn() {
+            return;
+        }
+
+        let board = self.game.get_board();
+        let own_piece = !board.is_empty(self.cursor) && board.piece_at(self.cursor).get_colour() == self.game.get_active_colour();
+
+        match self.selected {
+            Some(from) if self.targets.contains(&self.cursor) => {
+                let _move = Move::new(from, self.cursor);
+                let piece = board.piece_at(from);
+                if piece.get_type() == PieceType::Pawn && (self.cursor.1 == 0 || self.cursor.1 == 7) {
+                    self.promotion = Some(_move);
+                    self.message = String::from("Promote to (q)ueen, (r)ook, (b)ishop or k(n)ight");
+                }
+                else {
+                    self.play(_move);
+                }
+            }
+            _ if own_piece => {
+                let square = Board::num_to_filerank(&self.cursor);
+                self.targets = self.game.get_possible_moves(square).unwrap_or_default().iter()
+                    .map(Board::filerank_to_num)
+                    .collect();
+                self.selected = Some(self.cursor);
+                if self.targets.is_empty() {
+                    self.message = String::from("That piece cannot move");
+                }
+            }
+            Some(_) => self.deselect(),
+            None => ()
+        }
+    }
+
+    fn deselect(&mut self) {
+        self.selected = None;
+        self.targets.clear();
+    }
+
+    fn play(&mut self, _move: Move) {
+        let san = self.game.move_to_san(&_move);
+        match self.game.play_move(_move) {
+            Some(_) => self.message = san,
+            None => self.message = String::from("Illegal move")
+        }
+        self.deselect();
+    }
+
+    fn is_engine_turn(&self) -> bool {
+        self.engine == Some(self.game.get_active_colour()) && !self.game.is_over()
+    }
+
+    /// Starts the engine when it is to move and plays its move once the search is done
+    fn poll_engine(&mut self) {
+        if let Some((ply, receiver)) = &self.thinking {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    // The result is stale if the game changed while the engine was thinking
+                    if *ply == self.game.get_history().len() && self.is_engine_turn() {
+                        if let Some(best_move) = result.best_move {
+                            self.message = format!("{} plays {}", colour_name(self.game.get_active_colour()), self.game.move_to_san(&best_move));
+                            self.game.play_move(best_move);
+                        }
+                    }
+                    self.thinking = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => self.thinking = None
+            }
+        }
+
+        if self.thinking.is_none() && self.is_engine_turn() {
+            let mut limits = self.limits;
+            // With a clock the engine spends a share of its remaining time
+            if let Some(clock) = self.game.get_clock() {
+                let share = clock.get_time_left(self.game.get_active_colour()) / 30;
+                limits.time = Some(limits.time.map_or(share, |time| time.min(share)));
+            }
+
+            let game = self.game.clone();
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || sender.send(game.search(limits)).ok());
+            self.thinking = Some((self.game.get_history().len(), receiver));
+        }
+    }
+
+    /// Takes back the last move, against the engine the player's last move too
+    fn undo(&mut self) {
+        let mut game = match self.game.take_back() {
+            Some(game) => game,
+            None => {
+                self.message = String::from("There is no move to undo");
+                return;
+            }
+        };
+        if self.engine == Some(game.get_active_colour()) && !game.get_history().is_empty() {
+            game = game.take_back().expect("a move was played");
+        }
+
+        self.game = game;
+        self.thinking = None;
+        self.deselect();
+    }
+
+    /// Claims a draw if possible, otherwise offers one or accepts the opponent's offer
+    fn draw_offer(&mut self) {
+        let colour = self.game.get_active_colour();
+
+        if let Some(reason) = self.game.get_claimable_draw() {
+            self.game.claim_draw();
+            self.message = format!("Draw claimed by {:?}", reason);
+            return;
+        }
+
+        let accepting = self.game.get_draw_offer() == Some(colour.opposite());
+        if !self.game.offer_draw(colour) {
+            return;
+        }
+        self.message = if accepting {format!("{} accepts the draw", colour_name(colour))} else {format!("{} offers a draw", colour_name(colour))};
+
+        // The engine takes a draw only when it is losing, the evaluation is from the side to move
+        if !accepting && self.engine == Some(colour.opposite()) {
+            if self.game.evaluate() > 200 {
+                self.game.accept_draw(colour.opposite());
+                self.message = String::from("The engine accepts the draw");
+            }
+            else {
+                self.game.decline_draw(colour.opposite());
+                self.message = String::from("The engine declines the draw");
+            }
+        }
+    }
+
+    /// Draws the whole screen, the board with the clocks above and below it and the moves on the right
+    fn draw(&self) {
+        let mut screen = String::with_capacity(8192);
+        // Lines are overwritten in place and cleared to their end, clearing the whole screen flickers
+        screen.push_str("\x1b[H");
+
+        let (top, bottom) = if self.flipped {(Colour::White, Colour::Black)} else {(Colour::Black, Colour::White)};
+        let files = if self.flipped {"   h  g  f  e  d  c  b  a "} else {"   a  b  c  d  e  f  g  h "};
+
+        let mut left: Vec<String> = Vec::with_capacity(12);
+        left.push(self.player_line(top));
+        left.push(String::from(files));
+        for line in 0..8 {
+            let row = if self.flipped {7 - line} else {line};
+            let mut text = format!("{} ", 8 - row);
+            for column in 0..8 {
+                let col = if self.flipped {7 - column} else {column};
+                text.push_str(&self.square((col, row)));
+            }
+            text.push_str(&format!(" {}", 8 - row));
+            left.push(text);
+        }
+        left.push(String::from(files));
+        left.push(self.player_line(bottom));
+
+        let right = self.move_list(left.len());
+        for (index, line) in left.iter().enumerate() {
+            screen.push_str(line);
+            if let Some(moves) = right.get(index) {
+                screen.push_str("    ");
+                screen.push_str(moves);
+            }
+            screen.push_str("\x1b[K\r\n");
+        }
+
+        screen.push_str("\x1b[K\r\n");
+        screen.push_str(&self.status());
+        screen.push_str("\x1b[K\r\n");
+        screen.push_str(&format!("\x1b[2m{}\x1b[0m\x1b[J", HELP));
+
+        print!("{}", screen);
+        io::stdout().flush().ok();
+    }
+
+    /// Returns a square with its piece, coloured by what is highlighted on it
+    fn square(&self, _square: (i8, i8)) -> String {
+        let board = self.game.get_board();
+        let light = (_square.0 + _square.1) % 2 == 0;
+        let pick = |(light_colour, dark_colour): (u8, u8)| if light {light_colour} else {dark_colour};
+
+        let last_move = self.game.get_history().last();
+        let in_check = self.game.get_game_state() == GameState::Check
+            && !board.is_empty(_square)
+            && board.piece_at(_square).get_piece_data() == (self.game.get_active_colour(), PieceType::King);
+
+        let background = if _square == self.cursor {CURSOR}
+            else if Some(_square) == self.selected {SELECTED}
+            else if in_check {CHECK}
+            else if self.targets.contains(&_square) {pick(TARGET)}
+            else if last_move.is_some_and(|last| last.from == _square || last.to == _square) {pick(LAST_MOVE)}
+            else {pick((LIGHT, DARK))};
+
+        let (foreground, icon) = if board.is_empty(_square) {
+            (30, if self.targets.contains(&_square) {'·'} else {' '})
+        }
+        else {
+            let piece = board.piece_at(_square);
+            (if piece.get_colour() == Colour::White {97} else {30}, piece.get_icon().to_ascii_uppercase())
+        };
+
+        format!("\x1b[48;5;{};1;{}m {} \x1b[0m", background, foreground, icon)
+    }
+
+    /// Returns the name of a player with their clock, the player to move is marked
+    fn player_line(&self, _colour: Colour) -> String {
+        let marker = if self.game.get_active_colour() == _colour && !self.game.is_over() {"●"} else {" "};
+        let time = self.game.get_clock().map(|clock| format_time(clock.get_time_left(_colour))).unwrap_or_default();
+        let engine = if self.engine == Some(_colour) {" (engine)"} else {""};
+        format!("  {} {:<18} {:>8}", marker, format!("{}{}", colour_name(_colour), engine), time)
+    }
+
+    /// Returns the last moves in SAN, one line per move number, as many as fit in the given height
+    fn move_list(&self, _height: usize) -> Vec<String> {
+        let mut game = self.game.get_start();
+
+        let mut lines: Vec<String> = Vec::new();
+        for _move in self.game.get_history() {
+            let san = game.move_to_san(_move);
+            match game.get_active_colour() {
+                Colour::White => lines.push(format!("{:>3}. {:<8}", game.get_fullmove_number(), san)),
+                Colour::Black if lines.is_empty() => lines.push(format!("{:>3}. {:<8}{}", game.get_fullmove_number(), "...", san)),
+                Colour::Black => lines.last_mut().expect("a line was pushed").push_str(&san)
+            }
+            game.play_move(*_move);
+        }
+
+        let mut list = vec![String::from("\x1b[1mMoves\x1b[0m")];
+        let shown = _height - 1;
+        list.extend(lines.split_off(lines.len().saturating_sub(shown)));
+        list
+    }
+
+    /// Returns the line under the board, the result, a message or whose turn it is
+    fn status(&self) -> String {
+        if let Some(termination) = self.game.get_termination() {
+            return format!("\x1b[1mGame over: {} ({:?})\x1b[0m  {}", termination.get_result(), termination.reason, self.message);
+        }
+
+        let mut status = format!("{} to move", colour_name(self.game.get_active_colour()));
+        if self.game.get_game_state() == GameState::Check {
+            status.push_str(", check");
+        }
+        if self.thinking.is_some() {
+            status.push_str(", thinking...");
+        }
+        if let Some(colour) = self.game.get_draw_offer() {
+            status.push_str(&format!(", {} offers a draw", colour_name(colour)));
+        }
+        if !self.message.is_empty() {
+            status.push_str("  ");
+            status.push_str(&self.message);
+        }
+        status
+    }
+}
+
+/// Formats a time as m:ss, or h:mm:ss from an hour on, with tenths under ten seconds
+fn format_time(_time: Duration) -> String {
+    let seconds = _time.as_secs();
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+    else if seconds >= 10 {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+    else {
+        format!("0:{:02}.{}", seconds, _time.subsec_millis() / 100)
+    }
+}
+
+fn colour_name(_colour: Colour) -> &'static str {
+    match _colour {
+        Colour::White => "White",
+        Colour::Black => "Black"
+    }
+}
+
+/// Prints a message and exits with an error code
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
@@ -0,0 +1,359 @@
+//! Plays chess in the terminal, two players on one keyboard or against the engine.
+//!
+//! Usage: `chess [--engine <white|black>] [--time <seconds>] [--fen <fen>] [--pgn <file.pgn>] [--unicode] [--colour]`
+
+use azeezd_chess::chess::moves::Move;
+use azeezd_chess::chess::piece_data::{Colour, PieceType};
+use azeezd_chess::pgn::Pgn;
+use azeezd_chess::render::{Glyphs, Renderer, Shading};
+use azeezd_chess::search::SearchLimits;
+use azeezd_chess::{Game, GameState};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: chess [--engine <white|black>] [--time <seconds>] [--fen <fen>] [--pgn <file.pgn>] [--unicode] [--colour]";
+
+const HELP: &str = "\
+Moves can be given in SAN (Nf3, exd5, O-O, e8=Q), UCI (g1f3, e7e8q) or as squares (g1 f3, g1-f3).
+Commands:
+  help                 Shows this help
+  undo                 Takes back the last move, or the last move of each side against the engine
+  flip                 Turns the board around
+  fen [<fen>]          Shows the FEN of the position, or starts a game from a FEN
+  save <file.pgn>      Saves the game as PGN
+  load <file.pgn>      Loads the first game of a PGN file
+  new                  Starts a new game
+  engine <white|black|off>  Lets the engine play a colour, or stops it
+  resign               Resigns the game for the side to move
+  draw                 Claims a draw if one can be claimed, otherwise offers or accepts one
+  quit                 Leaves the program";
+
+/// The game being played and how it is shown
+struct Session {
+    game: Game,
+    engine: Option<Colour>,
+    limits: SearchLimits,
+    renderer: Renderer
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut session = Session {
+        game: Game::new(),
+        engine: None,
+        limits: SearchLimits::time(Duration::from_secs(2)),
+        renderer: Renderer::new()
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = || args.get(i + 1).cloned().unwrap_or_else(|| exit_with(USAGE));
+        match args[i].as_str() {
+            "--engine" => {
+                session.engine = Some(parse_colour(&value()).unwrap_or_else(|| exit_with(USAGE)));
+                i += 1;
+            }
+            "--time" => {
+                let seconds: f64 = value().parse().ok().filter(|seconds: &f64| *seconds > 0.0).unwrap_or_else(|| exit_with(USAGE));
+                session.limits = SearchLimits::time(Duration::from_secs_f64(seconds));
+                i += 1;
+            }
+            "--fen" => {
+                session.game = Game::from_fen(&value()).unwrap_or_else(|| exit_with("Invalid FEN"));
+                i += 1;
+            }
+            "--pgn" => {
+                session.game = load_pgn(&value()).unwrap_or_else(|error| exit_with(&error));
+                i += 1;
+            }
+            "--unicode" => session.renderer.glyphs = Glyphs::Unicode,
+            "--colour" => session.renderer.shading = Shading::Ansi,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => exit_with(USAGE)
+        }
+        i += 1;
+    }
+
+    if session.engine == Some(Colour::White) {
+        session.renderer.orientation = Colour::Black;
+    }
+    println!("Type a move or \"help\" for the commands.");
+    session.show();
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        if session.is_engine_turn() {
+            session.play_engine_move();
+            continue;
+        }
+
+        print!("{}> ", colour_name(session.game.get_active_colour()));
+        io::stdout().flush().ok();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break
+        };
+
+        match session.run(line.trim()) {
+            Ok(true) => session.show(),
+            Ok(false) => (),
+            Err(Quit) => break
+        }
+    }
+}
+
+/// Returned by `Session::run` to leave the program
+struct Quit;
+
+impl Session {
+    /// Runs a command or plays a move. Returns true if the board should be shown again
+    fn run(&mut self, _input: &str) -> Result<bool, Quit> {
+        let mut words = _input.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return Ok(false)
+        };
+        let argument = _input[command.len()..].trim();
+
+        match command.to_ascii_lowercase().as_str() {
+            "quit" | "exit" => return Err(Quit),
+            "help" | "?" => println!("{}", HELP),
+            "flip" => {
+                self.renderer.orientation = self.renderer.orientation.opposite();
+                return Ok(true);
+            }
+            "new" => {
+                self.game = Game::new();
+                return Ok(true);
+            }
+            "undo" => return Ok(self.undo()),
+            "fen" if argument.is_empty() => println!("{}", self.game.to_fen()),
+            "fen" => match Game::from_fen(argument) {
+                Some(game) => {
+                    self.game = game;
+                    return Ok(true);
+                }
+                None => println!("Invalid FEN: {}", argument)
+            },
+            "save" if argument.is_empty() => println!("Give the file to save to, e.g \"save game.pgn\""),
+            "save" => match fs::write(argument, self.game.to_pgn().to_string()) {
+                Ok(()) => println!("Saved to {}", argument),
+                Err(error) => println!("Could not write {}: {}", argument, error)
+            },
+            "load" if argument.is_empty() => println!("Give the file to load, e.g \"load game.pgn\""),
+            "load" => match load_pgn(argument) {
+                Ok(game) => {
+                    self.game = game;
+                    return Ok(true);
+                }
+                Err(error) => println!("{}", error)
+            },
+            "engine" => match argument.to_ascii_lowercase().as_str() {
+                "off" => {
+                    self.engine = None;
+                    println!("The engine stopped playing");
+                }
+                colour => match parse_colour(colour) {
+                    Some(colour) => {
+                        self.engine = Some(colour);
+                        println!("The engine plays {}", colour_name(colour));
+                    }
+                    None => println!("Use \"engine white\", \"engine black\" or \"engine off\"")
+                }
+            },
+            "resign" => {
+                let colour = self.game.get_active_colour();
+                if self.game.resign(colour).is_none() {
+                    println!("The game is already over");
+                    return Ok(false);
+                }
+                println!("{} resigns", colour_name(colour));
+                self.show_result();
+            }
+            "draw" => self.draw(),
+            _ => return Ok(self.play(_input))
+        }
+
+        Ok(false)
+    }
+
+    /// Plays a move given by the player, printing why it could not be played. Returns true if it was played
+    fn play(&mut self, _input: &str) -> bool {
+        if self.game.is_over() {
+            println!("The game is over, use \"undo\", \"new\" or \"load\" to continue");
+            return false;
+        }
+
+        let _move = match parse_move(&self.game, _input) {
+            Ok(_move) => _move,
+            Err(error) => {
+                println!("{}", error);
+                return false;
+            }
+        };
+
+        let san = self.game.move_to_san(&_move);
+        if self.game.play_move(_move).is_none() {
+            println!("Illegal move: {}", _input);
+            return false;
+        }
+
+        println!("{}", san);
+        self.show_result();
+        true
+    }
+
+    /// Returns true if the engine is to move in a game that is still going on
+    fn is_engine_turn(&self) -> bool {
+        self.engine == Some(self.game.get_active_colour()) && !self.game.is_over()
+    }
+
+    fn play_engine_move(&mut self) {
+        println!("Thinking...");
+        let result = self.game.search(self.limits);
+
+        match result.best_move {
+            Some(best_move) => {
+                println!("{} plays {}", colour_name(self.game.get_active_colour()), self.game.move_to_san(&best_move));
+                self.game.play_move(best_move);
+            }
+            // Without legal moves the game has ended, give the turn back so the player can use the commands
+            None => self.engine = None
+        }
+
+        self.show();
+        self.show_result();
+    }
+
+    /// Takes back the last move, against the engine the player's last move too. Returns true if a move was taken back
+    fn undo(&mut self) -> bool {
+        let mut game = match self.game.take_back() {
+            Some(game) => game,
+            None => {
+                println!("There is no move to undo");
+                return false;
+            }
+        };
+        if self.engine == Some(game.get_active_colour()) && !game.get_history().is_empty() {
+            game = game.take_back().expect("a move was played");
+        }
+
+        self.game = game;
+        true
+    }
+
+    /// Claims a draw if possible, otherwise offers one, which accepts a standing offer of the opponent
+    fn draw(&mut self) {
+        let colour = self.game.get_active_colour();
+
+        if let Some(reason) = self.game.get_claimable_draw() {
+            self.game.claim_draw();
+            println!("Draw claimed by {:?}", reason);
+            self.show_result();
+            return;
+        }
+
+        let accepting = self.game.get_draw_offer() == Some(colour.opposite());
+        if !self.game.offer_draw(colour) {
+            println!("The game is already over");
+            return;
+        }
+        if accepting {
+            println!("{} accepts the draw", colour_name(colour));
+            self.show_result();
+            return;
+        }
+
+        println!("{} offers a draw", colour_name(colour));
+
+        // The engine takes a draw only when it is losing, the evaluation is from the side to move
+        if self.engine == Some(colour.opposite()) {
+            if self.game.evaluate() > 200 {
+                self.game.accept_draw(colour.opposite());
+                println!("The engine accepts the draw");
+                self.show_result();
+            }
+            else {
+                self.game.decline_draw(colour.opposite());
+                println!("The engine declines the draw");
+            }
+        }
+    }
+
+    /// Prints the board from the side of the player with the last move marked
+    fn show(&mut self) {
+        self.renderer.marked = self.game.get_history().last().map_or(Vec::new(), |last| vec![last.from, last.to]);
+
+        println!();
+        print!("{}", self.renderer.display(self.game.get_board()));
+        println!();
+    }
+
+    /// Prints the result once the game has ended and warns about check
+    fn show_result(&self) {
+        match self.game.get_termination() {
+            Some(termination) => println!("Game over: {} ({:?})", termination.get_result(), termination.reason),
+            None if self.game.get_game_state() == GameState::Check => println!("Check!"),
+            None => ()
+        }
+    }
+}
+
+/// Reads a move as SAN, UCI or two squares. Pawn moves to the last rank without a piece promote to a queen
+fn parse_move(_game: &Game, _input: &str) -> Result<Move, String> {
+    let squares: String = _input.split(|c: char| c.is_whitespace() || c == '-').collect::<Vec<&str>>().concat().to_ascii_lowercase();
+
+    if let Some(_move) = Move::from_uci(&squares) {
+        let legal = _game.get_all_legal_moves();
+        let matching: Vec<&Move> = legal.iter().filter(|legal| legal.from == _move.from && legal.to == _move.to).collect();
+
+        if matching.is_empty() {
+            return Err(format!("Illegal move: {}", _input));
+        }
+        return match _move.promotion {
+            None if matching.len() == 1 => Ok(*matching[0]),
+            None => Ok(Move { promotion: Some(PieceType::Queen), .._move }),
+            Some(_) if matching.contains(&&_move) => Ok(_move),
+            Some(_) => Err(format!("{} is not a promotion", _input))
+        };
+    }
+
+    _game.san_to_move(_input).ok_or_else(|| format!("Unknown move or command: {}, type \"help\" for the commands", _input))
+}
+
+fn load_pgn(_path: &str) -> Result<Game, String> {
+    let text = fs::read_to_string(_path).map_err(|error| format!("Could not read {}: {}", _path, error))?;
+    let pgn = Pgn::parse(&text).ok_or_else(|| format!("{} holds no PGN game", _path))?;
+    pgn.to_game().ok_or_else(|| format!("{} holds an illegal move or position", _path))
+}
+
+fn parse_colour(_name: &str) -> Option<Colour> {
+    match _name {
+        "white" | "w" => Some(Colour::White),
+        "black" | "b" => Some(Colour::Black),
+        _ => None
+    }
+}
+
+fn colour_name(_colour: Colour) -> &'static str {
+    match _colour {
+        Colour::White => "White",
+        Colour::Black => "Black"
+    }
+}
+
+/// Prints a message and exits with an error code
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
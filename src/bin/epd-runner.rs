@@ -0,0 +1,118 @@
+//! Runs an EPD test suite such as WAC, STS or Bratko-Kopec through the search and reports every position and the totals.
+//!
+//! Usage: `epd-runner <file.epd> [--time <seconds>] [--depth <plies>]`
+
+use azeezd_chess::epd::Epd;
+use azeezd_chess::search::SearchLimits;
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: epd-runner <file.epd> [--time <seconds>] [--depth <plies>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut path = None;
+    let mut limits = SearchLimits::time(Duration::from_secs(1));
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--time" => {
+                let seconds: f64 = args.get(i + 1).and_then(|value| value.parse().ok()).unwrap_or_else(|| exit_with(USAGE));
+                limits.time = Some(Duration::from_secs_f64(seconds));
+                i += 1;
+            }
+            "--depth" => {
+                limits.max_depth = args.get(i + 1).and_then(|value| value.parse().ok()).unwrap_or_else(|| exit_with(USAGE));
+                i += 1;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if path.is_none() => path = Some(args[i].clone()),
+            _ => exit_with(USAGE)
+        }
+        i += 1;
+    }
+
+    let path = path.unwrap_or_else(|| exit_with(USAGE));
+    let contents = fs::read_to_string(&path).unwrap_or_else(|error| exit_with(&format!("Could not read {}: {}", path, error)));
+
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let name = format!("line {}", line_number + 1);
+        let epd = match Epd::parse(line) {
+            Some(epd) => epd,
+            None => {
+                println!("skip  {:<16} could not parse the line", name);
+                skipped += 1;
+                continue;
+            }
+        };
+        let name = epd.get_id().map_or(name, String::from);
+
+        let game = match epd.to_game() {
+            Some(game) => game,
+            None => {
+                println!("skip  {:<16} invalid position", name);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let result = game.search(limits);
+        let best_move = match result.best_move {
+            Some(best_move) => best_move,
+            None => {
+                println!("skip  {:<16} no legal moves", name);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let score = match result.mate_in() {
+            Some(moves) => format!("mate {}", moves),
+            None => format!("{:+.2}", result.score as f64 / 100.0)
+        };
+        let expected: Vec<String> = ["bm", "am", "dm"].iter()
+            .filter_map(|opcode| epd.get_operation(opcode).map(|operands| format!("{} {}", opcode, operands.join(" "))))
+            .collect();
+        let details = format!("found {:<7} expected {:<16} depth {:>2} nodes {:>9} score {}",
+            game.move_to_san(&best_move), expected.join(", "), result.depth, result.nodes, score);
+
+        match epd.is_solved_by(&best_move, result.mate_in()) {
+            Some(true) => {
+                println!("pass  {:<16} {}", name, details);
+                passed += 1;
+            }
+            Some(false) => {
+                println!("FAIL  {:<16} {}", name, details);
+                failed += 1;
+            }
+            None => {
+                println!("skip  {:<16} unreadable bm/am moves", name);
+                skipped += 1;
+            }
+        }
+    }
+
+    let total = passed + failed;
+    println!();
+    println!("Solved {}/{} ({:.1}%), {} skipped", passed, total,
+        if total > 0 {100.0 * passed as f64 / total as f64} else {0.0}, skipped);
+}
+
+/// Prints a message and exits with an error code
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
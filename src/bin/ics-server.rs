@@ -0,0 +1,50 @@
+//! Hosts games for telnet clients in the style of the Internet Chess Servers, see `azeezd_chess::ics` for the commands.
+//!
+//! Usage: `ics-server [--host <address>] [--port <port>]`
+
+use azeezd_chess::ics::IcsServer;
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: ics-server [--host <address>] [--port <port>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut host = String::from("127.0.0.1");
+    let mut port: u16 = 5000;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--host" => {
+                host = args.get(i + 1).cloned().unwrap_or_else(|| exit_with(USAGE));
+                i += 1;
+            }
+            "--port" => {
+                port = args.get(i + 1).and_then(|value| value.parse().ok()).unwrap_or_else(|| exit_with(USAGE));
+                i += 1;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => exit_with(USAGE)
+        }
+        i += 1;
+    }
+
+    let listener = TcpListener::bind((host.as_str(), port)).unwrap_or_else(|error| exit_with(&format!("Could not listen on {}:{}: {}", host, port, error)));
+    println!("Listening on {}, connect with telnet", listener.local_addr().map_or_else(|_| format!("{}:{}", host, port), |address| address.to_string()));
+
+    if let Err(error) = Arc::new(IcsServer::new()).serve(listener) {
+        exit_with(&format!("The server stopped: {}", error));
+    }
+}
+
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
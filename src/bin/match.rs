@@ -0,0 +1,506 @@
+//! Plays a match between two UCI engines running as local processes. Every move is checked with the rules of `Game`:
+//! an illegal move, a crash or a flag fall loses, checkmate, stalemate, repetitions, the fifty move rule and
+//! insufficient material end the game. Each opening is played twice with the colours reversed.
+//!
+//! Usage: `match --engine <command> [--option <name>=<value>]... --engine <command> [--option <name>=<value>]...
+//! [--tc <seconds>[+<increment>]] [--openings <file.epd|file.pgn>] [--rounds <pairs>] [--pgn <file.pgn>]
+//! [--sprt elo0=<elo>,elo1=<elo>,alpha=<p>,beta=<p>]`
+//!
+//! Options follow the engine they are for. Openings are used in order and start over once all were played,
+//! the games are written to `match.pgn` unless another file is given. The Elo difference of the first engine is
+//! printed after every game. With `--sprt` the match stops once the test accepts a hypothesis, it runs until then
+//! unless a number of rounds is given
+
+use azeezd_chess::chess::moves::Move;
+use azeezd_chess::chess::piece_data::Colour;
+use azeezd_chess::clock::{Clock, TimeControl};
+use azeezd_chess::epd::Epd;
+use azeezd_chess::pgn::Pgn;
+use azeezd_chess::stats::{Pentanomial, Results, Sprt, SprtStatus, Trinomial};
+use azeezd_chess::termination::TerminationReason;
+use azeezd_chess::Game;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{self, Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "Usage: match --engine <command> [--option <name>=<value>]... --engine <command> [--option <name>=<value>]... \
+[--tc <seconds>[+<increment>]] [--openings <file.epd|file.pgn>] [--rounds <pairs>] [--pgn <file.pgn>] \
+[--sprt elo0=<elo>,elo1=<elo>,alpha=<p>,beta=<p>]";
+
+/// How long an engine may take to start, to answer `isready` or to stop searching
+const RESPONSE_TIME: Duration = Duration::from_secs(10);
+
+/// How long past its time an engine is waited for before the game is lost on time without its move
+const TIME_GRACE: Duration = Duration::from_secs(1);
+
+/// ## EngineConfig
+/// ### Type: `struct`
+/// How to start an engine.
+/// ### Members:
+/// - `command`: The program and its arguments, separated by whitespace
+/// - `options`: The UCI options to set after starting it
+struct EngineConfig {
+    command: String,
+    options: Vec<(String, String)>
+}
+
+/// ## Engine
+/// ### Type: `struct`
+/// A running UCI engine.
+/// ### Members:
+/// - `name`: The name the engine gave with `id name`, or its command
+/// - `child`: The engine process
+/// - `input`: The standard input of the engine
+/// - `lines`: The lines the engine wrote, read by a thread so waiting for them can time out
+/// - `responsive`: False once the engine exited or stopped answering, it is restarted before the next game
+struct Engine {
+    name: String,
+    child: Child,
+    input: ChildStdin,
+    lines: Receiver<String>,
+    responsive: bool
+}
+
+/// What an engine answered to `go`
+enum Answer {
+    /// The move in UCI notation, as written by the engine
+    Move(String),
+    /// No move came before the deadline
+    TimedOut,
+    /// The engine exited
+    Gone
+}
+
+impl Engine {
+    /// ## `start`
+    /// Starts an engine, waits for `uciok`, sets its options and waits until it is ready
+    /// ### Returns
+    /// Returns an error with the reason if the engine could not be started or did not answer
+    fn start(_config: &EngineConfig) -> Result<Engine, String> {
+        let mut parts = _config.command.split_whitespace();
+        let program = parts.next().ok_or("An engine command is empty")?;
+        let mut child = Command::new(program).args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Could not start {}: {}", _config.command, error))?;
+
+        let input = child.stdin.take().ok_or("The engine has no input")?;
+        let output = child.stdout.take().ok_or("The engine has no output")?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                match line {
+                    Ok(line) if sender.send(line.trim().to_string()).is_ok() => (),
+                    _ => break
+                }
+            }
+        });
+
+        let mut engine = Engine { name: _config.command.clone(), child, input, lines, responsive: true };
+        engine.send("uci");
+        let failed = |_reason: &str| format!("{} {}", _config.command, _reason);
+        loop {
+            let line = engine.lines.recv_timeout(RESPONSE_TIME).map_err(|_| failed("did not answer \"uci\""))?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_string();
+            }
+            if line == "uciok" {
+                break;
+            }
+        }
+
+        for (name, value) in &_config.options {
+            engine.send(&format!("setoption name {} value {}", name, value));
+        }
+        if !engine.synchronise() {
+            return Err(failed("did not answer \"isready\""));
+        }
+        Ok(engine)
+    }
+
+    /// Writes a command to the engine, an engine that cannot be written to is no longer responsive
+    fn send(&mut self, _command: &str) {
+        if writeln!(self.input, "{}", _command).and_then(|_| self.input.flush()).is_err() {
+            self.responsive = false;
+        }
+    }
+
+    /// Sends `isready` and waits for `readyok`, returns false if it did not come
+    fn synchronise(&mut self) -> bool {
+        self.send("isready");
+        let deadline = Instant::now() + RESPONSE_TIME;
+        while self.responsive {
+            match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) if line == "readyok" => return true,
+                Ok(_) => (),
+                Err(_) => self.responsive = false
+            }
+        }
+        false
+    }
+
+    /// Tells the engine that the next position is from a new game
+    fn new_game(&mut self) {
+        self.send("ucinewgame");
+        self.synchronise();
+    }
+
+    /// ## `go`
+    /// Sends a position and a `go` command and waits for the best move until a deadline.
+    /// An engine that runs past the deadline is stopped, and is no longer responsive if it does not stop
+    fn go(&mut self, _position: &str, _go: &str, _deadline: Duration) -> Answer {
+        self.send(_position);
+        self.send(_go);
+        let start = Instant::now();
+
+        while self.responsive {
+            match self.lines.recv_timeout(_deadline.saturating_sub(start.elapsed())) {
+                Ok(line) => if let Some(best) = line.strip_prefix("bestmove") {
+                    return Answer::Move(best.split_whitespace().next().unwrap_or_default().to_string());
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    self.stop();
+                    return Answer::TimedOut;
+                }
+                Err(RecvTimeoutError::Disconnected) => self.responsive = false
+            }
+        }
+        Answer::Gone
+    }
+
+    /// Stops a search that ran out of time and drops its best move
+    fn stop(&mut self) {
+        self.send("stop");
+        let deadline = Instant::now() + RESPONSE_TIME;
+        while self.responsive {
+            match self.lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) if line.starts_with("bestmove") => return,
+                Ok(_) => (),
+                Err(_) => self.responsive = false
+            }
+        }
+    }
+}
+
+impl Drop for Engine {
+    /// Asks the engine to quit and kills it if it has not after a moment
+    fn drop(&mut self) {
+        self.send("quit");
+        for _ in 0..20 {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// ## Opening
+/// ### Type: `struct`
+/// A position the games of a pair start from.
+/// ### Members:
+/// - `game`: The game with the moves of the opening played
+/// - `start_fen`: The FEN of the position before the moves of the opening, sent to the engines with every move
+struct Opening {
+    game: Game,
+    start_fen: String
+}
+
+/// ## Outcome
+/// ### Type: `struct`
+/// How a game of the match ended.
+/// ### Members:
+/// - `result`: The PGN result
+/// - `reason`: Why, e.g `"White mates"` or `"Black makes an illegal move: e1g1"`
+/// - `termination`: The value of the PGN `Termination` tag
+struct Outcome {
+    result: &'static str,
+    reason: String,
+    termination: &'static str
+}
+
+impl Outcome {
+    /// The game is lost by a colour for something the rules of `Game` do not cover
+    fn loss(_colour: Colour, _reason: String, _termination: &'static str) -> Outcome {
+        Outcome { result: if _colour == Colour::White {"0-1"} else {"1-0"}, reason: _reason, termination: _termination }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut configs: Vec<EngineConfig> = Vec::new();
+    let mut control = (Duration::from_secs(10), Duration::from_millis(100));
+    let mut openings_path = None;
+    let mut rounds = None;
+    let mut pgn_path = String::from("match.pgn");
+    let mut sprt = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = || args.get(i + 1).cloned().unwrap_or_else(|| exit_with(USAGE));
+        match args[i].as_str() {
+            "--engine" => configs.push(EngineConfig { command: value(), options: Vec::new() }),
+            "--option" => {
+                let option = value();
+                let (name, option_value) = option.split_once('=').unwrap_or_else(|| exit_with(USAGE));
+                let config = configs.last_mut().unwrap_or_else(|| exit_with("--option must follow the --engine it is for"));
+                config.options.push((name.trim().to_string(), option_value.trim().to_string()));
+            }
+            "--tc" => control = parse_time_control(&value()).unwrap_or_else(|| exit_with(USAGE)),
+            "--openings" => openings_path = Some(value()),
+            "--rounds" => rounds = Some(value().parse::<usize>().ok().filter(|rounds| *rounds > 0).unwrap_or_else(|| exit_with(USAGE))),
+            "--pgn" => pgn_path = value(),
+            "--sprt" => sprt = Some(parse_sprt(&value()).unwrap_or_else(|| exit_with(USAGE))),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => exit_with(USAGE)
+        }
+        i += 2;
+    }
+    if configs.len() != 2 {
+        exit_with(USAGE);
+    }
+
+    let openings = match &openings_path {
+        Some(path) => load_openings(path).unwrap_or_else(|error| exit_with(&error)),
+        None => vec![Opening { game: Game::new(), start_fen: Game::new().to_fen() }]
+    };
+    let rounds = rounds.unwrap_or(if sprt.is_some() {usize::MAX} else {openings.len()});
+
+    let mut engines: Vec<Engine> = configs.iter().map(|config| Engine::start(config).unwrap_or_else(|error| exit_with(&error))).collect();
+    let mut names: Vec<String> = engines.iter().map(|engine| engine.name.clone()).collect();
+    if names[0] == names[1] {
+        names[1].push_str(" (2)");
+    }
+
+    let mut pgn_file = File::create(&pgn_path).unwrap_or_else(|error| exit_with(&format!("Could not create {}: {}", pgn_path, error)));
+    let time_control = format!("{}+{}", control.0.as_secs_f64(), control.1.as_secs_f64());
+
+    // The results of the first engine
+    let mut games = Trinomial::default();
+    let mut pairs = Pentanomial::default();
+    for round in 0..rounds {
+        let opening = &openings[round % openings.len()];
+        let mut pair_score = 0.0;
+
+        for (pair_game, white) in [0, 1].iter().enumerate() {
+            let number = round * 2 + pair_game + 1;
+            for (index, config) in configs.iter().enumerate() {
+                if !engines[index].responsive {
+                    eprintln!("Restarting {}", names[index]);
+                    engines[index] = Engine::start(config).unwrap_or_else(|error| exit_with(&error));
+                }
+            }
+
+            let (game, outcome) = play_game(&mut engines, *white, opening, control);
+            let (white_name, black_name) = (&names[*white], &names[1 - *white]);
+
+            let mut pgn = game.to_pgn();
+            pgn.set_tag("Event", "Engine match");
+            pgn.set_tag("Round", &format!("{}.{}", round + 1, pair_game + 1));
+            pgn.set_tag("White", white_name);
+            pgn.set_tag("Black", black_name);
+            pgn.set_tag("Result", outcome.result);
+            pgn.set_tag("TimeControl", &time_control);
+            pgn.set_tag("Termination", outcome.termination);
+            pgn.result = String::from(outcome.result);
+            if let Err(error) = writeln!(pgn_file, "{}", pgn).and_then(|_| pgn_file.flush()) {
+                exit_with(&format!("Could not write {}: {}", pgn_path, error));
+            }
+
+            let score = match (outcome.result, *white) {
+                ("1-0", 0) | ("0-1", 1) => 1.0,
+                ("1-0", _) | ("0-1", _) => 0.0,
+                _ => 0.5
+            };
+            games.add(score);
+            pair_score += score;
+
+            println!("Finished game {} ({} vs {}): {} {{{}}}", number, white_name, black_name, outcome.result, outcome.reason);
+            println!("Score of {} vs {}: {} - {} - {}  [{:.3}] {}", names[0], names[1], games.wins, games.losses, games.draws,
+                (games.wins as f64 + games.draws as f64 / 2.0) / number as f64, number);
+            if let Some(estimate) = games.estimate(0.95) {
+                println!("Elo difference: {}", estimate);
+            }
+        }
+
+        pairs.add(pair_score);
+        if let Some(sprt) = &sprt {
+            let (lower, upper) = sprt.get_bounds();
+            println!("SPRT: llr {:.3}, lbound {:.3}, ubound {:.3}", sprt.llr(&pairs), lower, upper);
+            match sprt.get_status(&pairs) {
+                SprtStatus::AcceptH0 => println!("SPRT: H0 ({} Elo) accepted", sprt.elo0),
+                SprtStatus::AcceptH1 => println!("SPRT: H1 ({} Elo) accepted", sprt.elo1),
+                SprtStatus::Continue => continue
+            }
+            break;
+        }
+    }
+
+    if let Some(estimate) = pairs.estimate(0.95) {
+        println!("Elo difference over {} pairs: {}", pairs.pairs.iter().sum::<u32>(), estimate);
+    }
+
+    println!("Games written to {}", pgn_path);
+}
+
+/// ## `play_game`
+/// Plays a game from an opening between two engines, checking every move and the clock with `Game`
+/// ### Parameters
+/// - `_engines: &mut [Engine]`: The two engines
+/// - `_white: usize`: The index of the engine playing white
+/// - `_opening: &Opening`: The position to start from
+/// - `_control: (Duration, Duration)`: The time of each player and the increment
+///
+/// ### Returns
+/// Returns the game as played and how it ended
+fn play_game(_engines: &mut [Engine], _white: usize, _opening: &Opening, _control: (Duration, Duration)) -> (Game, Outcome) {
+    let mut game = _opening.game.clone();
+    for engine in _engines.iter_mut() {
+        engine.new_game();
+    }
+
+    let start = if _opening.start_fen == Game::new().to_fen() {String::from("startpos")} else {format!("fen {}", _opening.start_fen)};
+    game.set_clock(Clock::new(TimeControl::fischer(_control.0, _control.1)));
+
+    loop {
+        game.claim_draw();
+        if let Some(outcome) = adjudicate(&game) {
+            return (game, outcome);
+        }
+
+        let colour = game.get_active_colour();
+        let index = if colour == Colour::White {_white} else {1 - _white};
+        let clock = game.get_clock().expect("the clock was set");
+        let time_left = clock.get_time_left(colour);
+
+        let moves: Vec<String> = game.get_history().iter().map(|_move| _move.to_uci()).collect();
+        let position = if moves.is_empty() {format!("position {}", start)} else {format!("position {} moves {}", start, moves.join(" "))};
+        let go = format!("go wtime {} btime {} winc {} binc {}", clock.get_time_left(Colour::White).as_millis(),
+            clock.get_time_left(Colour::Black).as_millis(), _control.1.as_millis(), _control.1.as_millis());
+
+        let side = if colour == Colour::White {"White"} else {"Black"};
+        match _engines[index].go(&position, &go, time_left + TIME_GRACE) {
+            Answer::Move(uci) => {
+                let legal = Move::from_uci(&uci).filter(|_move| game.get_all_legal_moves().contains(_move));
+                match legal {
+                    Some(_move) => {
+                        game.play_move(_move);
+                    }
+                    None => return (game, Outcome::loss(colour, format!("{} makes an illegal move: {}", side, uci), "rules infraction"))
+                }
+            }
+            Answer::TimedOut => {
+                game.check_time();
+            }
+            Answer::Gone => return (game, Outcome::loss(colour, format!("{} disconnects", side), "abandoned"))
+        }
+    }
+}
+
+/// Returns how the game ended if it did, with insufficient material to mate for both sides a draw
+fn adjudicate(_game: &Game) -> Option<Outcome> {
+    if let Some(termination) = _game.get_termination() {
+        let winner = if termination.winner == Some(Colour::White) {"White"} else {"Black"};
+        let reason = match termination.reason {
+            TerminationReason::Checkmate => format!("{} mates", winner),
+            TerminationReason::Stalemate => String::from("Draw by stalemate"),
+            TerminationReason::ThreefoldRepetition => String::from("Draw by 3-fold repetition"),
+            TerminationReason::FiftyMoveRule => String::from("Draw by fifty moves rule"),
+            TerminationReason::TimeForfeit if termination.winner.is_some() => format!("{} wins on time", winner),
+            TerminationReason::TimeForfeit => String::from("Draw by timeout vs insufficient mating material"),
+            TerminationReason::Resignation => format!("{} wins by resignation", winner),
+            TerminationReason::DrawAgreement => String::from("Draw by agreement"),
+            TerminationReason::VariantEnd if termination.winner.is_some() => format!("{} wins by variant rule", winner),
+            TerminationReason::VariantEnd => String::from("Draw by variant rule")
+        };
+        return Some(Outcome { result: termination.get_result(), reason, termination: termination.get_pgn_termination() });
+    }
+
+    let board = _game.get_board();
+    if !board.has_mating_material(Colour::White) && !board.has_mating_material(Colour::Black) {
+        return Some(Outcome { result: "1/2-1/2", reason: String::from("Draw by insufficient mating material"), termination: "normal" });
+    }
+    None
+}
+
+/// ## `load_openings`
+/// Reads the openings of an EPD file, one position per line, or of a PGN file, the moves of every game
+/// ### Returns
+/// Returns an error if the file cannot be read or holds no opening
+fn load_openings(_path: &str) -> Result<Vec<Opening>, String> {
+    let text = fs::read_to_string(_path).map_err(|error| format!("Could not read {}: {}", _path, error))?;
+
+    let mut openings = Vec::new();
+    if _path.to_ascii_lowercase().ends_with(".pgn") {
+        for (index, pgn) in Pgn::parse_all(&text).iter().enumerate() {
+            let start = pgn.get_start().ok_or_else(|| format!("Game {} of {} has an invalid FEN", index + 1, _path))?;
+            let start_fen = start.to_fen();
+            let mut game = start;
+            for san in &pgn.moves {
+                let _move = game.san_to_move(san).ok_or_else(|| format!("Game {} of {} has an illegal move: {}", index + 1, _path, san))?;
+                game.play_move(_move);
+            }
+            openings.push(Opening { game, start_fen });
+        }
+    }
+    else {
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let game = Epd::parse(line).and_then(|epd| epd.to_game()).ok_or_else(|| format!("Line {} of {} is not a valid EPD position", index + 1, _path))?;
+            let start_fen = game.to_fen();
+            openings.push(Opening { game, start_fen });
+        }
+    }
+
+    if openings.is_empty() {
+        return Err(format!("{} holds no openings", _path));
+    }
+    Ok(openings)
+}
+
+/// Reads SPRT parameters, e.g `"elo0=0,elo1=5"`, those left out keep the values of `Sprt::default`
+fn parse_sprt(_text: &str) -> Option<Sprt> {
+    let mut sprt = Sprt::default();
+    for parameter in _text.split(',') {
+        let (name, value) = parameter.split_once('=')?;
+        let value: f64 = value.trim().parse().ok().filter(|value: &f64| value.is_finite())?;
+        match name.trim() {
+            "elo0" => sprt.elo0 = value,
+            "elo1" => sprt.elo1 = value,
+            "alpha" if value > 0.0 && value < 1.0 => sprt.alpha = value,
+            "beta" if value > 0.0 && value < 1.0 => sprt.beta = value,
+            _ => return None
+        }
+    }
+    if sprt.elo0 >= sprt.elo1 {
+        return None;
+    }
+    Some(sprt)
+}
+
+/// Reads a time control of seconds with an optional increment, e.g `"60"` or `"10+0.1"`
+fn parse_time_control(_text: &str) -> Option<(Duration, Duration)> {
+    let (time, increment) = _text.split_once('+').unwrap_or((_text, "0"));
+    let time: f64 = time.parse().ok().filter(|time: &f64| *time > 0.0 && time.is_finite())?;
+    let increment: f64 = increment.parse().ok().filter(|increment: &f64| *increment >= 0.0 && increment.is_finite())?;
+    Some((Duration::from_secs_f64(time), Duration::from_secs_f64(increment)))
+}
+
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
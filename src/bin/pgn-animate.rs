@@ -0,0 +1,71 @@
+//! Turns the first game of a PGN file into an animated GIF, or a numbered PNG sequence when the output is a directory.
+//!
+//! Usage: `pgn-animate <game.pgn> <output.gif | directory> [--size <pixels>] [--delay <seconds>] [--black] [--no-coordinates]`
+
+use azeezd_chess::animation::Animation;
+use azeezd_chess::chess::piece_data::Colour;
+use azeezd_chess::pgn::Pgn;
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: pgn-animate <game.pgn> <output.gif | directory> [--size <pixels>] [--delay <seconds>] [--black] [--no-coordinates]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut animation = Animation::new();
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--size" => {
+                animation.size = args.get(i + 1).and_then(|value| value.parse().ok()).filter(|size| (8..=4096).contains(size)).unwrap_or_else(|| exit_with(USAGE));
+                i += 1;
+            }
+            "--delay" => {
+                let seconds: f64 = args.get(i + 1).and_then(|value| value.parse().ok()).filter(|seconds: &f64| *seconds >= 0.0).unwrap_or_else(|| exit_with(USAGE));
+                animation.delay = Duration::from_secs_f64(seconds);
+                i += 1;
+            }
+            "--black" => animation.orientation = Colour::Black,
+            "--no-coordinates" => animation.coordinates = false,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => paths.push(args[i].clone())
+        }
+        i += 1;
+    }
+
+    if paths.len() != 2 {
+        exit_with(USAGE);
+    }
+
+    let text = fs::read_to_string(&paths[0]).unwrap_or_else(|error| exit_with(&format!("Could not read {}: {}", paths[0], error)));
+    let game = Pgn::parse(&text)
+        .and_then(|pgn| pgn.to_game())
+        .unwrap_or_else(|| exit_with(&format!("{} holds no legal game", paths[0])));
+
+    let output = Path::new(&paths[1]);
+    if output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gif")) {
+        let file = File::create(output).unwrap_or_else(|error| exit_with(&format!("Could not create {}: {}", output.display(), error)));
+        animation.write_gif(&game, BufWriter::new(file)).unwrap_or_else(|error| exit_with(&format!("Could not write {}: {}", output.display(), error)));
+        println!("Wrote {} positions to {}", game.get_history().len() + 1, output.display());
+    }
+    else {
+        let written = animation.write_png_sequence(&game, output, "frame").unwrap_or_else(|error| exit_with(&format!("Could not write to {}: {}", output.display(), error)));
+        println!("Wrote {} frames to {}", written.len(), output.display());
+    }
+}
+
+/// Prints a message and exits with an error code
+fn exit_with(_message: &str) -> ! {
+    eprintln!("{}", _message);
+    process::exit(1)
+}
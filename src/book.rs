@@ -0,0 +1,305 @@
+use crate::chess::moves::Move;
+use crate::chess::piece_data::Colour;
+use crate::pgn::Pgn;
+use crate::Game;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::Path;
+
+/// ## BookEntry
+/// ### Type: `struct`
+/// One 16 byte entry of a Polyglot opening book, stored big-endian in the file.
+/// ### Members:
+/// - `key`: The Polyglot key of the position, see `Game::get_polyglot_key`
+/// - `move_data`: The move encoded as in `Game::encode_polyglot_move`
+/// - `weight`: How often the move should be chosen relative to the other moves of the position
+/// - `learn`: Learning data, unused by the reader
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub move_data: u16,
+    pub weight: u16,
+    pub learn: u32
+}
+
+impl BookEntry {
+    /// Reads an entry from its 16 bytes
+    pub fn from_bytes(_bytes: &[u8; 16]) -> BookEntry {
+        BookEntry {
+            key: u64::from_be_bytes(_bytes[0..8].try_into().unwrap()),
+            move_data: u16::from_be_bytes([_bytes[8], _bytes[9]]),
+            weight: u16::from_be_bytes([_bytes[10], _bytes[11]]),
+            learn: u32::from_be_bytes(_bytes[12..16].try_into().unwrap())
+        }
+    }
+
+    /// Returns the 16 bytes of the entry as they are stored in a book file
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[0..8].copy_from_slice(&self.key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&self.move_data.to_be_bytes());
+        bytes[10..12].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+/// ## Book
+/// ### Type: `struct`
+/// A Polyglot `.bin` opening book.
+/// ### Members:
+/// - `entries`: All entries of the book sorted by key
+pub struct Book {
+    entries: Vec<BookEntry>
+}
+
+impl Book {
+    /// ## `open`
+    /// Reads a Polyglot book from a file.
+    /// ### Returns
+    /// Returns an `io::Result<Book>`, an error of kind `InvalidData` if the file is not a whole number of entries
+    pub fn open<P: AsRef<Path>>(_path: P) -> io::Result<Book> {
+        Book::from_bytes(&fs::read(_path)?)
+    }
+
+    /// ## `from_bytes`
+    /// Reads a Polyglot book from the contents of a book file.
+    pub fn from_bytes(_bytes: &[u8]) -> io::Result<Book> {
+        if !_bytes.len().is_multiple_of(16) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Polyglot book size is not a multiple of 16 bytes"));
+        }
+
+        let mut entries: Vec<BookEntry> = _bytes.chunks_exact(16)
+            .map(|chunk| BookEntry::from_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        // Books are sorted by key already, sorting again keeps the lookup correct for books that are not
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(Book { entries })
+    }
+
+    /// Creates a book from entries, for example the ones of `BookBuilder::build`
+    pub fn from_entries(mut _entries: Vec<BookEntry>) -> Book {
+        _entries.sort_by_key(|entry| entry.key);
+        Book { entries: _entries }
+    }
+
+    /// Returns the entries of the book sorted by key
+    pub fn get_entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+
+    /// ## `probe`
+    /// Looks the position of a game up in the book.
+    /// ### Parameters
+    /// - `_game: &Game`: The game whose current position is looked up
+    ///
+    /// ### Returns
+    /// Returns a `Vec<(Move, u16)>` holding every legal book move with its weight, in the order of the book
+    pub fn probe(&self, _game: &Game) -> Vec<(Move, u16)> {
+        let key = _game.get_polyglot_key();
+        let start = self.entries.partition_point(|entry| entry.key < key);
+
+        self.entries[start..].iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| Some((_game.decode_polyglot_move(entry.move_data)?, entry.weight)))
+            .collect()
+    }
+
+    /// ## `choose_move`
+    /// Picks a random book move for the position, each move with a probability proportional to its weight.
+    /// Returns `None` if the position is not in the book
+    pub fn choose_move(&self, _game: &Game) -> Option<Move> {
+        let roll = RandomState::new().build_hasher().finish();
+        self.choose_move_with(_game, roll)
+    }
+
+    /// ## `choose_move_with`
+    /// Same as `choose_move` but with the random number given, which makes the choice reproducible.
+    /// ### Parameters
+    /// - `_game: &Game`: The game whose current position is looked up
+    /// - `_roll: u64`: Any number, it is reduced modulo the total weight of the moves
+    pub fn choose_move_with(&self, _game: &Game, _roll: u64) -> Option<Move> {
+        let moves = self.probe(_game);
+        let total: u64 = moves.iter().map(|(_, weight)| *weight as u64).sum();
+
+        // Moves with weight 0 are only played if every move has weight 0
+        if total == 0 {
+            return moves.first().map(|(_move, _)| *_move);
+        }
+
+        let mut roll = _roll % total;
+        for (_move, weight) in moves {
+            if roll < weight as u64 {
+                return Some(_move);
+            }
+            roll -= weight as u64;
+        }
+
+        None
+    }
+}
+
+/// ## MoveStatistics
+/// ### Type: `struct`
+/// Statistics of a move in a position, gathered by `BookBuilder` from the games it was played in.
+/// Wins and losses are from the point of view of the player making the move.
+/// ### Members:
+/// - `count`: The number of games the move was played in
+/// - `wins`, `draws`, `losses`: The results of those games
+/// - `rating_sum`, `rated_count`: Sum and number of known ratings of the player making the move
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MoveStatistics {
+    pub count: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub rating_sum: u64,
+    pub rated_count: u32
+}
+
+impl MoveStatistics {
+    /// Returns the average rating of the players that made the move, `None` if none of them were rated
+    pub fn get_average_rating(&self) -> Option<u32> {
+        if self.rated_count == 0 {
+            return None;
+        }
+        Some((self.rating_sum / self.rated_count as u64) as u32)
+    }
+
+    /// Returns the Polyglot weight of the move before scaling, two points per win and one per draw
+    pub fn get_score(&self) -> u64 {
+        2 * self.wins as u64 + self.draws as u64
+    }
+}
+
+/// ## BookBuilder
+/// ### Type: `struct`
+/// Builds a Polyglot opening book by replaying games and gathering statistics for every move played in every position.
+/// ### Members:
+/// - `min_games`: Moves played in fewer games are left out of the book
+/// - `max_ply`: Only the first `max_ply` halfmoves of every game are used
+/// - `min_rating`: Moves by players rated lower, or without a rating, are skipped. 0 uses every move
+/// - `statistics`: The statistics of every move keyed by the Polyglot key of the position and the Polyglot move
+pub struct BookBuilder {
+    pub min_games: u32,
+    pub max_ply: usize,
+    pub min_rating: u32,
+    statistics: HashMap<(u64, u16), MoveStatistics>
+}
+
+impl BookBuilder {
+    /// Creates a builder with no filters, every move of the first 40 halfmoves is used
+    pub fn new() -> BookBuilder {
+        BookBuilder {
+            min_games: 1,
+            max_ply: 40,
+            min_rating: 0,
+            statistics: HashMap::new()
+        }
+    }
+
+    /// ## `add_game`
+    /// Replays a game and adds its moves to the statistics.
+    /// ### Parameters
+    /// - `_pgn: &Pgn`: The game to add, games with an unknown result `"*"` are skipped
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping the number of moves added, `None` if the game was skipped or has an illegal move.
+    /// Moves before an illegal move are still added
+    pub fn add_game(&mut self, _pgn: &Pgn) -> Option<usize> {
+        let (white_points, black_points) = match _pgn.result.as_str() {
+            "1-0" => (2, 0),
+            "0-1" => (0, 2),
+            "1/2-1/2" => (1, 1),
+            _ => return None
+        };
+
+        let white_rating: Option<u32> = _pgn.get_tag("WhiteElo").and_then(|rating| rating.parse().ok());
+        let black_rating: Option<u32> = _pgn.get_tag("BlackElo").and_then(|rating| rating.parse().ok());
+
+        let mut game = _pgn.get_start()?;
+        let mut added = 0;
+
+        for san in _pgn.moves.iter().take(self.max_ply) {
+            let _move = game.san_to_move(san)?;
+
+            let (points, rating) = if game.active_colour == Colour::White {(white_points, white_rating)} else {(black_points, black_rating)};
+
+            if rating.unwrap_or(0) >= self.min_rating {
+                let entry = self.statistics.entry((game.get_polyglot_key(), game.encode_polyglot_move(&_move))).or_default();
+
+                entry.count += 1;
+                match points {
+                    2 => entry.wins += 1,
+                    1 => entry.draws += 1,
+                    _ => entry.losses += 1
+                }
+                if let Some(rating) = rating {
+                    entry.rating_sum += rating as u64;
+                    entry.rated_count += 1;
+                }
+                added += 1;
+            }
+
+            game.apply_move(_move);
+        }
+
+        Some(added)
+    }
+
+    /// Returns the statistics of a move in a position given by its Polyglot key and Polyglot move
+    pub fn get_statistics(&self, _key: u64, _move_data: u16) -> Option<&MoveStatistics> {
+        self.statistics.get(&(_key, _move_data))
+    }
+
+    /// Returns the number of different positions with at least one move
+    pub fn get_position_count(&self) -> usize {
+        let mut keys: Vec<u64> = self.statistics.keys().map(|(key, _)| *key).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys.len()
+    }
+
+    /// ## `build`
+    /// Returns the book entries of every move played in at least `min_games` games, sorted by key and then by weight.
+    /// Weights are two points per win and one per draw, scaled down if needed to fit in 16 bits
+    pub fn build(&self) -> Vec<BookEntry> {
+        let kept: Vec<(&(u64, u16), &MoveStatistics)> = self.statistics.iter()
+            .filter(|(_, statistics)| statistics.count >= self.min_games)
+            .collect();
+
+        let max_score = kept.iter().map(|(_, statistics)| statistics.get_score()).max().unwrap_or(0);
+        let scale = |score: u64| if max_score > u16::MAX as u64 {score * u16::MAX as u64 / max_score} else {score};
+
+        let mut entries: Vec<BookEntry> = kept.iter()
+            .map(|((key, move_data), statistics)| BookEntry {
+                key: *key,
+                move_data: *move_data,
+                weight: scale(statistics.get_score()) as u16,
+                learn: 0
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)).then(a.move_data.cmp(&b.move_data)));
+        entries
+    }
+
+    /// ## `write`
+    /// Writes the built book to a Polyglot `.bin` file
+    pub fn write<P: AsRef<Path>>(&self, _path: P) -> io::Result<()> {
+        let bytes: Vec<u8> = self.build().iter().flat_map(|entry| entry.to_bytes()).collect();
+        fs::write(_path, bytes)
+    }
+}
+
+impl Default for BookBuilder {
+    fn default() -> Self {
+        BookBuilder::new()
+    }
+}
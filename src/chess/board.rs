@@ -7,11 +7,14 @@ macro_rules! n_p {
     ($x:expr) => (Piece::from_u8($x));
 }
 
-/// Board struct, holds the board and threat maps
+/// Board struct, holds the board and threat maps.
+/// In Chess960 mode castling moves are given as the king moving to the square of its rook, see `get_king_moves`
+#[derive(Clone)]
 pub struct Board{
     pub board: [[Piece; 8]; 8],
     pub white_threat_map: Vec<(i8, i8)>,
-    pub black_threat_map: Vec<(i8, i8)>
+    pub black_threat_map: Vec<(i8, i8)>,
+    pub chess960: bool
 }
 
 impl Board {
@@ -28,7 +31,8 @@ impl Board {
             [n_p!(0x82), n_p!(0x82), n_p!(0x82), n_p!(0x82), n_p!(0x82), n_p!(0x82), n_p!(0x82), n_p!(0x82)],
             [n_p!(0x88), n_p!(0x04), n_p!(0x06), n_p!(0x8A), n_p!(0x8C), n_p!(0x06), n_p!(0x04), n_p!(0x88)]],
             white_threat_map: vec![],
-            black_threat_map: vec![]
+            black_threat_map: vec![],
+            chess960: false
         }
     }
 
@@ -45,16 +49,18 @@ impl Board {
             [n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x00)],
             [n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x00), n_p!(0x8C), n_p!(0x00), n_p!(0x00), n_p!(0x00)]],
             white_threat_map: vec![],
-            black_threat_map: vec![]
+            black_threat_map: vec![],
+            chess960: false
         }
     }
 
     // Returns a copy of the board using a previous board
     pub fn copy(board: [[Piece; 8]; 8]) -> Board {
         Board {
-            board: board,
+            board,
             white_threat_map: vec![],
-            black_threat_map: vec![]
+            black_threat_map: vec![],
+            chess960: false
         }
     }
 
@@ -88,28 +94,42 @@ impl Board {
 
     // Make move
     pub fn make_move(&mut self, _from: &String, _to: &String) {
-        let moves = self.get_legal_moves(&_from);
+        let moves = self.get_legal_moves(_from);
 
-        if moves.contains(&_to) {
-            // Get moving piece
-            let piece = self.mut_piece_at(Board::filerank_to_num(_from));
-
-            // Set moving bitflag to 0 with 01111111 flag
-            piece.set_data(piece.as_u8() & 0x7f);
-
-            // If pawn does a double move, set en passant move to 1 using 00100000
-            if piece.get_type() == PieceType::Pawn
-            && (Board::filerank_to_num(_from).1 - Board::filerank_to_num(_to).1 == 2
-            || Board::filerank_to_num(_to).1 - Board::filerank_to_num(_from).1 == 2)
-            {
-                piece.set_data(piece.as_u8() | 0x20);
-            }
-
-            self.make_pseudo_legal_move(Board::filerank_to_num(&_from), Board::filerank_to_num(&_to));
+        if moves.contains(_to) {
+            self.play_move(Board::filerank_to_num(_from), Board::filerank_to_num(_to));
         }
         else {
             panic!("Illegal Move!")
         }
     }
+
+    /// Moves a piece without checking legality and updates the moving and en passant bitflags
+    pub fn play_move(&mut self, _from: (i8, i8), _to: (i8, i8)) {
+        // En passant is only possible for one move, remove all en passant bitflags using 11011111
+        for row in self.board.iter_mut() {
+            for piece in row.iter_mut() {
+                piece.set_data(piece.as_u8() & 0xdf);
+            }
+        }
+
+        // Get moving piece
+        let piece = self.mut_piece_at(_from);
+
+        // Set moving bitflag to 0 with 01111111 flag
+        piece.set_data(piece.as_u8() & 0x7f);
+
+        // If pawn does a double move, set en passant move to 1 using 00100000
+        if piece.get_type() == PieceType::Pawn && (_from.1 - _to.1).abs() == 2 {
+            piece.set_data(piece.as_u8() | 0x20);
+        }
+
+        self.make_pseudo_legal_move(_from, _to);
+    }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
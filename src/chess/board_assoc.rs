@@ -37,16 +37,37 @@ impl Board {
         coords
     }
 
-    /// Returns true if the king is in check. By seeing if the king is in the threat map
+    /// Returns true if the king is in check. By seeing if the king's square is attacked by the opponent
     pub fn king_in_check(_board: &mut Board, _colour: Colour) -> bool {
-        _board.generate_threat_maps();
         let king = _board.get_king(_colour);
-        
-        if _colour == Colour::Black {
-            return _board.white_threat_map.contains(&king);
-        }
-        else {
-            return _board.black_threat_map.contains(&king);
+
+        _board.is_square_attacked(king, _colour.opposite())
+    }
+
+    /// Returns true if a colour could checkmate with some series of legal moves, however unlikely.
+    /// A lone king never can, nor a king and a knight against a lone king, nor a king and bishops all on squares of one colour
+    /// against a king with at most bishops on that same colour, as nothing can ever block the king's escape squares
+    pub fn has_mating_material(&self, _colour: Colour) -> bool {
+        let pieces = |colour: Colour| -> Vec<(PieceType, i8)> {
+            (0..8).flat_map(|row| (0..8).map(move |col| (col, row)))
+                .map(|square: (i8, i8)| (self.piece_at(square).get_piece_data(), (square.0 + square.1) % 2))
+                .filter(|((piece_colour, piece_type), _)| *piece_colour == colour && *piece_type != PieceType::None && *piece_type != PieceType::King)
+                .map(|((_, piece_type), square_colour)| (piece_type, square_colour))
+                .collect()
+        };
+
+        let own = pieces(_colour);
+        let opponent = pieces(_colour.opposite());
+
+        match own.as_slice() {
+            [] => false,
+            [(PieceType::Knight, _)] => !opponent.is_empty(),
+            _ => {
+                // Bishops on one colour of squares only mate if the opponent has a piece that is not such a bishop to block with
+                let square_colour = own[0].1;
+                let same_bishop = |(piece_type, colour): &(PieceType, i8)| *piece_type == PieceType::Bishop && *colour == square_colour;
+                !(own.iter().all(same_bishop) && opponent.iter().all(same_bishop))
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,111 @@
+use super::board::*;
+use super::piece::*;
+use super::piece_data::*;
+use std::convert::TryInto;
+
+impl Board {
+    /// ## `to_bytes`
+    /// Returns the board in a compact binary form of 10 to 26 bytes:
+    /// - 8 bytes: occupancy, bit `n` of the little-endian `u64` is set when square `n` holds a piece, a1 is 0, b1 is 1 and h8 is 63
+    /// - 4 bits per piece in the order of the occupancy, the colour and type bits of the piece, two pieces per byte with the first in the low bits.
+    ///   Rooks that can still castle use the unused type bits `111`, 14 for White and 15 for Black
+    /// - 1 byte: flags, bit 0 is set in Chess960 mode
+    /// - 1 byte: the en passant square as `n + 1` for square `n`, 0 if there is none
+    ///
+    /// The side to move and the clocks are not part of the board, see `Game::to_bytes`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut occupancy: u64 = 0;
+        let mut codes: Vec<u8> = Vec::with_capacity(32);
+        let castling_rooks = [self.get_castling_rooks(Colour::White), self.get_castling_rooks(Colour::Black)].concat();
+
+        for square in 0..64 {
+            let coords = ((square % 8) as i8, 7 - (square / 8) as i8);
+            if !self.is_empty(coords) {
+                occupancy |= 1 << square;
+                // Mask with 00001111 to keep the colour and type bits only, or with 00001110 set for a castling rook
+                let code = self.piece_at(coords).as_u8() & 0x0F;
+                codes.push(if castling_rooks.contains(&coords) {code | 0x0E} else {code});
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(10 + codes.len().div_ceil(2));
+        bytes.extend_from_slice(&occupancy.to_le_bytes());
+        for pair in codes.chunks(2) {
+            bytes.push(pair[0] | pair.get(1).map_or(0, |code| code << 4));
+        }
+
+        bytes.push(if self.chess960 {1} else {0});
+
+        let en_passant = self.get_fen_en_passant();
+        bytes.push(if en_passant == "-" {
+            0
+        }
+        else {
+            let (col, row) = Board::filerank_to_num(&en_passant);
+            ((7 - row) * 8 + col + 1) as u8
+        });
+
+        bytes
+    }
+
+    /// ## `from_bytes`
+    /// Reads a board written by `to_bytes`.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `Board`, `None` if the bytes are not exactly one valid board
+    pub fn from_bytes(_bytes: &[u8]) -> Option<Board> {
+        match Board::read_bytes(_bytes)? {
+            (board, length) if length == _bytes.len() => Some(board),
+            _ => None
+        }
+    }
+
+    /// Reads a board from the start of the bytes and returns it with the number of bytes it took
+    pub(crate) fn read_bytes(_bytes: &[u8]) -> Option<(Board, usize)> {
+        let occupancy = u64::from_le_bytes(_bytes.get(0..8)?.try_into().ok()?);
+        let pieces = occupancy.count_ones() as usize;
+        let length = 8 + pieces.div_ceil(2) + 2;
+        let bytes = _bytes.get(..length)?;
+
+        let mut board = Board::copy([[Piece::from_u8(0x0); 8]; 8]);
+        let mut castling = String::new();
+        let mut index = 0;
+        for square in 0..64 {
+            if occupancy & 1 << square == 0 {
+                continue;
+            }
+
+            let mut code = (bytes[8 + index / 2] >> (4 * (index % 2))) & 0x0F;
+            if code & 0x0E == 0x0E {
+                // Castling rights are written in Shredder-FEN, the file of the rook
+                let file = (b'a' + (square % 8) as u8) as char;
+                castling.push(if code & 1 == 0 {file.to_ascii_uppercase()} else {file});
+                code = (code & 1) | 8;
+            }
+
+            let piece = Piece::from_u8(code);
+            if piece.get_type() == PieceType::None {
+                return None;
+            }
+
+            board.board[7 - square / 8][square % 8] = piece;
+            index += 1;
+        }
+
+        let flags = bytes[length - 2];
+        if flags > 1 {
+            return None;
+        }
+
+        let en_passant = match bytes[length - 1] {
+            0 => String::from("-"),
+            square @ 1..=64 => Board::num_to_filerank(&(((square - 1) % 8) as i8, 7 - ((square - 1) / 8) as i8)),
+            _ => return None
+        };
+
+        // The moving and en passant bitflags are set the same way as for FEN
+        let mut board = Board::from_fen(&board.get_fen_placement(), if castling.is_empty() {"-"} else {&castling}, &en_passant)?;
+        board.chess960 = flags == 1;
+
+        Some((board, length))
+    }
+}
@@ -0,0 +1,179 @@
+use super::board::*;
+use super::piece::*;
+use super::piece_data::*;
+
+impl Board {
+    /// Creates a board from the piece placement, castling and en passant fields of a FEN string.
+    /// The moving and en passant bitflags are set from the castling and en passant fields. Returns None if a field is malformed
+    pub fn from_fen(_placement: &str, _castling: &str, _en_passant: &str) -> Option<Board> {
+        let mut board = Board::copy([[Piece::from_u8(0x0); 8]; 8]);
+
+        let ranks: Vec<&str> = _placement.split('/').collect();
+        if ranks.len() != 8 {
+            return None;
+        }
+
+        // FEN starts from the 8th rank which is row 0 in the board
+        for (row, rank) in ranks.iter().enumerate() {
+            let mut col = 0;
+
+            for icon in rank.chars() {
+                if let Some(empty) = icon.to_digit(10) {
+                    col += empty as usize;
+                    continue;
+                }
+
+                if col > 7 {
+                    return None;
+                }
+
+                let mut piece = Piece::from_icon(icon)?;
+
+                // Pawns on their starting rank have not moved yet, set the moving bitflag using 10000000
+                if piece.get_type() == PieceType::Pawn
+                && ((piece.get_colour() == Colour::White && row == 6) || (piece.get_colour() == Colour::Black && row == 1)) {
+                    piece.set_data(piece.as_u8() | 0x80);
+                }
+
+                board.board[row][col] = piece;
+                col += 1;
+            }
+
+            if col != 8 {
+                return None;
+            }
+        }
+
+        // Castling rights are kept as the moving bitflag on the king and the rook. Besides KQkq the rook may be given
+        // by its file as in Shredder-FEN and X-FEN, e.g "HAha", for Chess960 positions
+        if _castling != "-" {
+            for right in _castling.chars() {
+                let colour = if right.is_ascii_uppercase() {Colour::White} else {Colour::Black};
+                let row = if colour == Colour::White {7} else {0};
+                let king = (0..8).map(|col| (col, row)).find(|square| board.piece_at(*square).get_piece_data() == (colour, PieceType::King))?;
+                let is_rook = |col: &i8| board.piece_at((*col, row)).get_piece_data() == (colour, PieceType::Rook);
+
+                // K and Q are the outermost rook on their side of the king
+                let rook_col = match right.to_ascii_lowercase() {
+                    'k' => (king.0 + 1..8).rev().find(is_rook)?,
+                    'q' => (0..king.0).find(is_rook)?,
+                    file @ 'a'..='h' if is_rook(&(file as i8 - 'a' as i8)) => file as i8 - 'a' as i8,
+                    _ => return None
+                };
+
+                for square in [king, (rook_col, row)] {
+                    let piece = board.mut_piece_at(square);
+                    piece.set_data(piece.as_u8() | 0x80);
+                }
+
+                // Castling with the king or a rook away from their standard squares is only possible in Chess960
+                if king.0 != 4 || (rook_col != 0 && rook_col != 7) {
+                    board.chess960 = true;
+                }
+            }
+        }
+
+        // The en passant square is behind the pawn that just made a double step, set its en passant bitflag using 00100000
+        if _en_passant != "-" {
+            let square = _en_passant.as_bytes();
+            if square.len() != 2 || !(b'a'..=b'h').contains(&square[0]) || (square[1] != b'3' && square[1] != b'6') {
+                return None;
+            }
+
+            let behind = Board::filerank_to_num(&String::from(_en_passant));
+            let pawn = (behind.0, if square[1] == b'3' {behind.1 - 1} else {behind.1 + 1});
+
+            if board.piece_at(pawn).get_type() == PieceType::Pawn {
+                let piece = board.mut_piece_at(pawn);
+                piece.set_data(piece.as_u8() | 0x20);
+            }
+        }
+
+        Some(board)
+    }
+
+    /// Returns the piece placement field of the FEN string of the board
+    pub fn get_fen_placement(&self) -> String {
+        let mut placement = String::with_capacity(72);
+
+        for row in 0..8 {
+            let mut empty = 0;
+
+            for col in 0..8 {
+                if self.is_empty((col, row)) {
+                    empty += 1;
+                    continue;
+                }
+
+                if empty > 0 {
+                    placement.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                placement.push(self.piece_at((col, row)).get_icon());
+            }
+
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if row < 7 {
+                placement.push('/');
+            }
+        }
+
+        placement
+    }
+
+    /// Returns the castling field of the FEN string of the board, read from the moving bitflags of kings and rooks.
+    /// Written as X-FEN, which is the standard `KQkq` unless a Chess960 castling rook is not the outermost rook on its side
+    pub fn get_fen_castling(&self) -> String {
+        self.castling_field(false)
+    }
+
+    /// Returns the castling field as in Shredder-FEN, the files of the castling rooks, e.g `"HAha"`
+    pub fn get_fen_castling_shredder(&self) -> String {
+        self.castling_field(true)
+    }
+
+    /// Writes the castling rights of White then Black, each with the king's side first
+    fn castling_field(&self, _shredder: bool) -> String {
+        let mut castling = String::with_capacity(4);
+
+        for colour in [Colour::White, Colour::Black] {
+            for (rook_col, row) in self.get_castling_rooks(colour) {
+                let king = self.get_king(colour);
+                let side: Vec<i8> = if rook_col > king.0 {(rook_col + 1..8).collect()} else {(0..rook_col).collect()};
+                let outermost = side.iter().all(|col| self.piece_at((*col, row)).get_piece_data() != (colour, PieceType::Rook));
+
+                let letter = match (_shredder || !outermost, rook_col > king.0) {
+                    (true, _) => (b'a' + rook_col as u8) as char,
+                    (false, true) => 'k',
+                    (false, false) => 'q'
+                };
+                castling.push(if colour == Colour::White {letter.to_ascii_uppercase()} else {letter});
+            }
+        }
+
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        castling
+    }
+
+    /// Returns the en passant field of the FEN string of the board, the square behind a pawn with the en passant bitflag
+    pub fn get_fen_en_passant(&self) -> String {
+        for row in [3, 4] {
+            for col in 0..8 {
+                let piece = self.piece_at((col, row));
+
+                if piece.get_type() == PieceType::Pawn && piece.as_u8() & 0x20 == 0x20 {
+                    // A white pawn that double stepped is on row 4 and passed row 5, a black one is on row 3 and passed row 2
+                    let behind = if piece.get_colour() == Colour::White {row + 1} else {row - 1};
+                    return Board::num_to_filerank(&(col, behind));
+                }
+            }
+        }
+
+        String::from("-")
+    }
+}
@@ -33,17 +33,23 @@ impl Board {
 
     /// Returns moves that are do not put own king in check. It takes pseudo legal and filters out the ones that put own king in check
     pub fn get_legal_moves(&self, _filerank: &String) -> Vec<String> {
-        let coordinates = Board::filerank_to_num(_filerank);
+        self.get_legal_move_coords(Board::filerank_to_num(_filerank))
+            .iter()
+            .map(Board::num_to_filerank)
+            .collect()
+    }
 
-        let pseudo = self.get_pseudo_legal_moves(coordinates);
-        let mut legal_moves : Vec<String> = Vec::with_capacity(15);
-        let piece = self.piece_at(coordinates).get_piece_data();
+    /// Same as `get_legal_moves` but takes and returns numerical coordinates
+    pub fn get_legal_move_coords(&self, _coordinates: (i8, i8)) -> Vec<(i8, i8)> {
+        let pseudo = self.get_pseudo_legal_moves(_coordinates);
+        let mut legal_moves : Vec<(i8, i8)> = Vec::with_capacity(15);
+        let piece = self.piece_at(_coordinates).get_piece_data();
 
-        for _move in 0..pseudo.len() {
+        for _move in pseudo {
             let mut new_board = Board::copy(self.board);
-            new_board.make_pseudo_legal_move(coordinates, pseudo[_move]);
+            new_board.make_pseudo_legal_move(_coordinates, _move);
             if !Board::king_in_check(&mut new_board, piece.0) {
-                legal_moves.push(Board::num_to_filerank(&pseudo[_move]));
+                legal_moves.push(_move);
             }
         }
 
@@ -68,14 +74,13 @@ impl Board {
         // Get attacks
         for direction in DIRECTIONS {
             checked_coord = (_coordinates.0 + direction, _coordinates.1 + move_direction);
-            if Board::within_bounds(checked_coord) {
-                if !_board.is_empty(checked_coord)
-                && _board.piece_at(checked_coord).get_colour() != _colour {moves.push(checked_coord);}
-            }
+            if Board::within_bounds(checked_coord)
+            && !_board.is_empty(checked_coord)
+            && _board.piece_at(checked_coord).get_colour() != _colour {moves.push(checked_coord);}
         }
 
-        // Get en passants
-        if _coordinates.0 == 3 || _coordinates.0 == 4 {
+        // Get en passants, only possible from the fifth rank of the moving side
+        if (_colour == Colour::White && _coordinates.1 == 3) || (_colour == Colour::Black && _coordinates.1 == 4) {
             for direction in DIRECTIONS {
                 let checked_coord = (_coordinates.0 + direction, _coordinates.1 + move_direction);
                 let en_passanting_piece = (_coordinates.0 + direction, _coordinates.1);
@@ -83,6 +88,7 @@ impl Board {
                 if Board::within_bounds(en_passanting_piece) 
                 && Board::within_bounds(checked_coord)
                 && _board.piece_at(en_passanting_piece).as_u8() & 0x20 == 0x20 // Get en passant bit
+                && _board.piece_at(en_passanting_piece).get_colour() != _colour
                 && _board.is_empty(checked_coord)
                 {moves.push(checked_coord);}
             }
@@ -191,13 +197,35 @@ impl Board {
         for row in -1..2 {
             for col in -1..2 {
                 let checked_coord = (_coordinates.0 + row, _coordinates.1 + col);
-                if Board::within_bounds(checked_coord) {
-                    if (!_board.is_empty(checked_coord) &&
-                    _board.piece_at(checked_coord).get_colour() != _colour) ||
-                    _board.is_empty(checked_coord)
-                    {
-                        moves.push(checked_coord);
-                    }
+                if Board::within_bounds(checked_coord)
+                && (_board.is_empty(checked_coord)
+                || _board.piece_at(checked_coord).get_colour() != _colour)
+                {
+                    moves.push(checked_coord);
+                }
+            }
+        }
+
+        // Castling, the king and the rook must not have moved (moving bitflag 10000000 still set),
+        // the squares both of them cross must be empty and the king may not castle out of or through check
+        if _board.piece_at(_coordinates).as_u8() & 0x80 == 0x80
+        && !_board.is_square_attacked(_coordinates, _colour.opposite())
+        {
+            for rook in _board.get_castling_rooks(_colour) {
+                let (king_to, rook_to) = Board::castling_targets(_coordinates, rook);
+
+                let low = _coordinates.0.min(rook.0).min(king_to.0).min(rook_to.0);
+                let high = _coordinates.0.max(rook.0).max(king_to.0).max(rook_to.0);
+                let path_empty = (low..=high)
+                    .all(|col| col == _coordinates.0 || col == rook.0 || _board.is_empty((col, _coordinates.1)));
+
+                // The square the king lands on is checked by the legality filter like any other move
+                let path_safe = (_coordinates.0.min(king_to.0) + 1.._coordinates.0.max(king_to.0))
+                    .all(|col| !_board.is_square_attacked((col, _coordinates.1), _colour.opposite()));
+
+                if path_empty && path_safe {
+                    // Chess960 castling is the king taking its own rook, as the king may not move or move a single square
+                    moves.push(if _board.chess960 {rook} else {king_to});
                 }
             }
         }
@@ -205,7 +233,61 @@ impl Board {
         Some(moves)
     }
 
+    /// Returns the squares of the rooks of a colour that can still castle, the king and the rook both still have their moving bitflag.
+    /// The rook on the king's side comes first
+    pub fn get_castling_rooks(&self, _colour: Colour) -> Vec<(i8, i8)> {
+        let row = if _colour == Colour::White {7} else {0};
+        let king = (0..8).map(|col| (col, row)).find(|square| self.piece_at(*square).get_piece_data() == (_colour, PieceType::King));
+
+        match king {
+            Some(king) if self.piece_at(king).as_u8() & 0x80 == 0x80 => (0..8).rev()
+                .map(|col| (col, row))
+                .filter(|square| self.piece_at(*square).get_piece_data() == (_colour, PieceType::Rook) && self.piece_at(*square).as_u8() & 0x80 == 0x80)
+                .collect(),
+            _ => Vec::new()
+        }
+    }
+
+    /// Returns the squares the king and the rook land on when castling, the g and f files on the king's side and the c and d files on the other
+    pub fn castling_targets(_king: (i8, i8), _rook: (i8, i8)) -> ((i8, i8), (i8, i8)) {
+        if _rook.0 > _king.0 {
+            ((6, _king.1), (5, _king.1))
+        }
+        else {
+            ((2, _king.1), (3, _king.1))
+        }
+    }
+
+    /// Returns true if a king move is castling, either the king moving two squares or, in Chess960, the king taking its own rook
+    pub fn is_castling(&self, _from: (i8, i8), _to: (i8, i8)) -> bool {
+        let king = self.piece_at(_from);
+        king.get_type() == PieceType::King
+        && ((_to.0 - _from.0).abs() == 2 && _to.1 == _from.1
+        || self.piece_at(_to).get_piece_data() == (king.get_colour(), PieceType::Rook))
+    }
+
     pub fn make_pseudo_legal_move(&mut self, _from: (i8, i8), _to: (i8, i8)) {
+        // A pawn moving diagonally to an empty square is capturing en passant, remove the passed pawn
+        if self.piece_at(_from).get_type() == PieceType::Pawn && _from.0 != _to.0 && self.is_empty(_to) {
+            self.board[_from.1 as usize][_to.0 as usize] = Piece::from_u8(0x0);
+        }
+
+        // Castling, put the king and the rook on their squares and clear the moving bitflag of the rook
+        if self.is_castling(_from, _to) {
+            let rook_from = if self.piece_at(_to).get_type() == PieceType::Rook {_to} else {(if _to.0 > _from.0 {7} else {0}, _from.1)};
+            let (king_to, rook_to) = Board::castling_targets(_from, rook_from);
+
+            let king = *self.piece_at(_from);
+            let mut rook = *self.piece_at(rook_from);
+            rook.set_data(rook.as_u8() & 0x7f);
+
+            self.board[_from.1 as usize][_from.0 as usize] = Piece::from_u8(0x0);
+            self.board[rook_from.1 as usize][rook_from.0 as usize] = Piece::from_u8(0x0);
+            self.board[king_to.1 as usize][king_to.0 as usize] = king;
+            self.board[rook_to.1 as usize][rook_to.0 as usize] = rook;
+            return;
+        }
+
         self.board[_to.1 as usize][_to.0 as usize] = self.board[_from.1 as usize][_from.0 as usize];
         self.board[_from.1 as usize][_from.0 as usize] = Piece::from_u8(0x0);
     }
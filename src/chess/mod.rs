@@ -4,4 +4,7 @@ pub mod piece_data;
 pub mod board;
 pub mod board_assoc;
 pub mod board_moves;
-pub mod threat_map;
\ No newline at end of file
+pub mod threat_map;
+pub mod moves;
+pub mod board_fen;
+pub mod board_bytes;
@@ -0,0 +1,71 @@
+use super::board::*;
+use super::piece_data::*;
+
+/// Move struct, holds the numerical coordinates of the start and end squares of a move and the piece type to promote to (if any)
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Move {
+    pub from: (i8, i8),
+    pub to: (i8, i8),
+    pub promotion: Option<PieceType>
+}
+
+impl Move {
+
+    /// Create a move from numerical coordinates without promotion
+    pub fn new(_from: (i8, i8), _to: (i8, i8)) -> Move {
+        Move {
+            from: _from,
+            to: _to,
+            promotion: None
+        }
+    }
+
+    /// Create a move from a UCI long algebraic string such as "e2e4" or "e7e8q". Returns None if the string is malformed
+    pub fn from_uci(_uci: &str) -> Option<Move> {
+        let bytes = _uci.as_bytes();
+
+        if bytes.len() != 4 && bytes.len() != 5 {
+            return None;
+        }
+
+        // Validate the file and rank characters before handing them to filerank_to_num
+        for square in [&bytes[0..2], &bytes[2..4]] {
+            if !(b'a'..=b'h').contains(&square[0]) || !(b'1'..=b'8').contains(&square[1]) {
+                return None;
+            }
+        }
+
+        let promotion = match bytes.get(4) {
+            None => None,
+            Some(b'q') => Some(PieceType::Queen),
+            Some(b'r') => Some(PieceType::Rook),
+            Some(b'b') => Some(PieceType::Bishop),
+            Some(b'n') => Some(PieceType::Knight),
+            Some(_) => return None
+        };
+
+        Some(Move {
+            from: Board::filerank_to_num(&String::from(&_uci[0..2])),
+            to: Board::filerank_to_num(&String::from(&_uci[2..4])),
+            promotion
+        })
+    }
+
+    /// Returns the move as a UCI long algebraic string, e.g "e2e4" or "e7e8q"
+    pub fn to_uci(&self) -> String {
+        let mut uci = String::with_capacity(5);
+
+        uci.push_str(&Board::num_to_filerank(&self.from));
+        uci.push_str(&Board::num_to_filerank(&self.to));
+
+        match self.promotion {
+            Some(PieceType::Queen) => uci.push('q'),
+            Some(PieceType::Rook) => uci.push('r'),
+            Some(PieceType::Bishop) => uci.push('b'),
+            Some(PieceType::Knight) => uci.push('n'),
+            _ => ()
+        }
+
+        uci
+    }
+}
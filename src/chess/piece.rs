@@ -117,6 +117,42 @@ impl Piece {
         }
     }
 
+    /// Returns the Unicode chess symbol of the piece, e.g '♔' for a white king and '♚' for a black one. Empty squares are a space
+    pub fn get_unicode_icon(&self) -> char {
+        match self.0 & 15 {
+            2 => '♙',
+            3 => '♟',
+            4 => '♘',
+            5 => '♞',
+            6 => '♗',
+            7 => '♝',
+            8 => '♖',
+            9 => '♜',
+            10 => '♕',
+            11 => '♛',
+            12 => '♔',
+            13 => '♚',
+            _ => ' '
+        }
+    }
+
+    /// Create a piece from its icon as given by get_icon, i.e FEN letters. Uppercase is white, lowercase is black
+    pub fn from_icon(_icon: char) -> Option<Piece> {
+        let colour = if _icon.is_ascii_uppercase() {Colour::White} else {Colour::Black};
+
+        let piece_type = match _icon.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            _ => return None
+        };
+
+        Some(Piece::new(colour, piece_type))
+    }
+
     /// Sets the data of the current piece in u8
     pub fn set_data (&mut self, u8_data: u8) {
         self.0 = u8_data;
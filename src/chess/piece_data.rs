@@ -1,5 +1,6 @@
 /// Pieces used in chess
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum PieceType {
     None,
     Pawn,
@@ -12,8 +13,18 @@ pub enum PieceType {
 
 /// Colours of piece and of the current turn
 #[derive(PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum Colour {
     White,
     Black
 }
 
+impl Colour {
+    /// Returns the colour of the opponent
+    pub fn opposite(&self) -> Colour {
+        match self {
+            Colour::White => Colour::Black,
+            Colour::Black => Colour::White
+        }
+    }
+}
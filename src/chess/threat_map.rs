@@ -33,6 +33,59 @@ impl Board {
         self.white_threat_map = white_map.into_iter().collect();
     }
 
+    /// Returns true if the square is attacked by any piece of the given colour.
+    /// Looks outwards from the square instead of generating the full threat maps which makes it much faster for check detection
+    pub fn is_square_attacked(&self, _square: (i8, i8), _by: Colour) -> bool {
+        let is_attacker = |coords: (i8, i8), types: &[PieceType]| {
+            let piece = self.piece_at(coords);
+            piece.get_type() != PieceType::None && piece.get_colour() == _by && types.contains(&piece.get_type())
+        };
+
+        // Pawns attack diagonally forward, so the attacking pawn is one row behind the square from its point of view
+        let pawn_row = if _by == Colour::White {1} else {-1};
+        for direction in DIRECTIONS {
+            let checked_coord = (_square.0 + direction, _square.1 + pawn_row);
+            if Board::within_bounds(checked_coord) && is_attacker(checked_coord, &[PieceType::Pawn]) {return true;}
+        }
+
+        // Knights
+        for direction_x in DIRECTIONS {
+            for direction_y in DIRECTIONS {
+                for l_long_side in 0..2 {
+                    let checked_coord = (_square.0 + direction_x * (1 + l_long_side),
+                                        _square.1 + direction_y * (2 - l_long_side));
+                    if Board::within_bounds(checked_coord) && is_attacker(checked_coord, &[PieceType::Knight]) {return true;}
+                }
+            }
+        }
+
+        // Kings
+        for row in -1..2 {
+            for col in -1..2 {
+                let checked_coord = (_square.0 + col, _square.1 + row);
+                if (row != 0 || col != 0) && Board::within_bounds(checked_coord) && is_attacker(checked_coord, &[PieceType::King]) {return true;}
+            }
+        }
+
+        // Sliding pieces, diagonals for bishops and queens, straight lines for rooks and queens
+        let diagonal = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+        let straight = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+        for (directions, types) in [(diagonal, [PieceType::Bishop, PieceType::Queen]), (straight, [PieceType::Rook, PieceType::Queen])] {
+            for direction in directions {
+                for square in 1..8 {
+                    let checked_coord = (_square.0 + direction.0 * square, _square.1 + direction.1 * square);
+                    if !Board::within_bounds(checked_coord) {break;}
+                    if !self.is_empty(checked_coord) {
+                        if is_attacker(checked_coord, &types) {return true;}
+                        break; // Blocked by the first piece in the way
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
     /// Get all squares that a piece can attack or defend
     fn get_piece_threat_moves(&self, _coordinates: (i8, i8), _piece: Piece, _colour: Colour) -> Option<Vec<(i8, i8)>> {
         // Store the movements
@@ -61,10 +114,8 @@ impl Board {
         // Get attacks
         for direction in DIRECTIONS {
             let checked_coord = (_coordinates.0 + direction, _coordinates.1 + move_direction);
-            if Board::within_bounds(checked_coord) {
-                
-                if _board.piece_at(checked_coord).get_colour() != _colour {moves.push(checked_coord);}
-            }
+            if Board::within_bounds(checked_coord)
+            && _board.piece_at(checked_coord).get_colour() != _colour {moves.push(checked_coord);}
         }
 
         // Get en passants
@@ -0,0 +1,74 @@
+use crate::Game;
+
+/// Squares of the two knights among the five squares left after the bishops and the queen, by knight number 0 to 9
+const KNIGHT_SQUARES: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+
+/// ## `chess960_back_rank`
+/// Returns the white pieces of the first rank, from the a to the h file, of a Chess960 starting position by its Scharnagl number.
+/// 518 is the standard position `"RNBQKBNR"`. Returns `None` if the number is not below 960
+pub fn chess960_back_rank(_id: u16) -> Option<String> {
+    if _id >= 960 {
+        return None;
+    }
+
+    let mut rank = [' '; 8];
+    let mut id = _id as usize;
+
+    // The bishops are on squares of different colours, the first on b, d, f or h and the second on a, c, e or g
+    rank[2 * (id % 4) + 1] = 'B';
+    id /= 4;
+    rank[2 * (id % 4)] = 'B';
+    id /= 4;
+
+    // The remaining pieces go on the free squares in order, which keeps the king between the rooks
+    let free = |rank: &[char; 8]| -> Vec<usize> {(0..8).filter(|file| rank[*file] == ' ').collect()};
+
+    rank[free(&rank)[id % 6]] = 'Q';
+    id /= 6;
+
+    let (first, second) = KNIGHT_SQUARES[id];
+    let squares = free(&rank);
+    rank[squares[first]] = 'N';
+    rank[squares[second]] = 'N';
+
+    for (file, piece) in free(&rank).into_iter().zip(['R', 'K', 'R']) {
+        rank[file] = piece;
+    }
+
+    Some(rank.iter().collect())
+}
+
+impl Game {
+    /// ## `new_chess960`
+    /// Creates a Chess960 game from one of the 960 starting positions given by its Scharnagl number, 0 to 959.
+    /// Castling moves of the game are the king moving to the square of its rook as in `UCI_Chess960`, e.g `"b1a1"`
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `Game`, `None` if the number is not below 960
+    pub fn new_chess960(_id: u16) -> Option<Game> {
+        let white = chess960_back_rank(_id)?;
+        let mut game = Game::from_fen(&format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", white.to_lowercase(), white))?;
+        game.set_chess960(true);
+        Some(game)
+    }
+
+    /// Returns true if the game is played with Chess960 castling
+    pub fn is_chess960(&self) -> bool {
+        self.board.chess960
+    }
+
+    /// ## `set_chess960`
+    /// Switches Chess960 castling on or off, it should be set before any move is played.
+    /// FEN strings with castling rooks or kings away from their standard squares switch it on by themselves,
+    /// this is needed for Chess960 positions that look like standard ones
+    pub fn set_chess960(&mut self, _chess960: bool) {
+        self.board.chess960 = _chess960;
+    }
+
+    /// ## `to_shredder_fen`
+    /// Returns the current position as a Shredder-FEN string, with the files of the castling rooks in the castling field, e.g `"HAha"`
+    pub fn to_shredder_fen(&self) -> String {
+        let mut fields: Vec<String> = self.to_fen().split(' ').map(String::from).collect();
+        fields[2] = self.board.get_fen_castling_shredder();
+        fields.join(" ")
+    }
+}
@@ -0,0 +1,390 @@
+use crate::chess::piece_data::Colour;
+use crate::termination::TerminationReason;
+use crate::{Game, GameState};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// ## TimeSource
+/// ### Type: `trait`
+/// Where a `Clock` reads the time from. Only differences between readings matter, the origin is arbitrary
+pub trait TimeSource {
+    /// Returns the time elapsed since the origin of the source
+    fn now(&self) -> Duration;
+}
+
+/// The system's monotonic clock, the origin is the moment the source was created
+#[derive(Copy, Clone, Debug)]
+pub struct MonotonicTime {
+    origin: Instant
+}
+
+impl MonotonicTime {
+    pub fn new() -> MonotonicTime {
+        MonotonicTime { origin: Instant::now() }
+    }
+}
+
+impl Default for MonotonicTime {
+    fn default() -> Self {
+        MonotonicTime::new()
+    }
+}
+
+impl TimeSource for MonotonicTime {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// ## ManualTime
+/// ### Type: `struct`
+/// A time source that only moves when told to, for deterministic tests and replays.
+/// Clones share the same time, so a clone can be kept to advance the time of a clock it was given to
+#[derive(Clone, Debug, Default)]
+pub struct ManualTime {
+    now: Arc<Mutex<Duration>>
+}
+
+impl ManualTime {
+    /// Creates a time source standing at 0
+    pub fn new() -> ManualTime {
+        ManualTime::default()
+    }
+
+    /// Moves the time forward
+    pub fn advance(&self, _duration: Duration) {
+        *self.now.lock().unwrap() += _duration;
+    }
+
+    /// Sets the time
+    pub fn set(&self, _now: Duration) {
+        *self.now.lock().unwrap() = _now;
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// How a period adds time to a player's clock for every move
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Timing {
+    /// Fischer increment, the time is added after every move
+    Increment(Duration),
+    /// Bronstein delay, the time spent on a move is given back after it, up to the delay
+    Bronstein(Duration),
+    /// Simple or US delay, the clock only starts running once the delay has passed
+    Delay(Duration)
+}
+
+/// ## Period
+/// ### Type: `struct`
+/// One period of a time control.
+/// ### Members:
+/// - `moves`: The number of moves to make in the period, `None` for the rest of the game
+/// - `time`: The time added to the clock when the period starts
+/// - `timing`: The increment or delay of every move of the period, if any
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Period {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub timing: Option<Timing>
+}
+
+/// ## TimeControl
+/// ### Type: `struct`
+/// The periods of a time control, played in order. The last period lasts for the rest of the game,
+/// and is repeated if it has a number of moves
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeControl {
+    pub periods: Vec<Period>
+}
+
+impl TimeControl {
+    /// Creates a time control from its periods, `None` if there are none
+    pub fn new(_periods: Vec<Period>) -> Option<TimeControl> {
+        if _periods.is_empty() {
+            return None;
+        }
+        Some(TimeControl { periods: _periods })
+    }
+
+    /// All moves in a fixed time
+    pub fn sudden_death(_time: Duration) -> TimeControl {
+        TimeControl { periods: vec![Period { moves: None, time: _time, timing: None }] }
+    }
+
+    /// A fixed time with a Fischer increment after every move, e.g 3 minutes plus 2 seconds
+    pub fn fischer(_time: Duration, _increment: Duration) -> TimeControl {
+        TimeControl { periods: vec![Period { moves: None, time: _time, timing: Some(Timing::Increment(_increment)) }] }
+    }
+
+    /// A fixed time with a Bronstein delay on every move
+    pub fn bronstein(_time: Duration, _delay: Duration) -> TimeControl {
+        TimeControl { periods: vec![Period { moves: None, time: _time, timing: Some(Timing::Bronstein(_delay)) }] }
+    }
+
+    /// A fixed time with a simple delay on every move
+    pub fn simple_delay(_time: Duration, _delay: Duration) -> TimeControl {
+        TimeControl { periods: vec![Period { moves: None, time: _time, timing: Some(Timing::Delay(_delay)) }] }
+    }
+
+    /// ## `from_pgn_tag`
+    /// Reads the value of a PGN `TimeControl` tag, periods of `moves/seconds` or `seconds` with an optional `+increment` separated by colons.
+    /// ### Parameters
+    /// - `_tag: &str`: The tag value, e.g `"40/5400+30:1800+30"` for 40 moves in 90 minutes then 30 minutes, with 30 seconds added from the first move
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `TimeControl`, `None` if the value is malformed or unknown (`"?"` or `"-"`)
+    pub fn from_pgn_tag(_tag: &str) -> Option<TimeControl> {
+        let periods: Option<Vec<Period>> = _tag.trim().split(':').map(|period| {
+            let (moves, rest) = match period.split_once('/') {
+                Some((moves, rest)) => (Some(moves.parse().ok()?), rest),
+                None => (None, period)
+            };
+            let (time, increment) = match rest.split_once('+') {
+                Some((time, increment)) => (time, Some(increment)),
+                None => (rest, None)
+            };
+
+            Some(Period {
+                moves,
+                time: Duration::from_secs(time.parse().ok()?),
+                timing: match increment {
+                    Some(increment) => Some(Timing::Increment(Duration::from_secs(increment.parse().ok()?))),
+                    None => None
+                }
+            })
+        }).collect();
+
+        TimeControl::new(periods?)
+    }
+}
+
+/// Writes the time control as the value of a PGN `TimeControl` tag. Delays have no PGN notation and are left out
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, period) in self.periods.iter().enumerate() {
+            if index > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = period.moves {
+                write!(f, "{}/", moves)?;
+            }
+            write!(f, "{}", period.time.as_secs())?;
+            if let Some(Timing::Increment(increment)) = period.timing {
+                write!(f, "+{}", increment.as_secs())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The time of one player
+#[derive(Copy, Clone, Debug)]
+struct PlayerTime {
+    remaining: Duration,
+    period: usize,
+    moves_in_period: u32,
+    flagged: bool
+}
+
+/// ## Clock
+/// ### Type: `struct`
+/// A chess clock for both players following a time control.
+/// ### Members:
+/// - `control`: The time control
+/// - `source`: Where the time is read from
+/// - `players`: The time of White and Black
+/// - `running`: The player whose time is running and when their turn started, `None` if the clock is stopped
+#[derive(Clone)]
+pub struct Clock {
+    control: Arc<TimeControl>,
+    source: Arc<dyn TimeSource + Send + Sync>,
+    players: [PlayerTime; 2],
+    running: Option<(Colour, Duration)>
+}
+
+/// Index of a colour in `Clock::players`
+fn index(_colour: Colour) -> usize {
+    match _colour {
+        Colour::White => 0,
+        Colour::Black => 1
+    }
+}
+
+impl Clock {
+    /// Creates a stopped clock reading the system's monotonic time
+    pub fn new(_control: TimeControl) -> Clock {
+        Clock::with_time_source(_control, Arc::new(MonotonicTime::new()))
+    }
+
+    /// ## `with_time_source`
+    /// Creates a stopped clock reading the time from the given source, e.g a `ManualTime` for tests
+    pub fn with_time_source(_control: TimeControl, _source: Arc<dyn TimeSource + Send + Sync>) -> Clock {
+        let player = PlayerTime {
+            remaining: _control.periods[0].time,
+            period: 0,
+            moves_in_period: 0,
+            flagged: false
+        };
+
+        Clock {
+            control: Arc::new(_control),
+            source: _source,
+            players: [player; 2],
+            running: None
+        }
+    }
+
+    /// Returns the time control of the clock
+    pub fn get_time_control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    /// Returns the player whose time is running, `None` if the clock is stopped
+    pub fn get_running(&self) -> Option<Colour> {
+        self.running.map(|(colour, _)| colour)
+    }
+
+    /// Starts the time of a player, stopping the other's without adding increments
+    pub fn start(&mut self, _colour: Colour) {
+        self.stop();
+        self.running = Some((_colour, self.source.now()));
+    }
+
+    /// Stops the clock, the time spent so far is taken from the player whose time was running
+    pub fn stop(&mut self) {
+        if let Some((colour, _)) = self.running {
+            let left = self.get_time_left(colour);
+            let player = &mut self.players[index(colour)];
+            player.flagged |= left.is_zero();
+            player.remaining = left;
+            self.running = None;
+        }
+    }
+
+    /// Returns the time of the current period of a player
+    fn get_period(&self, _colour: Colour) -> &Period {
+        let period = self.players[index(_colour)].period;
+        &self.control.periods[period.min(self.control.periods.len() - 1)]
+    }
+
+    /// The time of the running turn that is taken from the clock, which is less than the time spent with a simple delay
+    fn get_charged(&self, _colour: Colour) -> Duration {
+        match self.running {
+            Some((colour, started)) if colour == _colour => {
+                let spent = self.source.now().saturating_sub(started);
+                match self.get_period(_colour).timing {
+                    Some(Timing::Delay(delay)) => spent.saturating_sub(delay),
+                    _ => spent
+                }
+            }
+            _ => Duration::ZERO
+        }
+    }
+
+    /// Returns the time a player has left, counting the running turn
+    pub fn get_time_left(&self, _colour: Colour) -> Duration {
+        self.players[index(_colour)].remaining.saturating_sub(self.get_charged(_colour))
+    }
+
+    /// Returns true if the time of a player has run out
+    pub fn is_flagged(&self, _colour: Colour) -> bool {
+        self.players[index(_colour)].flagged || self.get_time_left(_colour).is_zero()
+    }
+
+    /// ## `press`
+    /// Ends the turn of the running player, adding any increment or Bronstein delay and the time of the next period if
+    /// the move completes the current one, and starts the time of the other player.
+    /// ### Returns
+    /// Returns false, and stops the clock, if the time of the player ran out before the move. Returns true if the clock is not running
+    pub fn press(&mut self) -> bool {
+        let (colour, started) = match self.running {
+            Some(running) => running,
+            None => return true
+        };
+
+        let spent = self.source.now().saturating_sub(started);
+        let left = self.get_time_left(colour);
+        let period = *self.get_period(colour);
+
+        if left.is_zero() {
+            self.players[index(colour)].flagged = true;
+            self.players[index(colour)].remaining = Duration::ZERO;
+            self.running = None;
+            return false;
+        }
+
+        let player = &mut self.players[index(colour)];
+        player.remaining = left + match period.timing {
+            Some(Timing::Increment(increment)) => increment,
+            Some(Timing::Bronstein(delay)) => spent.min(delay),
+            _ => Duration::ZERO
+        };
+
+        // Completing a period adds the time of the next, the last period repeats
+        player.moves_in_period += 1;
+        if period.moves == Some(player.moves_in_period) {
+            player.period = (player.period + 1).min(self.control.periods.len() - 1);
+            player.moves_in_period = 0;
+            player.remaining += self.control.periods[player.period].time;
+        }
+
+        self.running = Some((colour.opposite(), self.source.now()));
+        true
+    }
+}
+
+impl Game {
+    /// ## `set_clock`
+    /// Attaches a clock to the game and starts the time of the active colour. Moves played with `play_move`,
+    /// `make_uci_move` and `make_move` press the clock, a move made after the player's time ran out is not played
+    pub fn set_clock(&mut self, mut _clock: Clock) {
+        _clock.start(self.active_colour);
+        self.clock = Some(_clock);
+    }
+
+    /// Returns the clock of the game, if it has one
+    pub fn get_clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// ## `check_time`
+    /// Checks whether the time of the active colour ran out and ends the game if so. The game is lost on time,
+    /// unless the opponent could not checkmate by any series of legal moves which makes it a draw.
+    /// Called before every move, and should be called regularly while waiting for one
+    /// ### Returns
+    /// Returns the `GameState` after the check
+    pub fn check_time(&mut self) -> GameState {
+        if let Some(clock) = &mut self.clock {
+            if clock.get_running() == Some(self.active_colour) && clock.is_flagged(self.active_colour) {
+                clock.stop();
+                self.flag_fall(self.active_colour);
+            }
+        }
+
+        self.state
+    }
+
+    /// Presses the clock after a move, the mover lost on time if it ran out during the move
+    pub(crate) fn press_clock(&mut self) {
+        let mover = self.active_colour.opposite();
+        if let Some(clock) = &mut self.clock {
+            if !clock.press() {
+                self.flag_fall(mover);
+            }
+        }
+    }
+
+    /// Ends the game on time for the player whose flag fell
+    fn flag_fall(&mut self, _colour: Colour) {
+        let opponent = _colour.opposite();
+        let winner = if self.board.has_mating_material(opponent) {Some(opponent)} else {None};
+        self.terminate(TerminationReason::TimeForfeit, winner);
+        self.state = GameState::TimeForfeit { winner };
+    }
+}
@@ -0,0 +1,150 @@
+use crate::chess::board::Board;
+use crate::chess::piece_data::Colour;
+use crate::termination::{Termination, TerminationReason};
+use crate::variant;
+use crate::Game;
+
+/// Appends a number as a LEB128 variable-length integer, 7 bits per byte with the high bit set on every byte but the last
+fn write_varint(_bytes: &mut Vec<u8>, mut _value: u32) {
+    while _value >= 0x80 {
+        _bytes.push((_value & 0x7F) as u8 | 0x80);
+        _value >>= 7;
+    }
+    _bytes.push(_value as u8);
+}
+
+/// Reads a LEB128 variable-length integer at the position and moves the position past it
+fn read_varint(_bytes: &[u8], _position: &mut usize) -> Option<u32> {
+    let mut value: u32 = 0;
+
+    for shift in (0..35).step_by(7) {
+        let byte = *_bytes.get(*_position)?;
+        *_position += 1;
+        value |= ((byte & 0x7F) as u32).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Reasons a game can end in the order of their codes in `Game::to_bytes`, code 0 is a game that was not ended by a player or the clock
+const TERMINATION_REASONS: [TerminationReason; 8] = [
+    TerminationReason::Checkmate,
+    TerminationReason::Stalemate,
+    TerminationReason::Resignation,
+    TerminationReason::DrawAgreement,
+    TerminationReason::ThreefoldRepetition,
+    TerminationReason::FiftyMoveRule,
+    TerminationReason::TimeForfeit,
+    TerminationReason::VariantEnd
+];
+
+impl Game {
+    /// ## `to_bytes`
+    /// Returns the whole game in a compact binary form:
+    /// - The starting board as written by `Board::to_bytes`
+    /// - 1 byte: the side to move at the start, 0 for White and 1 for Black
+    /// - 1 byte: the variant, 0 for standard chess and 1 for King of the Hill
+    /// - The halfmove clock, fullmove number and number of moves as variable-length integers
+    /// - Every move as the variable-length index of the move in `get_all_legal_moves` of its position, a single byte for all real positions
+    /// - 1 byte: how the game was ended, the `TerminationReason` counted from 1, or 0 if it goes on or ended by checkmate or stalemate
+    /// - 1 byte only for an ended game: the winner, 0 for White, 1 for Black and 2 for a draw
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping the bytes, `None` if a move of the history cannot be replayed, e.g a pawn moved to the last rank
+    /// with `make_move` that was never promoted with `set_promotion`, or if the game is of a variant from outside this crate
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let variant = variant::get_variants().iter().position(|known| known.get_name() == self.variant.get_name())?;
+        let mut game = self.get_start();
+
+        let mut bytes = game.board.to_bytes();
+        bytes.push(if game.active_colour == Colour::White {0} else {1});
+        bytes.push(variant as u8);
+        write_varint(&mut bytes, game.halfmove_clock);
+        write_varint(&mut bytes, game.fullmove_number);
+        write_varint(&mut bytes, self.history.len() as u32);
+
+        for _move in &self.history {
+            let index = game.get_all_legal_moves().iter().position(|legal| legal == _move)?;
+            write_varint(&mut bytes, index as u32);
+            game.apply_move(*_move);
+        }
+
+        match self.termination {
+            Some(termination) => {
+                let reason = TERMINATION_REASONS.iter().position(|reason| *reason == termination.reason)?;
+                bytes.push(reason as u8 + 1);
+                bytes.push(match termination.winner {
+                    Some(Colour::White) => 0,
+                    Some(Colour::Black) => 1,
+                    None => 2
+                });
+            }
+            None => bytes.push(0)
+        }
+
+        Some(bytes)
+    }
+
+    /// ## `from_bytes`
+    /// Reads a game written by `to_bytes`, replays its moves and ends it the way it was ended.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `Game` after the last move, `None` if the bytes are malformed or a move index is out of range
+    pub fn from_bytes(_bytes: &[u8]) -> Option<Game> {
+        let (board, mut position) = Board::read_bytes(_bytes)?;
+
+        let active_colour = match _bytes.get(position)? {
+            0 => "w",
+            1 => "b",
+            _ => return None
+        };
+        position += 1;
+
+        let variant = variant::get_variants().into_iter().nth(*_bytes.get(position)? as usize)?;
+        position += 1;
+
+        let halfmove_clock = read_varint(_bytes, &mut position)?;
+        let fullmove_number = read_varint(_bytes, &mut position)?;
+        let move_count = read_varint(_bytes, &mut position)?;
+
+        let mut game = Game::from_fen(&format!("{} {} {} {} {} {}",
+            board.get_fen_placement(),
+            active_colour,
+            board.get_fen_castling(),
+            board.get_fen_en_passant(),
+            halfmove_clock,
+            fullmove_number))?;
+        game.set_chess960(board.chess960);
+        game.set_variant(variant);
+
+        for _ in 0..move_count {
+            let index = read_varint(_bytes, &mut position)? as usize;
+            let _move = *game.get_all_legal_moves().get(index)?;
+            game.apply_move(_move);
+        }
+
+        let reason = *_bytes.get(position)? as usize;
+        position += 1;
+        if reason != 0 {
+            let winner = match _bytes.get(position)? {
+                0 => Some(Colour::White),
+                1 => Some(Colour::Black),
+                2 => None,
+                _ => return None
+            };
+            position += 1;
+
+            let termination = Termination { reason: *TERMINATION_REASONS.get(reason - 1)?, winner };
+            game.terminate(termination.reason, termination.winner);
+            game.state = termination.get_game_state(game.variant.as_ref());
+        }
+
+        if position != _bytes.len() {
+            return None;
+        }
+
+        Some(game)
+    }
+}
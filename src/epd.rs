@@ -0,0 +1,189 @@
+use crate::chess::moves::Move;
+use crate::Game;
+use std::fmt;
+
+/// ## Epd
+/// ### Type: `struct`
+/// A position in Extended Position Description, the first four FEN fields followed by operations such as `bm Qd1+; id "WAC.001";`
+/// ### Members:
+/// - `position`: The first four fields of the FEN string, placement, active colour, castling and en passant
+/// - `operations`: Every operation as its opcode and its operands, in the order they were given. Quoted operands are stored without quotes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Epd {
+    pub position: String,
+    pub operations: Vec<(String, Vec<String>)>
+}
+
+impl Epd {
+    /// ## `parse`
+    /// Parses a line of EPD.
+    /// ### Parameters
+    /// - `_line: &str`: The EPD record, e.g `"6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"mate\";"`
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping an `Epd`
+    /// `
+    /// Option<Epd>
+    /// `
+    /// or `None` if the line has fewer than four position fields or an unterminated string
+    pub fn parse(_line: &str) -> Option<Epd> {
+        let mut rest = _line.trim();
+        let mut fields: Vec<&str> = Vec::with_capacity(4);
+
+        while fields.len() < 4 {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+
+        // Split the operations into operands at whitespace and at semicolons, keeping quoted strings whole
+        let mut operations: Vec<(String, Vec<String>)> = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut chars = rest.chars().peekable();
+
+        while let Some(character) = chars.next() {
+            match character {
+                '"' => {
+                    let mut string = String::new();
+                    loop {
+                        match chars.next()? {
+                            '"' => break,
+                            other => string.push(other)
+                        }
+                    }
+                    current.push(string);
+                }
+                ';' => {
+                    if !current.is_empty() {
+                        let opcode = current.remove(0);
+                        operations.push((opcode, std::mem::take(&mut current)));
+                    }
+                }
+                _ if character.is_whitespace() => (),
+                _ => {
+                    let mut token = String::from(character);
+                    while let Some(next) = chars.peek() {
+                        if next.is_whitespace() || *next == ';' || *next == '"' {
+                            break;
+                        }
+                        token.push(*next);
+                        chars.next();
+                    }
+                    current.push(token);
+                }
+            }
+        }
+
+        // The last operation may be missing its semicolon
+        if !current.is_empty() {
+            let opcode = current.remove(0);
+            operations.push((opcode, current));
+        }
+
+        Some(Epd { position: fields.join(" "), operations })
+    }
+
+    /// Returns the operands of the first operation with the given opcode
+    pub fn get_operation(&self, _opcode: &str) -> Option<&[String]> {
+        self.operations.iter()
+            .find(|(opcode, _)| opcode == _opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    /// Returns the `id` of the position
+    pub fn get_id(&self) -> Option<&str> {
+        self.get_operation("id")?.first().map(String::as_str)
+    }
+
+    /// Returns the comment `c0` to `c9` of the given number
+    pub fn get_comment(&self, _number: u8) -> Option<&str> {
+        self.get_operation(&format!("c{}", _number))?.first().map(String::as_str)
+    }
+
+    /// Returns the number of moves of the direct mate given by `dm`
+    pub fn get_direct_mate(&self) -> Option<u32> {
+        self.get_operation("dm")?.first()?.parse().ok()
+    }
+
+    /// ## `to_game`
+    /// Creates the game of the position, using the `hmvc` and `fmvn` operations for the clocks if they are given
+    pub fn to_game(&self) -> Option<Game> {
+        let halfmove_clock = self.get_operation("hmvc").and_then(|operands| operands.first()).map_or("0", String::as_str);
+        let fullmove_number = self.get_operation("fmvn").and_then(|operands| operands.first()).map_or("1", String::as_str);
+
+        Game::from_fen(&format!("{} {} {}", self.position, halfmove_clock, fullmove_number))
+    }
+
+    /// Returns the moves of an operation with SAN operands such as `bm` or `am`, `None` if an operand is not a legal move
+    fn get_san_moves(&self, _game: &Game, _opcode: &str) -> Option<Vec<Move>> {
+        match self.get_operation(_opcode) {
+            Some(operands) => operands.iter().map(|san| _game.san_to_move(san)).collect(),
+            None => Some(Vec::new())
+        }
+    }
+
+    /// Returns the best moves given by `bm`, `None` if one of them is not a legal move in the position
+    pub fn get_best_moves(&self) -> Option<Vec<Move>> {
+        self.get_san_moves(&self.to_game()?, "bm")
+    }
+
+    /// Returns the moves to avoid given by `am`, `None` if one of them is not a legal move in the position
+    pub fn get_avoid_moves(&self) -> Option<Vec<Move>> {
+        self.get_san_moves(&self.to_game()?, "am")
+    }
+
+    /// ## `is_solved_by`
+    /// Checks the answer of a search against the `bm`, `am` and `dm` operations of the position.
+    /// Every operation that is given must be satisfied, positions with none of them are never solved.
+    /// ### Parameters
+    /// - `_move: &Move`: The best move found by the search
+    /// - `_mate_in: Option<i32>`: The number of moves to mate found by the search, if any
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping a `bool`, `None` if the position or its moves cannot be read
+    pub fn is_solved_by(&self, _move: &Move, _mate_in: Option<i32>) -> Option<bool> {
+        let best_moves = self.get_best_moves()?;
+        let avoid_moves = self.get_avoid_moves()?;
+        let direct_mate = self.get_direct_mate();
+
+        if best_moves.is_empty() && avoid_moves.is_empty() && direct_mate.is_none() {
+            return Some(false);
+        }
+
+        Some((best_moves.is_empty() || best_moves.contains(_move))
+            && !avoid_moves.contains(_move)
+            && direct_mate.is_none_or(|moves| _mate_in.is_some_and(|mate| mate > 0 && mate as u32 <= moves)))
+    }
+}
+
+/// Writes the position back as a line of EPD
+impl fmt::Display for Epd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.position)?;
+
+        for (opcode, operands) in &self.operations {
+            write!(f, " {}", opcode)?;
+            for operand in operands {
+                // Operands with spaces and the strings of `id` and the comments `c0` to `c9` are written quoted
+                if operand.is_empty() || operand.contains(char::is_whitespace) || is_string_opcode(opcode) {
+                    write!(f, " \"{}\"", operand)?;
+                }
+                else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+            write!(f, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true for the opcodes whose operand is a string, `id` and the comments `c0` to `c9`
+fn is_string_opcode(_opcode: &str) -> bool {
+    let bytes = _opcode.as_bytes();
+    _opcode == "id" || (bytes.len() == 2 && bytes[0] == b'c' && bytes[1].is_ascii_digit())
+}
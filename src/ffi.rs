@@ -0,0 +1,281 @@
+//! A C API around `Game`, enabled by the `ffi` feature. The header `include/azeezd_chess.h` is generated from this
+//! module with `cbindgen --config cbindgen.toml --output include/azeezd_chess.h`.
+//!
+//! Games are opaque `ChessGame` handles created by `chess_game_new` or `chess_game_from_fen` and released with
+//! `chess_game_free`. Every function returns a `ChessError`, `CHESS_ERROR_OK` on success, and writes its results
+//! through pointers given by the caller. Panics never cross the boundary, they are returned as `CHESS_ERROR_PANIC`
+use crate::chess::moves::Move;
+use crate::termination::TerminationReason;
+use crate::{Game, GameState};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// The most legal moves a position can have is 218, a buffer of this many `ChessMove` always fits them
+pub const CHESS_MAX_MOVES: usize = 256;
+
+/// Results of the C API functions
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ChessError {
+    Ok = 0,
+    /// A pointer argument was null
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// The FEN string could not be read
+    InvalidFen = 3,
+    /// The move is not a UCI move such as `e2e4` or `e7e8q`
+    InvalidMove = 4,
+    /// The move is not legal in the position
+    IllegalMove = 5,
+    /// The game has ended and no more moves can be played
+    GameOver = 6,
+    /// The buffer is too small for the result, the required size was still written
+    BufferTooSmall = 7,
+    /// The library panicked, the game may be in an inconsistent state
+    Panic = 8
+}
+
+/// States of a game as seen from C, checkmate, stalemate and the ways a game ends are told apart
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ChessState {
+    InProgress = 0,
+    Check = 1,
+    Checkmate = 2,
+    Stalemate = 3,
+    /// Drawn by agreement, threefold repetition or the fifty move rule
+    Draw = 4,
+    Resigned = 5,
+    TimeForfeit = 6,
+    /// Won or drawn by a rule of the variant the game is played with
+    VariantEnd = 7
+}
+
+/// ## ChessMove
+/// ### Type: `struct`
+/// A move in UCI notation as a NUL terminated string, e.g `"e2e4"` or `"e7e8q"`
+/// ### Members:
+/// - `uci`: The characters of the move followed by NUL bytes
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct ChessMove {
+    pub uci: [c_char; 6]
+}
+
+/// An opaque game handle
+pub struct ChessGame {
+    game: Game
+}
+
+/// Runs the body of an API function, turning a panic into `ChessError::Panic`
+fn guard<F: FnOnce() -> ChessError>(_body: F) -> ChessError {
+    panic::catch_unwind(AssertUnwindSafe(_body)).unwrap_or(ChessError::Panic)
+}
+
+/// Reads a NUL terminated string given by the caller
+unsafe fn read_str<'a>(_string: *const c_char) -> Result<&'a str, ChessError> {
+    if _string.is_null() {
+        return Err(ChessError::NullPointer);
+    }
+    CStr::from_ptr(_string).to_str().map_err(|_| ChessError::InvalidUtf8)
+}
+
+/// Boxes a game and hands its handle to the caller
+unsafe fn write_handle(_game: Game, _out: *mut *mut ChessGame) -> ChessError {
+    *_out = Box::into_raw(Box::new(ChessGame { game: _game }));
+    ChessError::Ok
+}
+
+/// ## `chess_game_new`
+/// Creates a game from the standard starting position
+/// ### Safety
+/// `out` must be null or valid for writing a pointer. The handle written to it must be released with `chess_game_free`
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_new(out: *mut *mut ChessGame) -> ChessError {
+    guard(|| {
+        if out.is_null() {
+            return ChessError::NullPointer;
+        }
+        write_handle(Game::new(), out)
+    })
+}
+
+/// ## `chess_game_from_fen`
+/// Creates a game from a FEN string
+/// ### Safety
+/// `fen` must be null or a NUL terminated string and `out` null or valid for writing a pointer.
+/// The handle written to it must be released with `chess_game_free`
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_from_fen(fen: *const c_char, out: *mut *mut ChessGame) -> ChessError {
+    guard(|| {
+        if out.is_null() {
+            return ChessError::NullPointer;
+        }
+        let fen = match read_str(fen) {
+            Ok(fen) => fen,
+            Err(error) => return error
+        };
+        match Game::from_fen(fen) {
+            Some(game) => write_handle(game, out),
+            None => ChessError::InvalidFen
+        }
+    })
+}
+
+/// ## `chess_game_free`
+/// Releases a game handle, null is ignored
+/// ### Safety
+/// `game` must be null or a handle from this library that was not released yet
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_free(game: *mut ChessGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// ## `chess_game_make_move`
+/// Plays a move given in UCI notation, e.g `"e2e4"` or `"e7e8q"`
+/// ### Safety
+/// `game` must be null or a live handle and `uci` null or a NUL terminated string
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_make_move(game: *mut ChessGame, uci: *const c_char) -> ChessError {
+    guard(|| {
+        let game = match game.as_mut() {
+            Some(handle) => &mut handle.game,
+            None => return ChessError::NullPointer
+        };
+        let _move = match read_str(uci).map(Move::from_uci) {
+            Ok(Some(_move)) => _move,
+            Ok(None) => return ChessError::InvalidMove,
+            Err(error) => return error
+        };
+
+        if game.is_over() {
+            return ChessError::GameOver;
+        }
+        match game.play_move(_move) {
+            Some(_) => ChessError::Ok,
+            None => ChessError::IllegalMove
+        }
+    })
+}
+
+/// ## `chess_game_legal_moves`
+/// Writes the legal moves of the active colour into a buffer, none once the game has ended.
+/// `count` receives the number of legal moves, if it is larger than `capacity` only the first `capacity`
+/// moves are written and `CHESS_ERROR_BUFFER_TOO_SMALL` is returned
+/// ### Safety
+/// `game` must be null or a live handle, `moves` valid for writing `capacity` moves (or null if `capacity` is 0)
+/// and `count` null or valid for writing
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_legal_moves(game: *const ChessGame, moves: *mut ChessMove, capacity: usize, count: *mut usize) -> ChessError {
+    guard(|| {
+        let game = match game.as_ref() {
+            Some(handle) => &handle.game,
+            None => return ChessError::NullPointer
+        };
+        if count.is_null() || (moves.is_null() && capacity > 0) {
+            return ChessError::NullPointer;
+        }
+
+        let legal = if game.is_over() {Vec::new()} else {game.get_all_legal_moves()};
+        *count = legal.len();
+
+        for (index, _move) in legal.iter().take(capacity).enumerate() {
+            let mut uci = [0 as c_char; 6];
+            for (slot, byte) in uci.iter_mut().zip(_move.to_uci().bytes()) {
+                *slot = byte as c_char;
+            }
+            ptr::write(moves.add(index), ChessMove { uci });
+        }
+
+        if legal.len() > capacity {ChessError::BufferTooSmall} else {ChessError::Ok}
+    })
+}
+
+/// ## `chess_game_state`
+/// Writes the state of the game
+/// ### Safety
+/// `game` must be null or a live handle and `state` null or valid for writing
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_state(game: *const ChessGame, state: *mut ChessState) -> ChessError {
+    guard(|| {
+        let game = match game.as_ref() {
+            Some(handle) => &handle.game,
+            None => return ChessError::NullPointer
+        };
+        if state.is_null() {
+            return ChessError::NullPointer;
+        }
+
+        *state = match game.get_termination().map(|termination| termination.reason) {
+            Some(TerminationReason::Checkmate) => ChessState::Checkmate,
+            Some(TerminationReason::Stalemate) => ChessState::Stalemate,
+            Some(TerminationReason::Resignation) => ChessState::Resigned,
+            Some(TerminationReason::TimeForfeit) => ChessState::TimeForfeit,
+            Some(TerminationReason::VariantEnd) => ChessState::VariantEnd,
+            Some(_) => ChessState::Draw,
+            None => match game.get_game_state() {
+                GameState::Check => ChessState::Check,
+                GameState::TimeForfeit { .. } => ChessState::TimeForfeit,
+                _ => ChessState::InProgress
+            }
+        };
+        ChessError::Ok
+    })
+}
+
+/// ## `chess_game_fen`
+/// Writes the FEN string of the current position as a NUL terminated string. `length` receives the length of the
+/// FEN without the NUL, if the buffer cannot hold it and the NUL nothing is written and `CHESS_ERROR_BUFFER_TOO_SMALL`
+/// is returned. 128 bytes always fit a FEN string
+/// ### Safety
+/// `game` must be null or a live handle, `buffer` valid for writing `capacity` bytes (or null if `capacity` is 0)
+/// and `length` null or valid for writing
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_fen(game: *const ChessGame, buffer: *mut c_char, capacity: usize, length: *mut usize) -> ChessError {
+    guard(|| {
+        let game = match game.as_ref() {
+            Some(handle) => &handle.game,
+            None => return ChessError::NullPointer
+        };
+        if length.is_null() || (buffer.is_null() && capacity > 0) {
+            return ChessError::NullPointer;
+        }
+
+        let fen = game.to_fen();
+        *length = fen.len();
+        if fen.len() >= capacity {
+            return ChessError::BufferTooSmall;
+        }
+
+        ptr::copy_nonoverlapping(fen.as_ptr() as *const c_char, buffer, fen.len());
+        *buffer.add(fen.len()) = 0;
+        ChessError::Ok
+    })
+}
+
+/// ## `chess_error_message`
+/// Returns a static NUL terminated description of an error.
+/// The error is taken as an `int` so that values outside of `ChessError` give a fallback message instead of undefined behaviour
+#[no_mangle]
+pub extern "C" fn chess_error_message(error: c_int) -> *const c_char {
+    let known = [ChessError::Ok, ChessError::NullPointer, ChessError::InvalidUtf8, ChessError::InvalidFen, ChessError::InvalidMove,
+        ChessError::IllegalMove, ChessError::GameOver, ChessError::BufferTooSmall, ChessError::Panic];
+    let message: &'static [u8] = match known.iter().find(|known| **known as c_int == error) {
+        Some(ChessError::Ok) => b"no error\0",
+        Some(ChessError::NullPointer) => b"a pointer argument was null\0",
+        Some(ChessError::InvalidUtf8) => b"a string argument was not valid UTF-8\0",
+        Some(ChessError::InvalidFen) => b"invalid FEN string\0",
+        Some(ChessError::InvalidMove) => b"the move is not in UCI notation\0",
+        Some(ChessError::IllegalMove) => b"illegal move\0",
+        Some(ChessError::GameOver) => b"the game is over\0",
+        Some(ChessError::BufferTooSmall) => b"the buffer is too small\0",
+        Some(ChessError::Panic) => b"internal error\0",
+        None => b"unknown error\0"
+    };
+    message.as_ptr() as *const c_char
+}
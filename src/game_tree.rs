@@ -0,0 +1,517 @@
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece_data::Colour;
+use crate::pgn::{self, Pgn, Token};
+use crate::Game;
+use std::fmt;
+
+/// Index of a node in a `GameTree`, the root is 0
+pub type NodeId = usize;
+
+/// Colours of arrows and highlighted squares, written as their first letter in PGN comments
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HighlightColour {
+    Green,
+    Red,
+    Yellow,
+    Blue
+}
+
+impl HighlightColour {
+    fn from_letter(_letter: char) -> Option<HighlightColour> {
+        match _letter {
+            'G' => Some(HighlightColour::Green),
+            'R' => Some(HighlightColour::Red),
+            'Y' => Some(HighlightColour::Yellow),
+            'B' => Some(HighlightColour::Blue),
+            _ => None
+        }
+    }
+
+    fn get_letter(&self) -> char {
+        match self {
+            HighlightColour::Green => 'G',
+            HighlightColour::Red => 'R',
+            HighlightColour::Yellow => 'Y',
+            HighlightColour::Blue => 'B'
+        }
+    }
+}
+
+/// An arrow drawn from one square to another
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arrow {
+    pub from: (i8, i8),
+    pub to: (i8, i8),
+    pub colour: HighlightColour
+}
+
+/// A highlighted square
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SquareHighlight {
+    pub square: (i8, i8),
+    pub colour: HighlightColour
+}
+
+/// ## Node
+/// ### Type: `struct`
+/// A position of a `GameTree` and the move that led to it.
+/// ### Members:
+/// - `_move`: The move from the parent, `None` for the root
+/// - `game`: The game after the move
+/// - `parent`: The node before the move, `None` for the root
+/// - `children`: The moves played from the position, the first is the main line and the others are variations
+/// - `comment`: The comment after the move, or before the first move for the root
+/// - `nags`: Numeric Annotation Glyphs of the move, e.g 1 for `!` and 4 for `??`
+/// - `arrows`, `squares`: Arrows and highlighted squares, written in comments as `[%cal Ge2e4]` and `[%csl Rd4]`
+#[derive(Clone, Debug)]
+pub struct Node {
+    _move: Option<Move>,
+    game: Game,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    pub comment: String,
+    pub nags: Vec<u8>,
+    pub arrows: Vec<Arrow>,
+    pub squares: Vec<SquareHighlight>
+}
+
+impl Node {
+    fn new(_move: Option<Move>, _game: Game, _parent: Option<NodeId>) -> Node {
+        Node {
+            _move,
+            game: _game,
+            parent: _parent,
+            children: Vec::new(),
+            comment: String::new(),
+            nags: Vec::new(),
+            arrows: Vec::new(),
+            squares: Vec::new()
+        }
+    }
+
+    /// Returns the move that led to the node, `None` for the root
+    pub fn get_move(&self) -> Option<Move> {
+        self._move
+    }
+
+    /// Returns the game at the position of the node
+    pub fn get_game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Returns the node before the move, `None` for the root
+    pub fn get_parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// Returns the nodes after the position, main line first
+    pub fn get_children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Reads the arrows and squares out of the text of a comment and keeps the rest as the comment
+    fn set_comment_text(&mut self, _text: &str) {
+        let mut comment = String::new();
+        let mut rest = _text;
+
+        while let Some(start) = rest.find("[%") {
+            comment.push_str(&rest[..start]);
+            let end = match rest[start..].find(']') {
+                Some(end) => start + end,
+                None => break
+            };
+
+            let command = &rest[start + 2..end];
+            let (name, values) = command.split_once(' ').unwrap_or((command, ""));
+            for value in values.split(',').map(str::trim).filter(|value| !value.is_empty()) {
+                let mut letters = value.chars();
+                let colour = letters.next().and_then(HighlightColour::from_letter);
+                let squares: String = letters.collect();
+                if !squares.is_ascii() {
+                    continue;
+                }
+
+                match (name, colour, squares.len()) {
+                    ("cal", Some(colour), 4) if valid_square(&squares[0..2]) && valid_square(&squares[2..4]) => self.arrows.push(Arrow {
+                        from: Board::filerank_to_num(&String::from(&squares[0..2])),
+                        to: Board::filerank_to_num(&String::from(&squares[2..4])),
+                        colour
+                    }),
+                    ("csl", Some(colour), 2) if valid_square(&squares) => self.squares.push(SquareHighlight {
+                        square: Board::filerank_to_num(&squares),
+                        colour
+                    }),
+                    _ => ()
+                }
+            }
+            rest = &rest[end + 1..];
+        }
+        comment.push_str(rest);
+
+        let comment = comment.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if !comment.is_empty() {
+            if !self.comment.is_empty() {
+                self.comment.push(' ');
+            }
+            self.comment.push_str(&comment);
+        }
+    }
+
+    /// Returns the comment with the arrows and squares as it is written in PGN, `None` if there is nothing to write
+    fn get_comment_text(&self) -> Option<String> {
+        let mut parts: Vec<String> = Vec::new();
+
+        if !self.squares.is_empty() {
+            let squares: Vec<String> = self.squares.iter()
+                .map(|highlight| format!("{}{}", highlight.colour.get_letter(), Board::num_to_filerank(&highlight.square)))
+                .collect();
+            parts.push(format!("[%csl {}]", squares.join(",")));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self.arrows.iter()
+                .map(|arrow| format!("{}{}{}", arrow.colour.get_letter(), Board::num_to_filerank(&arrow.from), Board::num_to_filerank(&arrow.to)))
+                .collect();
+            parts.push(format!("[%cal {}]", arrows.join(",")));
+        }
+        if !self.comment.is_empty() {
+            parts.push(self.comment.clone());
+        }
+
+        if parts.is_empty() {None} else {Some(format!("{{{}}}", parts.join(" ")))}
+    }
+}
+
+/// Returns true for a square such as "e4"
+fn valid_square(_square: &str) -> bool {
+    let bytes = _square.as_bytes();
+    bytes.len() == 2 && (b'a'..=b'h').contains(&bytes[0]) && (b'1'..=b'8').contains(&bytes[1])
+}
+
+/// Returns the NAG of a move suffix such as `!?`
+fn suffix_nag(_suffix: &str) -> Option<u8> {
+    match _suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None
+    }
+}
+
+/// ## GameTree
+/// ### Type: `struct`
+/// A game with variations and annotations. Every node is a position, its children the moves played from it.
+/// ### Members:
+/// - `nodes`: Every node by its id, `None` for deleted nodes
+/// - `current`: The node being looked at
+/// - `tags`: The PGN tags of the game
+/// - `result`: The PGN result of the game
+pub struct GameTree {
+    nodes: Vec<Option<Node>>,
+    current: NodeId,
+    pub tags: Vec<(String, String)>,
+    pub result: String
+}
+
+impl GameTree {
+    /// Creates a tree with only a starting position
+    pub fn new(_start: Game) -> GameTree {
+        GameTree {
+            nodes: vec![Some(Node::new(None, _start, None))],
+            current: 0,
+            tags: Vec::new(),
+            result: String::from("*")
+        }
+    }
+
+    /// ## `from_game`
+    /// Creates a tree with the moves of a game as its main line, the current node is the last position
+    pub fn from_game(_game: &Game) -> GameTree {
+        let mut tree = GameTree::new(_game.get_start());
+        for _move in _game.get_history() {
+            tree.add_move(*_move);
+        }
+        tree.result = String::from(_game.get_result());
+        tree
+    }
+
+    /// Returns the id of the root, the starting position
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    /// Returns the id of the current node
+    pub fn get_current(&self) -> NodeId {
+        self.current
+    }
+
+    /// Returns a node, `None` if there is no node with the id
+    pub fn get_node(&self, _id: NodeId) -> Option<&Node> {
+        self.nodes.get(_id)?.as_ref()
+    }
+
+    /// Returns a node to change its annotations
+    pub fn get_node_mut(&mut self, _id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(_id)?.as_mut()
+    }
+
+    /// Returns the game at the current node
+    pub fn get_game(&self) -> &Game {
+        &self.node(self.current).game
+    }
+
+    /// Returns a node that is known to exist
+    fn node(&self, _id: NodeId) -> &Node {
+        self.nodes[_id].as_ref().expect("node ids in the tree are never deleted nodes")
+    }
+
+    fn node_mut(&mut self, _id: NodeId) -> &mut Node {
+        self.nodes[_id].as_mut().expect("node ids in the tree are never deleted nodes")
+    }
+
+    /// ## `add_move`
+    /// Plays a move from the current node and makes the node after it current. A move that is already a child is
+    /// followed, a new move is added as the main line if the node has no children and as a variation otherwise.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the id of the node after the move, `None` if the move is illegal
+    pub fn add_move(&mut self, _move: Move) -> Option<NodeId> {
+        if let Some(child) = self.node(self.current).children.iter().find(|child| self.node(**child)._move == Some(_move)) {
+            self.current = *child;
+            return Some(self.current);
+        }
+
+        let mut game = self.get_game().clone();
+        game.play_move(_move)?;
+
+        let id = self.nodes.len();
+        self.nodes.push(Some(Node::new(Some(_move), game, Some(self.current))));
+        self.node_mut(self.current).children.push(id);
+        self.current = id;
+        Some(id)
+    }
+
+    /// Same as `add_move` with a move in SAN
+    pub fn add_san(&mut self, _san: &str) -> Option<NodeId> {
+        let _move = self.get_game().san_to_move(_san)?;
+        self.add_move(_move)
+    }
+
+    /// ## `go_to`
+    /// Makes a node current, returns false if there is no node with the id
+    pub fn go_to(&mut self, _id: NodeId) -> bool {
+        if self.get_node(_id).is_none() {
+            return false;
+        }
+        self.current = _id;
+        true
+    }
+
+    /// Goes one move back, returns false at the root
+    pub fn go_back(&mut self) -> bool {
+        match self.node(self.current).parent {
+            Some(parent) => {
+                self.current = parent;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Goes one move forward along the main line, returns false at the end of the line
+    pub fn go_forward(&mut self) -> bool {
+        match self.node(self.current).children.first() {
+            Some(child) => {
+                self.current = *child;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Returns the nodes of the main line after the root
+    pub fn get_main_line(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut node = self.root();
+        while let Some(child) = self.node(node).children.first() {
+            line.push(*child);
+            node = *child;
+        }
+        line
+    }
+
+    /// ## `promote_variation`
+    /// Makes the variation a node is in the main line where it branches off, moving it before its siblings.
+    /// Returns false if the node is not in a variation
+    pub fn promote_variation(&mut self, _id: NodeId) -> bool {
+        let mut node = _id;
+
+        while let Some(parent) = self.get_node(node).and_then(|node| node.parent) {
+            let siblings = &mut self.node_mut(parent).children;
+            let index = siblings.iter().position(|child| *child == node).expect("a node is a child of its parent");
+            if index > 0 {
+                let variation = siblings.remove(index);
+                siblings.insert(0, variation);
+                return true;
+            }
+            node = parent;
+        }
+
+        false
+    }
+
+    /// ## `delete_variation`
+    /// Deletes a node and every move after it. The root cannot be deleted.
+    /// If the current node is deleted the node before the deleted one becomes current
+    pub fn delete_variation(&mut self, _id: NodeId) -> bool {
+        let parent = match self.get_node(_id).and_then(|node| node.parent) {
+            Some(parent) => parent,
+            None => return false
+        };
+
+        self.node_mut(parent).children.retain(|child| *child != _id);
+
+        let mut deleted = vec![_id];
+        while let Some(id) = deleted.pop() {
+            if id == self.current {
+                self.current = parent;
+            }
+            if let Some(node) = self.nodes[id].take() {
+                deleted.extend(node.children);
+            }
+        }
+
+        true
+    }
+
+    /// ## `parse`
+    /// Reads the first game of PGN text with its variations, comments, NAGs, move suffixes such as `!?` and the arrows and
+    /// squares of comments. The current node is the end of the main line
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `GameTree`, `None` if there is no game or a move is illegal
+    pub fn parse(_text: &str) -> Option<GameTree> {
+        let mut tags = Vec::new();
+        let mut tree: Option<GameTree> = None;
+        let mut variations: Vec<NodeId> = Vec::new();
+
+        for token in pgn::tokenize(_text) {
+            // The tree starts at the first token after the tags, from the position of the FEN tag
+            if tree.is_none() && !matches!(token, Token::Tag(_, _)) {
+                let start = Pgn { tags: tags.clone(), moves: Vec::new(), result: String::from("*") }.get_start()?;
+                let mut new_tree = GameTree::new(start);
+                new_tree.tags = std::mem::take(&mut tags);
+                tree = Some(new_tree);
+            }
+
+            match (token, tree.as_mut()) {
+                (Token::Tag(name, value), None) => tags.push((name, value)),
+                (Token::Tag(_, _), Some(_)) => break,
+                (Token::San(san), Some(tree)) => {
+                    let end = san.trim_end_matches(['!', '?']).len();
+                    tree.add_san(&san)?;
+                    if let Some(nag) = suffix_nag(&san[end..]) {
+                        tree.node_mut(tree.current).nags.push(nag);
+                    }
+                }
+                (Token::Comment(comment), Some(tree)) => {
+                    let current = tree.current;
+                    tree.node_mut(current).set_comment_text(&comment);
+                }
+                (Token::Nag(nag), Some(tree)) => {
+                    let current = tree.current;
+                    tree.node_mut(current).nags.push(nag);
+                }
+                // A variation is an alternative to the last move, it starts from the position before it
+                (Token::StartVariation, Some(tree)) => {
+                    variations.push(tree.current);
+                    tree.go_back();
+                }
+                (Token::EndVariation, Some(tree)) => {
+                    if let Some(node) = variations.pop() {
+                        tree.current = node;
+                    }
+                }
+                (Token::Result(result), Some(tree)) => {
+                    tree.result = result;
+                    break;
+                }
+                _ => ()
+            }
+        }
+
+        let mut tree = tree?;
+        tree.current = tree.get_main_line().last().copied().unwrap_or(0);
+        Some(tree)
+    }
+
+    /// Adds the PGN words of the moves after a node, its main line with the variations of every move in parentheses
+    fn write_line(&self, _words: &mut Vec<String>, _id: NodeId, mut _number_needed: bool) {
+        let mut node = _id;
+
+        while let Some((main, variations)) = self.node(node).children.split_first() {
+            _number_needed = self.write_move(_words, *main, _number_needed);
+
+            for variation in variations {
+                let first = _words.len();
+                let number_needed = self.write_move(_words, *variation, true);
+                self.write_line(_words, *variation, number_needed);
+                _words[first].insert(0, '(');
+                _words.last_mut().expect("a variation has a move").push(')');
+                _number_needed = true;
+            }
+
+            node = *main;
+        }
+    }
+
+    /// Adds the words of one move, returns true if the next move needs its number because a comment follows this one
+    fn write_move(&self, _words: &mut Vec<String>, _id: NodeId, _number_needed: bool) -> bool {
+        let node = self.node(_id);
+        let before = &self.node(node.parent.expect("only moves are written")).game;
+        let number = before.fullmove_number;
+
+        if before.get_active_colour() == Colour::White {
+            _words.push(format!("{}.", number));
+        }
+        else if _number_needed {
+            _words.push(format!("{}...", number));
+        }
+
+        _words.push(before.move_to_san(&node._move.expect("only moves are written")));
+        for nag in &node.nags {
+            _words.push(format!("${}", nag));
+        }
+
+        match node.get_comment_text() {
+            Some(comment) => {
+                _words.push(comment);
+                true
+            }
+            None => false
+        }
+    }
+}
+
+impl Default for GameTree {
+    fn default() -> Self {
+        GameTree::new(Game::new())
+    }
+}
+
+/// Writes the whole tree as PGN, variations in parentheses after the move they replace
+impl fmt::Display for GameTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        pgn::write_tags(f, &self.tags, &self.result)?;
+
+        let mut words = Vec::new();
+        let root_comment = self.node(self.root()).get_comment_text();
+        let number_needed = root_comment.is_some() || self.node(self.root()).game.get_active_colour() == Colour::Black;
+        words.extend(root_comment);
+        self.write_line(&mut words, self.root(), number_needed);
+        words.push(self.result.clone());
+
+        pgn::write_wrapped(f, &words)
+    }
+}
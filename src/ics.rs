@@ -0,0 +1,1006 @@
+//! A chess server for telnet clients in the style of the Internet Chess Servers, run with the `ics-server` binary.
+//!
+//! Users log in with a handle and type one command per line: they seek and accept games, play moves in SAN or
+//! coordinates, talk to each other and observe the games of others. After every move the board is sent as text to
+//! the players and observers, in style 1 (drawn for people) or style 12 (one line for interface programs) as on FICS.
+//! Seeks and games are numbered from 1, numbers are reused once free
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece_data::{Colour, PieceType};
+use crate::clock::{Clock, TimeControl};
+use crate::termination::{Termination, TerminationReason};
+use crate::{Game, GameState};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Shown after every answer of the server
+const PROMPT: &str = "fics% ";
+
+/// Longer lines are cut
+const MAX_LINE: u64 = 1024;
+
+/// Seeks a user can have open at once
+const MAX_SEEKS: usize = 3;
+
+const WELCOME: &str = "Welcome to the chess server. Log in with a handle of 3 to 17 letters, type \"help\" once logged in.";
+
+const HELP: &str = "\
+While playing, moves can be given in SAN (Nf3, exd5, O-O, e8=Q) or coordinates (g1f3, g1-f3, e7e8q).
+Commands:
+  who                          Lists the users logged in
+  seek [<minutes> [<increment>]] [white|black]
+                               Seeks a game, 5 0 by default and untimed for 0 0
+  unseek                       Withdraws your seeks
+  sought                       Lists the open seeks
+  play <seek>                  Accepts a seek and starts the game
+  games                        Lists the games being played
+  observe <game>               Follows a game
+  unobserve [<game>]           Stops following a game, or all of them
+  refresh                      Shows the board of your game again
+  moves [<game>]               Lists the moves of a game
+  style <1|12>                 Boards drawn as text (1) or as a single line for interfaces (12)
+  resign                       Resigns your game
+  draw                         Claims a draw, offers one or accepts the offer of your opponent
+  decline                      Declines the draw offered by your opponent
+  say <message>                Talks to your opponent
+  tell <handle> <message>      Talks to a user
+  kibitz <message>             Talks to the players and observers of your game
+  quit                         Logs out, a game being played is lost";
+
+/// How boards are sent to a user
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Style {
+    /// Style 1, the board drawn with text
+    Text,
+    /// Style 12, everything about the position on one line starting with `<12>`
+    Line
+}
+
+/// ## User
+/// ### Type: `struct`
+/// A user logged in
+/// ### Members:
+/// - `output`: Sends text to the connection of the user
+/// - `style`: How boards are sent to the user
+/// - `game`: The number of the game the user plays, if any
+struct User {
+    output: Sender<String>,
+    style: Style,
+    game: Option<u32>
+}
+
+/// ## Seek
+/// ### Type: `struct`
+/// An open offer to play a game
+/// ### Members:
+/// - `number`: The number to accept it with
+/// - `handle`: The user seeking
+/// - `minutes`: The time of each player, 0 for an untimed game
+/// - `increment`: The seconds added after every move
+/// - `colour`: The colour the seeker wants to play, `None` to let chance decide
+struct Seek {
+    number: u32,
+    handle: String,
+    minutes: u64,
+    increment: u64,
+    colour: Option<Colour>
+}
+
+/// ## LastMove
+/// ### Type: `struct`
+/// The last move of a game as shown on boards
+/// ### Members:
+/// - `verbose`: The move with the piece and both squares, e.g `"P/e2-e4"` or `"o-o"`
+/// - `san`: The move in SAN
+/// - `time`: How long the player thought about it
+struct LastMove {
+    verbose: String,
+    san: String,
+    time: Duration
+}
+
+/// ## Table
+/// ### Type: `struct`
+/// A game being played on the server
+/// ### Members:
+/// - `game`: The game, with a clock unless it is untimed
+/// - `white`, `black`: The handles of the players
+/// - `minutes`, `increment`: The time control it was sought with
+/// - `observers`: The handles of the users observing it
+/// - `last_move`: The last move played, if any
+/// - `turn_started`: When the player to move got the turn
+struct Table {
+    game: Game,
+    white: String,
+    black: String,
+    minutes: u64,
+    increment: u64,
+    observers: Vec<String>,
+    last_move: Option<LastMove>,
+    turn_started: Instant
+}
+
+impl Table {
+    /// Returns the handle of the player of a colour
+    fn handle(&self, _colour: Colour) -> &str {
+        match _colour {
+            Colour::White => &self.white,
+            Colour::Black => &self.black
+        }
+    }
+
+    /// Returns the colour a user plays in this game, `None` for observers
+    fn colour_of(&self, _handle: &str) -> Option<Colour> {
+        if _handle == self.white {
+            Some(Colour::White)
+        }
+        else if _handle == self.black {
+            Some(Colour::Black)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Returns the players and observers of the game
+    fn audience(&self) -> Vec<String> {
+        let mut audience = vec![self.white.clone(), self.black.clone()];
+        audience.extend(self.observers.iter().cloned());
+        audience
+    }
+
+    /// Returns the time left of a player, `None` in an untimed game
+    fn time_left(&self, _colour: Colour) -> Option<Duration> {
+        self.game.get_clock().map(|clock| clock.get_time_left(_colour))
+    }
+
+    /// Returns how the game ended in the words of the server, e.g `"alice resigns"` or `"Game drawn by repetition"`
+    fn describe_end(&self, _termination: Option<Termination>) -> String {
+        let termination = match _termination {
+            Some(termination) => termination,
+            None => return String::from("Game aborted")
+        };
+
+        match (termination.reason, termination.winner) {
+            (TerminationReason::Checkmate, Some(winner)) => format!("{} checkmated", self.handle(winner.opposite())),
+            (TerminationReason::Resignation, Some(winner)) => format!("{} resigns", self.handle(winner.opposite())),
+            (TerminationReason::TimeForfeit, Some(winner)) => format!("{} forfeits on time", self.handle(winner.opposite())),
+            (TerminationReason::TimeForfeit, None) => {
+                let flagged = self.game.get_active_colour();
+                format!("{} ran out of time and {} has no material to mate", self.handle(flagged), self.handle(flagged.opposite()))
+            }
+            (TerminationReason::Stalemate, _) => String::from("Game drawn by stalemate"),
+            (TerminationReason::DrawAgreement, _) => String::from("Game drawn by mutual agreement"),
+            (TerminationReason::ThreefoldRepetition, _) => String::from("Game drawn by repetition"),
+            (TerminationReason::FiftyMoveRule, _) => String::from("Game drawn by the 50 move rule"),
+            (TerminationReason::VariantEnd, Some(winner)) => format!("{} wins by variant rule", self.handle(winner)),
+            (TerminationReason::VariantEnd, None) => String::from("Game drawn by variant rule"),
+            _ => String::from("Game over")
+        }
+    }
+}
+
+/// Everyone and everything on the server
+struct Lobby {
+    users: HashMap<String, User>,
+    seeks: Vec<Seek>,
+    tables: BTreeMap<u32, Table>
+}
+
+/// ## IcsServer
+/// ### Type: `struct`
+/// The server, shared by the threads of the connections
+pub struct IcsServer {
+    lobby: Mutex<Lobby>
+}
+
+impl IcsServer {
+    /// Creates a server without users or games
+    pub fn new() -> IcsServer {
+        IcsServer { lobby: Mutex::new(Lobby { users: HashMap::new(), seeks: Vec::new(), tables: BTreeMap::new() }) }
+    }
+
+    /// ## `serve`
+    /// Accepts connections on a listener, each on its own thread, and ends games whose players ran out of time
+    /// ### Returns
+    /// Returns an error if accepting a connection failed
+    pub fn serve(self: Arc<Self>, _listener: TcpListener) -> io::Result<()> {
+        let ticker = Arc::clone(&self);
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_millis(250));
+            ticker.lobby.lock().unwrap().tick();
+        });
+
+        for stream in _listener.incoming() {
+            let stream = stream?;
+            let server = Arc::clone(&self);
+            thread::spawn(move || server.handle_connection(stream));
+        }
+        Ok(())
+    }
+
+    /// ## `handle_connection`
+    /// Runs the session of one connection: the login, then commands until the user quits or disconnects.
+    /// Everything sent to the user goes through a writer thread, so a slow client does not hold up the others
+    pub fn handle_connection(&self, _stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(_stream.try_clone()?);
+        let (output, messages) = mpsc::channel::<String>();
+        let mut writer = _stream;
+        thread::spawn(move || {
+            for message in messages {
+                if writer.write_all(message.replace('\n', "\r\n").as_bytes()).and_then(|_| writer.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let _ = output.send(format!("{}\n\nlogin: ", WELCOME));
+        let handle = loop {
+            let line = match read_line(&mut reader)? {
+                Some(line) => line,
+                None => return Ok(())
+            };
+            match self.lobby.lock().unwrap().login(&line, output.clone()) {
+                Ok(()) => break line,
+                Err(reason) => {
+                    let _ = output.send(format!("{}\nlogin: ", reason));
+                }
+            }
+        };
+
+        let session = self.run_session(&handle, &mut reader);
+        self.lobby.lock().unwrap().logout(&handle);
+        session
+    }
+
+    /// Reads and answers the commands of a logged in user
+    fn run_session<R: BufRead>(&self, _handle: &str, _reader: &mut R) -> io::Result<()> {
+        while let Some(line) = read_line(_reader)? {
+            let mut lobby = self.lobby.lock().unwrap();
+            if line.eq_ignore_ascii_case("quit") || line.eq_ignore_ascii_case("exit") {
+                if let Some(user) = lobby.users.get(_handle) {
+                    let _ = user.output.send(String::from("Logging you out.\n"));
+                }
+                break;
+            }
+            if let Some(reply) = lobby.command(_handle, &line) {
+                lobby.send(_handle, &reply);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for IcsServer {
+    fn default() -> Self {
+        IcsServer::new()
+    }
+}
+
+impl Lobby {
+    /// Sends text followed by the prompt to a user
+    fn send(&self, _handle: &str, _text: &str) {
+        if let Some(user) = self.users.get(_handle) {
+            let _ = user.output.send(format!("{}\n{}", _text, PROMPT));
+        }
+    }
+
+    /// Returns the handle of a user, whose name is given in any case
+    fn find_user(&self, _name: &str) -> Option<String> {
+        self.users.keys().find(|handle| handle.eq_ignore_ascii_case(_name)).cloned()
+    }
+
+    /// Returns the number of the game a user plays and the colour played
+    fn seat(&self, _handle: &str) -> Option<(u32, Colour)> {
+        let number = self.users.get(_handle)?.game?;
+        let colour = self.tables.get(&number)?.colour_of(_handle)?;
+        Some((number, colour))
+    }
+
+    /// Returns the game a user plays, or else the first game the user observes
+    fn watching(&self, _handle: &str) -> Option<u32> {
+        self.seat(_handle).map(|(number, _)| number)
+            .or_else(|| self.tables.iter().find(|(_, table)| table.observers.iter().any(|observer| observer == _handle)).map(|(number, _)| *number))
+    }
+
+    /// Logs a user in, refusing handles that are malformed or already taken
+    fn login(&mut self, _handle: &str, _output: Sender<String>) -> Result<(), String> {
+        if _handle.len() < 3 || _handle.len() > 17 || !_handle.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(String::from("A handle must be 3 to 17 letters."));
+        }
+        if self.find_user(_handle).is_some() {
+            return Err(format!("\"{}\" is already logged in.", _handle));
+        }
+
+        let _ = _output.send(format!("**** Starting session as {} ****\n\n{}", _handle, PROMPT));
+        self.users.insert(_handle.to_string(), User { output: _output, style: Style::Text, game: None });
+        Ok(())
+    }
+
+    /// Logs a user out, withdrawing the seeks and losing the game being played
+    fn logout(&mut self, _handle: &str) {
+        self.seeks.retain(|seek| seek.handle != _handle);
+        for table in self.tables.values_mut() {
+            table.observers.retain(|observer| observer != _handle);
+        }
+
+        if let Some((number, colour)) = self.seat(_handle) {
+            if let Some(table) = self.tables.get_mut(&number) {
+                table.game.resign(colour);
+            }
+            self.finish(number, Some(format!("{} forfeits by disconnection", _handle)));
+        }
+        self.users.remove(_handle);
+    }
+
+    /// ## `command`
+    /// Runs a command of a user, anything that is not a command is tried as a move
+    /// ### Returns
+    /// Returns the answer to send to the user, `None` if the user was already sent one
+    fn command(&mut self, _handle: &str, _line: &str) -> Option<String> {
+        let (name, rest) = match _line.split_once(' ') {
+            Some((name, rest)) => (name, rest.trim()),
+            None => (_line, "")
+        };
+        let args: Vec<&str> = rest.split_whitespace().collect();
+
+        match name.to_ascii_lowercase().as_str() {
+            "" => Some(String::new()),
+            "help" => Some(String::from(HELP)),
+            "who" => Some(self.who()),
+            "seek" => Some(self.seek(_handle, &args)),
+            "unseek" => Some(self.unseek(_handle)),
+            "sought" => Some(self.sought()),
+            "play" => self.play(_handle, &args),
+            "games" => Some(self.games()),
+            "observe" => self.observe(_handle, &args),
+            "unobserve" => Some(self.unobserve(_handle, &args)),
+            "refresh" => self.refresh(_handle),
+            "moves" => Some(self.moves(_handle, &args)),
+            "style" => Some(self.style(_handle, &args)),
+            "resign" => self.resign(_handle),
+            "draw" => self.draw(_handle),
+            "decline" => Some(self.decline(_handle)),
+            "say" => Some(self.say(_handle, rest)),
+            "tell" => Some(self.tell(_handle, rest)),
+            "kibitz" => Some(self.kibitz(_handle, rest)),
+            _ => self.try_move(_handle, _line)
+        }
+    }
+
+    fn who(&self) -> String {
+        let mut handles: Vec<&String> = self.users.keys().collect();
+        handles.sort_by_key(|handle| handle.to_ascii_lowercase());
+
+        let mut text = String::new();
+        for handle in &handles {
+            match self.users[*handle].game {
+                Some(number) => text.push_str(&format!("{:<17} playing game {}\n", handle, number)),
+                None => text.push_str(&format!("{}\n", handle))
+            }
+        }
+        text.push_str(&format!("\n{} players displayed.", handles.len()));
+        text
+    }
+
+    /// Opens a seek and announces it to the users not playing
+    fn seek(&mut self, _handle: &str, _args: &[&str]) -> String {
+        const USAGE: &str = "Usage: seek [<minutes> [<increment>]] [white|black]";
+
+        let mut numbers: Vec<u64> = Vec::new();
+        let mut colour = None;
+        for arg in _args {
+            match (arg.parse::<u64>(), parse_colour(arg)) {
+                (Ok(number), _) if numbers.len() < 2 && number <= 999 => numbers.push(number),
+                (_, Some(parsed)) if colour.is_none() => colour = Some(parsed),
+                _ => return String::from(USAGE)
+            }
+        }
+        let minutes = numbers.first().copied().unwrap_or(5);
+        let increment = numbers.get(1).copied().unwrap_or(0);
+
+        if self.seat(_handle).is_some() {
+            return String::from("You are playing a game.");
+        }
+        if minutes == 0 && increment > 0 {
+            return String::from("Timed games need at least a minute.");
+        }
+        if self.seeks.iter().filter(|seek| seek.handle == _handle).count() >= MAX_SEEKS {
+            return format!("You can have at most {} seeks open.", MAX_SEEKS);
+        }
+
+        let number = free_number(self.seeks.iter().map(|seek| seek.number));
+        let seek = Seek { number, handle: _handle.to_string(), minutes, increment, colour };
+        let announcement = format!("{} seeking {} (\"play {}\" to respond)", _handle, describe_seek(&seek), number);
+        self.seeks.push(seek);
+
+        for (handle, user) in &self.users {
+            if handle != _handle && user.game.is_none() {
+                self.send(handle, &announcement);
+            }
+        }
+        format!("Your seek has been posted with index {}.", number)
+    }
+
+    fn unseek(&mut self, _handle: &str) -> String {
+        let before = self.seeks.len();
+        self.seeks.retain(|seek| seek.handle != _handle);
+        match before - self.seeks.len() {
+            0 => String::from("You have no seeks open."),
+            removed => format!("{} seeks withdrawn.", removed)
+        }
+    }
+
+    fn sought(&self) -> String {
+        let mut text = String::new();
+        for seek in &self.seeks {
+            text.push_str(&format!("{:>3} {:<17} {}\n", seek.number, seek.handle, describe_seek(seek)));
+        }
+        text.push_str(&format!("{} ads displayed.", self.seeks.len()));
+        text
+    }
+
+    /// Accepts a seek and starts its game, the seeker plays the colour asked for or a colour drawn by chance
+    fn play(&mut self, _handle: &str, _args: &[&str]) -> Option<String> {
+        let number = match _args.first().and_then(|number| number.parse::<u32>().ok()) {
+            Some(number) if _args.len() == 1 => number,
+            _ => return Some(String::from("Usage: play <seek>"))
+        };
+        if self.seat(_handle).is_some() {
+            return Some(String::from("You are playing a game."));
+        }
+        let index = match self.seeks.iter().position(|seek| seek.number == number) {
+            Some(index) => index,
+            None => return Some(String::from("That seek is not available."))
+        };
+        if self.seeks[index].handle == _handle {
+            return Some(String::from("You cannot accept your own seek."));
+        }
+
+        let mut seek = self.seeks.remove(index);
+        self.seeks.retain(|other| other.handle != seek.handle && other.handle != _handle);
+
+        let seeker_is_white = match seek.colour {
+            Some(colour) => colour == Colour::White,
+            None => RandomState::new().build_hasher().finish() & 1 == 0
+        };
+        let (white, black) = if seeker_is_white {(seek.handle.clone(), _handle.to_string())} else {(_handle.to_string(), seek.handle.clone())};
+
+        seek.colour = None;
+
+        let mut game = Game::new();
+        if seek.minutes > 0 {
+            game.set_clock(Clock::new(TimeControl::fischer(Duration::from_secs(seek.minutes * 60), Duration::from_secs(seek.increment))));
+        }
+
+        let game_number = free_number(self.tables.keys().copied());
+        for handle in [&white, &black] {
+            if let Some(user) = self.users.get_mut(handle.as_str()) {
+                user.game = Some(game_number);
+            }
+        }
+        let header = format!("{{Game {} ({} vs. {}) Creating {} match.}}\n", game_number, white, black, describe_seek(&seek));
+        self.tables.insert(game_number, Table {
+            game, white, black,
+            minutes: seek.minutes,
+            increment: seek.increment,
+            observers: Vec::new(),
+            last_move: None,
+            turn_started: Instant::now()
+        });
+
+        self.show_board(game_number, &header);
+        None
+    }
+
+    fn games(&self) -> String {
+        let mut text = String::new();
+        for (number, table) in &self.tables {
+            let clock = |colour| table.time_left(colour).map_or_else(|| String::from("-"), format_clock);
+            let turn = if table.game.get_active_colour() == Colour::White {'W'} else {'B'};
+            text.push_str(&format!("{:>3} {:<17} {:<17} [{:>3} {:>3}] {:>6} - {:<6} ({}: {})\n",
+                number, table.white, table.black, table.minutes, table.increment,
+                clock(Colour::White), clock(Colour::Black), turn, table.game.get_fullmove_number()));
+        }
+        text.push_str(&format!("{} games displayed.", self.tables.len()));
+        text
+    }
+
+    fn observe(&mut self, _handle: &str, _args: &[&str]) -> Option<String> {
+        let number = match _args.first().and_then(|number| number.parse::<u32>().ok()) {
+            Some(number) if _args.len() == 1 => number,
+            _ => return Some(String::from("Usage: observe <game>"))
+        };
+        let table = match self.tables.get_mut(&number) {
+            Some(table) => table,
+            None => return Some(String::from("There is no such game."))
+        };
+        if table.colour_of(_handle).is_some() {
+            return Some(String::from("You are playing that game."));
+        }
+        if table.observers.iter().any(|observer| observer == _handle) {
+            return Some(format!("You are already observing game {}.", number));
+        }
+
+        table.observers.push(_handle.to_string());
+        self.send_board(_handle, number, &format!("You are now observing game {}.\n", number));
+        None
+    }
+
+    fn unobserve(&mut self, _handle: &str, _args: &[&str]) -> String {
+        let only: Option<u32> = match _args {
+            [] => None,
+            [number] => match number.parse() {
+                Ok(number) => Some(number),
+                Err(_) => return String::from("Usage: unobserve [<game>]")
+            },
+            _ => return String::from("Usage: unobserve [<game>]")
+        };
+
+        let mut text = String::new();
+        for (number, table) in self.tables.iter_mut() {
+            if only.is_none_or(|only| only == *number) && table.observers.iter().any(|observer| observer == _handle) {
+                table.observers.retain(|observer| observer != _handle);
+                text.push_str(&format!("Removing game {} from observation list.\n", number));
+            }
+        }
+
+        if text.is_empty() {String::from("You are not observing that game.")} else {text.trim_end().to_string()}
+    }
+
+    fn refresh(&self, _handle: &str) -> Option<String> {
+        match self.watching(_handle) {
+            Some(number) => {
+                self.send_board(_handle, number, "");
+                None
+            }
+            None => Some(String::from("You are neither playing nor observing a game."))
+        }
+    }
+
+    fn moves(&self, _handle: &str, _args: &[&str]) -> String {
+        let number = match _args {
+            [] => self.watching(_handle),
+            [number] => number.parse().ok(),
+            _ => None
+        };
+        let (number, table) = match number.and_then(|number| self.tables.get(&number).map(|table| (number, table))) {
+            Some(found) => found,
+            None => return String::from("There is no such game.")
+        };
+
+        let mut text = format!("Movelist for game {} ({} vs. {}):\n", number, table.white, table.black);
+        for (index, pair) in table.game.to_pgn().moves.chunks(2).enumerate() {
+            text.push_str(&format!("{:>3}.  {:<9} {}\n", index + 1, pair[0], pair.get(1).map_or("", |san| san.as_str())));
+        }
+        text.trim_end().to_string()
+    }
+
+    fn style(&mut self, _handle: &str, _args: &[&str]) -> String {
+        let style = match _args {
+            ["1"] => Style::Text,
+            ["12"] => Style::Line,
+            _ => return String::from("Usage: style <1|12>")
+        };
+        if let Some(user) = self.users.get_mut(_handle) {
+            user.style = style;
+        }
+        format!("Style {} set.", _args[0])
+    }
+
+    fn resign(&mut self, _handle: &str) -> Option<String> {
+        let (number, colour) = match self.seat(_handle) {
+            Some(seat) => seat,
+            None => return Some(String::from("You are not playing a game."))
+        };
+
+        if let Some(table) = self.tables.get_mut(&number) {
+            table.game.resign(colour);
+        }
+        self.finish(number, None);
+        None
+    }
+
+    /// Claims a draw if one can be claimed, accepts the offer of the opponent or else offers one
+    fn draw(&mut self, _handle: &str) -> Option<String> {
+        let (number, colour) = match self.seat(_handle) {
+            Some(seat) => seat,
+            None => return Some(String::from("You are not playing a game."))
+        };
+        let table = self.tables.get_mut(&number)?;
+
+        if table.game.claim_draw().is_some() || table.game.accept_draw(colour).is_some() {
+            self.finish(number, None);
+            return None;
+        }
+        if table.game.get_draw_offer() == Some(colour) {
+            return Some(String::from("You already offered a draw."));
+        }
+
+        table.game.offer_draw(colour);
+        let opponent = table.handle(colour.opposite()).to_string();
+        self.send(&opponent, &format!("{} offers you a draw.\nType \"draw\" to accept or \"decline\" to decline it.", _handle));
+        Some(String::from("Draw request sent."))
+    }
+
+    fn decline(&mut self, _handle: &str) -> String {
+        let (number, colour) = match self.seat(_handle) {
+            Some(seat) => seat,
+            None => return String::from("You are not playing a game.")
+        };
+        let table = match self.tables.get_mut(&number) {
+            Some(table) => table,
+            None => return String::from("You are not playing a game.")
+        };
+
+        if !table.game.decline_draw(colour) {
+            return String::from("There is no draw offer to decline.");
+        }
+        let opponent = table.handle(colour.opposite()).to_string();
+        self.send(&opponent, &format!("{} declines your draw request.", _handle));
+        String::from("You decline the draw request.")
+    }
+
+    fn say(&self, _handle: &str, _message: &str) -> String {
+        let opponent = match self.seat(_handle).and_then(|(number, colour)| self.tables.get(&number).map(|table| table.handle(colour.opposite()))) {
+            Some(opponent) => opponent,
+            None => return String::from("You are not playing a game.")
+        };
+        if _message.is_empty() {
+            return String::from("Usage: say <message>");
+        }
+
+        self.send(opponent, &format!("{} says: {}", _handle, _message));
+        format!("(told {})", opponent)
+    }
+
+    fn tell(&self, _handle: &str, _rest: &str) -> String {
+        let (name, message) = match _rest.split_once(' ') {
+            Some((name, message)) if !message.trim().is_empty() => (name, message.trim()),
+            _ => return String::from("Usage: tell <handle> <message>")
+        };
+        let handle = match self.find_user(name) {
+            Some(handle) => handle,
+            None => return format!("No user named \"{}\" is logged in.", name)
+        };
+
+        self.send(&handle, &format!("{} tells you: {}", _handle, message));
+        format!("(told {})", handle)
+    }
+
+    fn kibitz(&self, _handle: &str, _message: &str) -> String {
+        let number = match self.watching(_handle) {
+            Some(number) => number,
+            None => return String::from("You are neither playing nor observing a game.")
+        };
+        if _message.is_empty() {
+            return String::from("Usage: kibitz <message>");
+        }
+
+        let listeners: Vec<String> = self.tables[&number].audience().into_iter().filter(|handle| handle != _handle).collect();
+        for handle in &listeners {
+            self.send(handle, &format!("{}[{}] kibitzes: {}", _handle, number, _message));
+        }
+        format!("(kibitzed to {} users)", listeners.len())
+    }
+
+    /// Plays the move of a player, anything else is an unknown command
+    fn try_move(&mut self, _handle: &str, _line: &str) -> Option<String> {
+        let command = _line.split_whitespace().next().unwrap_or(_line);
+        let (number, colour) = match self.seat(_handle) {
+            Some(seat) => seat,
+            None => return Some(format!("{}: Command not found.", command))
+        };
+        let table = self.tables.get_mut(&number)?;
+
+        if table.game.get_active_colour() != colour {
+            return Some(String::from("It is not your move."));
+        }
+        let _move = match parse_move(&table.game, _line) {
+            Ok(_move) => _move,
+            Err(error) => return Some(error)
+        };
+
+        let san = table.game.move_to_san(&_move);
+        let verbose = verbose_move(table.game.get_board(), &_move);
+        let played = table.game.get_history().len();
+        table.game.play_move(_move);
+
+        if table.game.get_history().len() > played {
+            let now = Instant::now();
+            table.last_move = Some(LastMove { verbose, san, time: now - table.turn_started });
+            table.turn_started = now;
+            self.show_board(number, "");
+        }
+        if table_over(&self.tables[&number]) {
+            self.finish(number, None);
+        }
+        None
+    }
+
+    /// Ends the games whose player to move ran out of time
+    fn tick(&mut self) {
+        let flagged: Vec<u32> = self.tables.iter_mut()
+            .filter(|(_, table)| table.game.get_clock().is_some())
+            .filter_map(|(number, table)| match table.game.check_time() {
+                GameState::TimeForfeit { .. } => Some(*number),
+                _ => None
+            })
+            .collect();
+
+        for number in flagged {
+            self.finish(number, None);
+        }
+    }
+
+    /// ## `finish`
+    /// Removes an ended game and tells its players and observers how it ended
+    /// ### Parameters
+    /// - `_number: u32`: The number of the game
+    /// - `_reason: Option<String>`: How the game ended if it cannot be told from the game, e.g a disconnection
+    fn finish(&mut self, _number: u32, _reason: Option<String>) {
+        let table = match self.tables.remove(&_number) {
+            Some(table) => table,
+            None => return
+        };
+
+        let reason = _reason.unwrap_or_else(|| table.describe_end(table.game.get_termination()));
+        let message = format!("{{Game {} ({} vs. {}) {}}} {}", _number, table.white, table.black, reason, table.game.get_result());
+        for handle in [&table.white, &table.black] {
+            if let Some(user) = self.users.get_mut(handle.as_str()) {
+                user.game = None;
+            }
+        }
+        for handle in table.audience() {
+            self.send(&handle, &message);
+        }
+    }
+
+    /// Sends the board of a game to its players and observers, after a header
+    fn show_board(&self, _number: u32, _header: &str) {
+        if let Some(table) = self.tables.get(&_number) {
+            for handle in table.audience() {
+                self.send_board(&handle, _number, _header);
+            }
+        }
+    }
+
+    /// Sends the board of a game to one user in the style of the user, after a header
+    fn send_board(&self, _handle: &str, _number: u32, _header: &str) {
+        let (user, table) = match (self.users.get(_handle), self.tables.get(&_number)) {
+            (Some(user), Some(table)) => (user, table),
+            _ => return
+        };
+
+        let board = match user.style {
+            Style::Text => style1(table, _number, _handle),
+            Style::Line => style12(table, _number, _handle)
+        };
+        self.send(_handle, &format!("{}{}", _header, board));
+    }
+}
+
+/// Returns true if the game of a table has ended, including checkmate and stalemate
+fn table_over(_table: &Table) -> bool {
+    _table.game.is_over()
+}
+
+/// ## `style1`
+/// Draws the board of a game as seen by a user, with the clocks, material and last move beside it.
+/// Black pieces are marked with `*` and players of black see the board from their side
+fn style1(_table: &Table, _number: u32, _viewer: &str) -> String {
+    let fen = _table.game.to_fen();
+    let placement = fen.split_whitespace().next().unwrap_or_default();
+    let rows = expand_placement(placement);
+    let flip = _table.colour_of(_viewer) == Some(Colour::Black);
+    let (white_strength, black_strength) = material(placement);
+    let clock = |colour| _table.time_left(colour).map_or_else(|| String::from("-"), format_clock);
+
+    let mut side = vec![String::new(); 8];
+    side[0] = format!("Move # : {} ({})", _table.game.get_fullmove_number(), colour_name(_table.game.get_active_colour()));
+    if let Some(last) = &_table.last_move {
+        side[1] = format!("Last move : {} ({}) {}", last.verbose, format_clock(last.time), last.san);
+    }
+    side[2] = format!("Black Clock : {}", clock(Colour::Black));
+    side[3] = format!("White Clock : {}", clock(Colour::White));
+    side[4] = format!("Black Strength : {}", black_strength);
+    side[5] = format!("White Strength : {}", white_strength);
+
+    let mut text = format!("Game {} ({} vs. {})\n\n", _number, _table.white, _table.black);
+    text.push_str("       ---------------------------------\n");
+    for (line, info) in side.iter().enumerate() {
+        let row = if flip {7 - line} else {line};
+        let mut squares = String::new();
+        for col in 0..8 {
+            let piece = rows[row][if flip {7 - col} else {col}];
+            squares.push_str(&match piece {
+                '-' => String::from("   |"),
+                piece if piece.is_ascii_uppercase() => format!(" {} |", piece),
+                piece => format!(" *{}|", piece.to_ascii_uppercase())
+            });
+        }
+        text.push_str(format!("    {}  |{}     {}", 8 - row, squares, info).trim_end());
+        text.push('\n');
+        if line < 7 {
+            text.push_str("       |---+---+---+---+---+---+---+---|\n");
+        }
+    }
+    text.push_str("       ---------------------------------\n");
+    text.push_str(if flip {"         h   g   f   e   d   c   b   a"} else {"         a   b   c   d   e   f   g   h"});
+    text
+}
+
+/// ## `style12`
+/// Writes the position of a game as a FICS style 12 line for a user: the ranks from the 8th, the colour to move,
+/// the en passant file, castling rights, the halfmove clock, the game number, the players, the relation of the user
+/// to the game, the time control, material, clocks in seconds, the move number, the last move and its time,
+/// and whether the board should be flipped
+fn style12(_table: &Table, _number: u32, _viewer: &str) -> String {
+    let fen = _table.game.to_fen();
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let placement = fields.first().copied().unwrap_or_default();
+    let castling = fields.get(2).copied().unwrap_or("-");
+    let en_passant = match fields.get(3).and_then(|square| square.bytes().next()) {
+        Some(file @ b'a'..=b'h') => (file - b'a') as i8,
+        _ => -1
+    };
+
+    let ranks: Vec<String> = expand_placement(placement).iter().map(|row| row.iter().collect()).collect();
+    let active = _table.game.get_active_colour();
+    let relation = match _table.colour_of(_viewer) {
+        Some(colour) if colour == active => 1,
+        Some(_) => -1,
+        None => 0
+    };
+    let (white_strength, black_strength) = material(placement);
+    let seconds = |colour| _table.time_left(colour).map_or(0, |time| time.as_secs());
+    let (verbose, time, san) = match &_table.last_move {
+        Some(last) => (last.verbose.as_str(), format_clock(last.time), last.san.as_str()),
+        None => ("none", String::from("0:00"), "none")
+    };
+    let right = |letter| if castling.contains(letter) {1} else {0};
+
+    format!("<12> {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {} ({}) {} {}",
+        ranks.join(" "), if active == Colour::White {'W'} else {'B'}, en_passant,
+        right('K'), right('Q'), right('k'), right('q'),
+        fields.get(4).copied().unwrap_or("0"), _number, _table.white, _table.black, relation,
+        _table.minutes, _table.increment, white_strength, black_strength,
+        seconds(Colour::White), seconds(Colour::Black), _table.game.get_fullmove_number(),
+        verbose, time, san, u8::from(_table.colour_of(_viewer) == Some(Colour::Black)))
+}
+
+/// Turns the placement field of a FEN into 8 rows of 8 characters, `-` for empty squares, from the 8th rank
+fn expand_placement(_placement: &str) -> Vec<Vec<char>> {
+    let mut rows: Vec<Vec<char>> = _placement.split('/').map(|rank| {
+        rank.chars().flat_map(|c| match c.to_digit(10) {
+            Some(empty) => vec!['-'; empty as usize],
+            None => vec![c]
+        }).collect()
+    }).collect();
+
+    rows.resize(8, Vec::new());
+    for row in rows.iter_mut() {
+        row.resize(8, '-');
+    }
+    rows
+}
+
+/// Returns the material of white and black counting pawns 1, knights and bishops 3, rooks 5 and queens 9
+fn material(_placement: &str) -> (u32, u32) {
+    let mut strength = (0, 0);
+    for c in _placement.chars() {
+        let value = match c.to_ascii_lowercase() {
+            'p' => 1,
+            'n' | 'b' => 3,
+            'r' => 5,
+            'q' => 9,
+            _ => continue
+        };
+        if c.is_ascii_uppercase() {strength.0 += value} else {strength.1 += value}
+    }
+    strength
+}
+
+/// Writes a move with its piece and both squares as on FICS, e.g `"N/g1-f3"`, `"P/e7-e8=Q"` or `"o-o"`
+fn verbose_move(_board: &Board, _move: &Move) -> String {
+    if _board.is_castling(_move.from, _move.to) {
+        return String::from(if _move.to.0 > _move.from.0 {"o-o"} else {"o-o-o"});
+    }
+
+    let uci = _move.to_uci();
+    let promotion = uci.get(4..).filter(|letter| !letter.is_empty()).map_or(String::new(), |letter| format!("={}", letter.to_ascii_uppercase()));
+    format!("{}/{}-{}{}", _board.piece_at(_move.from).get_icon().to_ascii_uppercase(), &uci[0..2], &uci[2..4], promotion)
+}
+
+/// Reads a move as SAN or coordinates, e.g `"e2e4"` or `"e2-e4"`. Pawn moves to the last rank without a piece promote to a queen
+fn parse_move(_game: &Game, _input: &str) -> Result<Move, String> {
+    let squares: String = _input.split(|c: char| c.is_whitespace() || c == '-').collect::<Vec<&str>>().concat().to_ascii_lowercase();
+
+    if let Some(_move) = Move::from_uci(&squares) {
+        let legal = _game.get_all_legal_moves();
+        return match legal.iter().find(|legal| legal.from == _move.from && legal.to == _move.to) {
+            None => Err(format!("Illegal move ({}).", _input)),
+            Some(legal) if _move.promotion.is_none() && legal.promotion.is_some() => Ok(Move { promotion: Some(PieceType::Queen), .._move }),
+            Some(_) if legal.contains(&_move) => Ok(_move),
+            Some(_) => Err(format!("Illegal move ({}).", _input))
+        };
+    }
+
+    _game.san_to_move(_input).ok_or_else(|| format!("Illegal move or unknown command ({}).", _input))
+}
+
+/// Describes the time control and colour of a seek, e.g `"5 2 blitz"` or `"untimed [white]"`
+fn describe_seek(_seek: &Seek) -> String {
+    // Estimated game length in minutes, for a game of 40 moves
+    let estimate = _seek.minutes + _seek.increment * 2 / 3;
+    let mut text = match estimate {
+        _ if _seek.minutes == 0 => String::from("untimed"),
+        0..=2 => format!("{} {} lightning", _seek.minutes, _seek.increment),
+        3..=14 => format!("{} {} blitz", _seek.minutes, _seek.increment),
+        _ => format!("{} {} standard", _seek.minutes, _seek.increment)
+    };
+    if let Some(colour) = _seek.colour {
+        text.push_str(&format!(" [{}]", colour_name(colour).to_ascii_lowercase()));
+    }
+    text
+}
+
+/// Formats a duration as minutes and seconds, e.g `"4:05"`
+fn format_clock(_time: Duration) -> String {
+    let seconds = _time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn colour_name(_colour: Colour) -> &'static str {
+    match _colour {
+        Colour::White => "White",
+        Colour::Black => "Black"
+    }
+}
+
+fn parse_colour(_name: &str) -> Option<Colour> {
+    match _name.to_ascii_lowercase().as_str() {
+        "white" | "w" => Some(Colour::White),
+        "black" | "b" => Some(Colour::Black),
+        _ => None
+    }
+}
+
+/// Returns the smallest number from 1 that is not taken
+fn free_number<I: Iterator<Item = u32>>(_taken: I) -> u32 {
+    let taken: Vec<u32> = _taken.collect();
+    (1..).find(|number| !taken.contains(number)).unwrap_or(0)
+}
+
+/// ## `read_line`
+/// Reads a line from a telnet client, dropping telnet commands and control characters
+/// ### Returns
+/// Returns `None` once the client closed the connection
+fn read_line<R: BufRead>(_reader: &mut R) -> io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    if _reader.by_ref().take(MAX_LINE).read_until(b'\n', &mut bytes)? == 0 {
+        return Ok(None);
+    }
+
+    // Telnet commands start with 255, option negotiations (251 to 254) carry one more byte
+    let mut line = String::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            255 => index += if (251..=254).contains(bytes.get(index + 1).unwrap_or(&0)) {3} else {2},
+            byte if byte.is_ascii_graphic() || byte == b' ' => {
+                line.push(byte as char);
+                index += 1;
+            }
+            _ => index += 1
+        }
+    }
+    Ok(Some(line.trim().to_string()))
+}
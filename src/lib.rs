@@ -1,15 +1,54 @@
 pub mod chess;
+pub mod san;
+pub mod search;
+pub mod epd;
+pub mod polyglot;
+pub mod book;
+pub mod pgn;
+pub mod tablebase;
+pub mod encoding;
+pub mod chess960;
+pub mod clock;
+pub mod termination;
+pub mod game_tree;
+pub mod render;
+pub mod svg;
+pub mod raster;
+pub mod animation;
+pub mod ics;
+pub mod stats;
+pub mod variant;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub mod websocket;
 use chess::piece_data::Colour;
 use chess::piece_data::PieceType;
 use chess::board::Board;
 use chess::piece::*;
+use chess::moves::Move;
+use clock::Clock;
+use termination::Termination;
+use variant::Variant;
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
 pub enum GameState {
     InProgress,
     Check,
-    GameOver
+    GameOver,
+    /// A player's time ran out. The opponent wins, or it is a draw (`None`) if the opponent could not checkmate
+    TimeForfeit { winner: Option<Colour> },
+    /// A king reached the centre in King of the Hill, its colour wins
+    KingOfTheHill { winner: Colour }
 }
 
 /* IMPORTANT:
@@ -24,11 +63,28 @@ pub enum GameState {
 /// - `board`: Representation of te board
 /// - `Colour`: The current player's colour
 /// - `GameState`: The state of the game, `InProgress`, `Check` or `GameOver`
+/// - `halfmove_clock`: Halfmoves since the last capture or pawn move, used for the fifty move rule
+/// - `fullmove_number`: The number of the current full move, starting at 1 and incremented after Black's move
+/// - `start_fen`: The FEN string of the position the game started from
+/// - `history`: Every move played since the starting position
+/// - `clock`: The clock of the game, `None` for games without time control
+/// - `termination`: How the game ended if it was by resignation, agreement, a claim or on time
+/// - `draw_offer`: The colour that offered a draw, if the offer still stands
+/// - `variant`: The rules the game is played with, `variant::Standard` unless set
+#[derive(Clone)]
 pub struct Game {
     /* save board, active colour, ... */
     board: Board,
     active_colour: Colour,
-    state: GameState
+    state: GameState,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    start_fen: String,
+    history: Vec<Move>,
+    clock: Option<Clock>,
+    termination: Option<Termination>,
+    draw_offer: Option<Colour>,
+    variant: Arc<dyn Variant>
 }
 
 impl Game {
@@ -44,6 +100,14 @@ impl Game {
             board: Board::new(),
             active_colour: Colour::White,
             state: GameState::InProgress,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            start_fen: String::from(variant::STANDARD_FEN),
+            history: Vec::new(),
+            clock: None,
+            termination: None,
+            draw_offer: None,
+            variant: Arc::new(variant::Standard)
         }
     }
 
@@ -58,9 +122,85 @@ impl Game {
             board: Board::new_empty(),
             active_colour: Colour::White,
             state: GameState::InProgress,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            start_fen: String::from("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            history: Vec::new(),
+            clock: None,
+            termination: None,
+            draw_offer: None,
+            variant: Arc::new(variant::Standard)
         }
     }
 
+    /// ## `from_fen`
+    /// Creates a game from a FEN string. The halfmove clock and fullmove number may be left out and default to 0 and 1.
+    /// ### Parameters
+    /// - `_fen: &str`: The position given in Forsyth-Edwards Notation, e.g `"rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"`
+    /// 
+    /// ### Returns
+    /// Returns `Option<>` wrapping a `Game`
+    /// `
+    /// Option<Game>
+    /// `
+    /// or `None` if the FEN is malformed or a side does not have exactly one king
+    pub fn from_fen(_fen: &str) -> Option<Game> {
+        let fields: Vec<&str> = _fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return None;
+        }
+
+        let board = Board::from_fen(fields[0], fields[2], fields[3])?;
+
+        for colour in [Colour::White, Colour::Black] {
+            let kings = board.board.iter().flatten().filter(|piece| piece.get_piece_data() == (colour, PieceType::King)).count();
+            if kings != 1 {
+                return None;
+            }
+        }
+
+        let active_colour = match fields[1] {
+            "w" => Colour::White,
+            "b" => Colour::Black,
+            _ => return None
+        };
+
+        let mut game = Game {
+            board,
+            active_colour,
+            state: GameState::InProgress,
+            halfmove_clock: fields.get(4).map_or(Some(0), |clock| clock.parse().ok())?,
+            fullmove_number: fields.get(5).map_or(Some(1), |number| number.parse().ok())?,
+            start_fen: String::new(),
+            history: Vec::new(),
+            clock: None,
+            termination: None,
+            draw_offer: None,
+            variant: Arc::new(variant::Standard)
+        };
+
+        if Board::king_in_check(&mut game.board, game.active_colour) {
+            game.state = GameState::Check;
+        }
+
+        // Stored normalised, with the clocks even if they were left out
+        game.start_fen = game.to_fen();
+
+        Some(game)
+    }
+
+    /// ## `to_fen`
+    /// Returns the current position as a FEN string
+    pub fn to_fen(&self) -> String {
+        format!("{} {} {} {} {} {}",
+            self.board.get_fen_placement(),
+            if self.active_colour == Colour::White {"w"} else {"b"},
+            self.board.get_fen_castling(),
+            self.board.get_fen_en_passant(),
+            self.halfmove_clock,
+            self.fullmove_number)
+    }
+
     /// ## `make_move`
     /// Takes squares and changes the position of the piece at the first to the second, checking for legality. If the move is illegal it panics.
     /// ### Parameters
@@ -76,42 +216,256 @@ impl Game {
     /// 
     /// ### Panics!
     /// - If a move is illegal:
-    /// `
-    /// panic!("Illegal Move!")
-    /// `
+    ///   `
+    ///   panic!("Illegal Move!")
+    ///   `
+    /// - If the game is over:
+    ///   `
+    ///   panic!("Game is over!")
+    ///   `
     pub fn make_move(&mut self, _from: String, _to: String) -> Option<GameState> {
+        if self.is_finished() {
+            panic!("Game is over!")
+        }
+
         if self.board.piece_at(Board::filerank_to_num(&_from)).get_colour() != self.active_colour
         || self.board.is_empty(Board::filerank_to_num(&_from))
         { panic!("Incorrect square to move!") }
 
+        if !self.get_legal_move_coords(Board::filerank_to_num(&_from)).contains(&Board::filerank_to_num(&_to)) {
+            panic!("Illegal Move!")
+        }
+
+        self.commit_move(Move::new(Board::filerank_to_num(&_from), Board::filerank_to_num(&_to)));
 
-        let mut possible_en_passant = false;
-        if self.board.piece_at(Board::filerank_to_num(&_from)).get_type() == PieceType::Pawn {
-            let _from = Board::filerank_to_num(&_from);
-            let _to = Board::filerank_to_num(&_to);
+        Some(self.state)
+    }
+
+    /// ## `make_uci_move`
+    /// Takes a move in UCI long algebraic notation and plays it, including the promotion if one is given.
+    /// Unlike `make_move` this does not panic, which makes it suitable for consuming moves from engines and logs.
+    /// ### Parameters
+    /// - `_uci: &str`: The move given as "\<From\>\<To\>\[Promotion\]", e.g `"e2e4"` or `"e7e8q"`
+    /// 
+    /// ### Returns
+    /// Returns `Option<>` wrapping a `GameState`
+    /// `
+    /// Option<GameState>
+    /// `
+    /// with the current state of the game, or `None` if the move is malformed or illegal.
+    pub fn make_uci_move(&mut self, _uci: &str) -> Option<GameState> {
+        self.play_move(Move::from_uci(_uci)?)
+    }
 
-            if self.board.is_empty(_to) && _to.0 != _from.0 && _to.1 != _from.0 {
-                possible_en_passant = true;
+    /// ## `play_move`
+    /// Takes a `Move` and plays it if it is legal for the active colour, including the promotion if one is given.
+    /// ### Parameters
+    /// - `_move: Move`: The move to play
+    /// 
+    /// ### Returns
+    /// Returns `Option<>` wrapping a `GameState`
+    /// `
+    /// Option<GameState>
+    /// `
+    /// with the current state of the game, or `None` if the move is illegal or the game is over.
+    /// A pawn move to the last rank without a promotion, or promoting to anything but a queen, rook, bishop or knight, is illegal.
+    /// If the player's time ran out the move is not played and the state is `GameState::TimeForfeit`
+    pub fn play_move(&mut self, _move: Move) -> Option<GameState> {
+        if self.is_finished() {
+            return None;
+        }
+
+        if !Board::within_bounds(_move.from) || !Board::within_bounds(_move.to)
+        || self.board.is_empty(_move.from)
+        || self.board.piece_at(_move.from).get_colour() != self.active_colour
+        { return None; }
+
+        // A pawn reaching the last rank must promote, and only such a pawn may
+        let promoting = self.board.piece_at(_move.from).get_type() == PieceType::Pawn && (_move.to.1 == 0 || _move.to.1 == 7);
+        if _move.promotion.is_some() != promoting {
+            return None;
+        }
+
+        // Pawns promote to a queen, rook, bishop or knight only
+        if let Some(piece_type) = _move.promotion {
+            if !matches!(piece_type, PieceType::Queen | PieceType::Rook | PieceType::Bishop | PieceType::Knight) {
+                return None;
             }
         }
 
-        self.board.make_move(&_from, &_to);
+        if !self.get_legal_move_coords(_move.from).contains(&_move.to) {
+            return None;
+        }
+
+        self.commit_move(_move);
 
-        self.active_colour = if self.active_colour == Colour::Black {Colour::White} else {Colour::Black};
+        Some(self.state)
+    }
 
-        if possible_en_passant {
-            let _from = Board::filerank_to_num(&_from);
-            let _to = Board::filerank_to_num(&_to);
-            if self.board.piece_at((_to.0, _to.1 )).get_type() == PieceType::Pawn {
+    /// Plays a validated move of a player, checking and pressing the clock around it. The move answers any draw offer of the opponent
+    fn commit_move(&mut self, _move: Move) {
+        self.check_time();
+        if self.is_finished() {
+            return;
+        }
 
+        if self.draw_offer == Some(self.active_colour.opposite()) {
+            self.draw_offer = None;
+        }
+
+        self.apply_move(_move);
+        self.press_clock();
+    }
+
+    /// ## `is_finished`
+    /// Returns true if the game has ended and no more moves can be played
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, GameState::GameOver | GameState::TimeForfeit { .. } | GameState::KingOfTheHill { .. })
+    }
+
+    /// ## `get_all_legal_moves`
+    /// Returns every legal move of the active colour. Pawn moves to the last rank are given once per promotion piece.
+    /// ### Return
+    /// Returns a `Vec<Move>` holding all legal moves, empty if the active colour is checkmated or stalemated
+    pub fn get_all_legal_moves(&self) -> Vec<Move> {
+        let mut moves: Vec<Move> = Vec::with_capacity(40);
+
+        for row in 0..8 {
+            for col in 0..8 {
+                let piece = self.board.piece_at((col, row));
+                if piece.get_type() == PieceType::None || piece.get_colour() != self.active_colour {
+                    continue;
+                }
+
+                for to in self.get_legal_move_coords((col, row)) {
+                    if piece.get_type() == PieceType::Pawn && (to.1 == 0 || to.1 == 7) {
+                        for promotion in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight] {
+                            moves.push(Move { from: (col, row), to, promotion: Some(promotion) });
+                        }
+                    }
+                    else {
+                        moves.push(Move::new((col, row), to));
+                    }
+                }
             }
         }
 
-        if Board::king_in_check(&mut self.board, self.active_colour) {
-            return Some(GameState::Check);
+        moves
+    }
+
+    /// Returns the piece a move captures, the pawn passed by an en passant capture and nothing for castling
+    pub(crate) fn get_captured(&self, _move: Move) -> Option<Piece> {
+        if self.board.is_castling(_move.from, _move.to) {
+            None
+        }
+        else if !self.board.is_empty(_move.to) {
+            Some(*self.board.piece_at(_move.to))
         }
+        // A pawn moving diagonally to an empty square takes en passant
+        else if self.board.piece_at(_move.from).get_type() == PieceType::Pawn && _move.from.0 != _move.to.0 {
+            Some(*self.board.piece_at((_move.to.0, _move.from.1)))
+        }
+        else {
+            None
+        }
+    }
+
+    /// ## `apply_move`
+    /// Plays a move without checking its legality and updates the active colour, clocks and game state, ending the game if the variant says so.
+    /// Used internally after validation and by the search where moves come from `get_all_legal_moves`
+    pub(crate) fn apply_move(&mut self, _move: Move) {
+        let moving_piece = *self.board.piece_at(_move.from);
+        let captured = self.get_captured(_move);
+
+        // The fifty move rule clock is reset by pawn moves and captures
+        if moving_piece.get_type() == PieceType::Pawn || captured.is_some() {
+            self.halfmove_clock = 0;
+        }
+        else {
+            self.halfmove_clock += 1;
+        }
+
+        self.board.play_move(_move.from, _move.to);
+
+        if let Some(piece_type) = _move.promotion {
+            self.board.mut_piece_at(_move.to).set_type(piece_type);
+        }
+
+        self.variant.apply_effects(&mut self.board, _move, captured);
+
+        self.history.push(_move);
+
+        if self.active_colour == Colour::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.active_colour = self.active_colour.opposite();
+
+        self.state = if self.variant.is_check(&mut self.board, self.active_colour) {GameState::Check} else {GameState::InProgress};
 
-        Some(GameState::InProgress)
+        if let Some(termination) = self.variant.get_outcome(self) {
+            self.terminate(termination.reason, termination.winner);
+            self.state = termination.get_game_state(self.variant.as_ref());
+        }
+    }
+
+    /// ## `get_active_colour`
+    /// Returns the colour of the player whose turn it is
+    pub fn get_active_colour(&self) -> Colour {
+        self.active_colour
+    }
+
+    /// ## `get_fullmove_number`
+    /// Returns the number of the current full move, starting at 1 and incremented after Black's move
+    pub fn get_fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// ## `get_start_fen`
+    /// Returns the FEN string of the position the game started from
+    pub fn get_start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    /// ## `get_history`
+    /// Returns every move played since the starting position, in the order they were played
+    pub fn get_history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// ## `get_start`
+    /// Creates the starting position of the game, keeping Chess960 mode and the variant.
+    /// Replaying the history on it gives the game again, without its clock
+    pub fn get_start(&self) -> Game {
+        let mut start = Game::from_fen(&self.start_fen).expect("the starting position of a game is valid");
+        start.set_chess960(self.is_chess960());
+        start.set_variant(self.variant.clone());
+        start
+    }
+
+    /// ## `take_back`
+    /// Replays the game without its last move, which also takes back a resignation, agreed draw or time forfeit.
+    /// The clock is kept as it is.
+    /// ### Returns
+    /// Returns the game before the last move, or `None` if no move was played
+    pub fn take_back(&self) -> Option<Game> {
+        let (_, kept) = self.history.split_last()?;
+
+        let mut game = self.get_start();
+        for _move in kept {
+            game.apply_move(*_move);
+        }
+        if let Some(clock) = self.get_clock() {
+            game.set_clock(clock.clone());
+        }
+
+        Some(game)
+    }
+
+    /// ## `get_board`
+    /// Returns a reference to the board of the game
+    pub fn get_board(&self) -> &Board {
+        &self.board
     }
 
     /// ## `set_promotion`
@@ -131,19 +485,28 @@ impl Game {
 
 
         if _piece.eq_ignore_ascii_case("queen") {
-            self.board.mut_piece_at(Board::filerank_to_num(&String::from(_square))).set_type(PieceType::Queen)
+            self.board.mut_piece_at(Board::filerank_to_num(&_square)).set_type(PieceType::Queen)
         }
         else if _piece.eq_ignore_ascii_case("knight") {
-            self.board.mut_piece_at(Board::filerank_to_num(&String::from(_square))).set_type(PieceType::Knight)
+            self.board.mut_piece_at(Board::filerank_to_num(&_square)).set_type(PieceType::Knight)
         }
         else if _piece.eq_ignore_ascii_case("rook") {
-            self.board.mut_piece_at(Board::filerank_to_num(&String::from(_square))).set_type(PieceType::Rook)
+            self.board.mut_piece_at(Board::filerank_to_num(&_square)).set_type(PieceType::Rook)
         }
         else if _piece.eq_ignore_ascii_case("bishop") {
-            self.board.mut_piece_at(Board::filerank_to_num(&String::from(_square))).set_type(PieceType::Bishop)
+            self.board.mut_piece_at(Board::filerank_to_num(&_square)).set_type(PieceType::Bishop)
         }
 
-        if Board::king_in_check(&mut self.board, if colour == Colour::White {Colour::Black} else {Colour::White}) {
+        // Promoting the piece that just moved completes that move in the history
+        let promoted = self.board.piece_at(Board::filerank_to_num(&_square)).get_type();
+        if let Some(last) = self.history.last_mut() {
+            if last.to == Board::filerank_to_num(&_square) && last.promotion.is_none() && (last.to.1 == 0 || last.to.1 == 7)
+            && promoted != PieceType::Pawn && promoted != PieceType::King {
+                last.promotion = Some(promoted);
+            }
+        }
+
+        if self.variant.is_check(&mut self.board, if colour == Colour::White {Colour::Black} else {Colour::White}) {
             self.state = GameState::Check;
         }
     }
@@ -169,7 +532,7 @@ impl Game {
     /// `
     /// Holding all legal possible moves of the given square
     pub fn get_possible_moves(&self, _position: String) -> Option<Vec<String>> {
-        Some(self.board.get_legal_moves(&_position))
+        Some(self.get_legal_move_coords(Board::filerank_to_num(&_position)).iter().map(Board::num_to_filerank).collect())
     }
 
     /// ## DEBUG METHOD: `_then`
@@ -253,6 +616,12 @@ impl Game {
     }
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
 /// Implement print routine for Game.
 impl fmt::Debug for Game {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
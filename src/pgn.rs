@@ -0,0 +1,295 @@
+use crate::chess::piece_data::Colour;
+use crate::variant;
+use crate::Game;
+use std::fmt;
+use std::sync::Arc;
+
+/// Tags that every PGN game has, written first and in this order
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// ## Pgn
+/// ### Type: `struct`
+/// A game in Portable Game Notation. Only the main line is kept, comments, NAGs and variations are skipped when reading,
+/// see `GameTree` to keep them.
+/// ### Members:
+/// - `tags`: The tag pairs of the game in the order they were given, e.g `("White", "Carlsen")`
+/// - `moves`: The moves of the main line in SAN
+/// - `result`: The game termination marker, `"1-0"`, `"0-1"`, `"1/2-1/2"` or `"*"`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pgn {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+    pub result: String
+}
+
+/// Tokens of PGN text
+pub(crate) enum Token {
+    Tag(String, String),
+    San(String),
+    Result(String),
+    Comment(String),
+    Nag(u8),
+    StartVariation,
+    EndVariation
+}
+
+/// Splits PGN text into tokens, dropping move numbers and rest of line comments
+pub(crate) fn tokenize(_text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = _text.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '{' => {
+                // Comments run to the closing brace and do not nest
+                let mut comment = String::new();
+                for next in chars.by_ref() {
+                    if next == '}' {
+                        break;
+                    }
+                    comment.push(next);
+                }
+                tokens.push(Token::Comment(comment.split_whitespace().collect::<Vec<&str>>().join(" ")));
+            }
+            ';' => {
+                // Rest of line comment
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => tokens.push(Token::StartVariation),
+            ')' => tokens.push(Token::EndVariation),
+            '[' => {
+                let mut tag = String::new();
+                let mut in_string = false;
+                for next in chars.by_ref() {
+                    match next {
+                        '"' => in_string = !in_string,
+                        ']' if !in_string => break,
+                        _ => ()
+                    }
+                    tag.push(next);
+                }
+
+                let tag = tag.trim();
+                if let Some(space) = tag.find(char::is_whitespace) {
+                    let value = tag[space..].trim().trim_matches('"').replace("\\\"", "\"").replace("\\\\", "\\");
+                    tokens.push(Token::Tag(String::from(&tag[..space]), value));
+                }
+            }
+            _ if character.is_whitespace() => (),
+            _ => {
+                let mut word = String::from(character);
+                while let Some(next) = chars.peek() {
+                    if next.is_whitespace() || "{}();[".contains(*next) {
+                        break;
+                    }
+                    word.push(*next);
+                    chars.next();
+                }
+
+                if let Some(nag) = word.strip_prefix('$') {
+                    if let Ok(nag) = nag.parse() {
+                        tokens.push(Token::Nag(nag));
+                    }
+                    continue;
+                }
+
+                match word.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => tokens.push(Token::Result(word)),
+                    _ => {
+                        // Move numbers may be glued to the move, e.g "12.Nf3" or "12...Nf6", but "0-0" is castling
+                        let digits = word.trim_start_matches(|c: char| c.is_ascii_digit());
+                        let san = if digits.starts_with('.') {digits.trim_start_matches('.')} else {word.as_str()};
+                        if !san.is_empty() {
+                            tokens.push(Token::San(String::from(san)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Writes tag pairs with the seven tag roster first and in order, unknown roster tags as `"?"`
+pub(crate) fn write_tags(f: &mut fmt::Formatter, _tags: &[(String, String)], _result: &str) -> fmt::Result {
+    let get_tag = |name: &str| _tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str());
+
+    for name in SEVEN_TAG_ROSTER {
+        let value = match name {
+            "Result" => Some(_result),
+            _ => get_tag(name)
+        };
+        writeln!(f, "[{} \"{}\"]", name, value.unwrap_or("?").replace('\\', "\\\\").replace('"', "\\\""))?;
+    }
+    for (name, value) in _tags {
+        if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+    }
+    writeln!(f)
+}
+
+/// Writes words separated by spaces in lines of at most 80 characters, ending with a newline
+pub(crate) fn write_wrapped(f: &mut fmt::Formatter, _words: &[String]) -> fmt::Result {
+    let mut line_length = 0;
+    for word in _words {
+        if line_length > 0 && line_length + 1 + word.len() > 80 {
+            writeln!(f)?;
+            line_length = 0;
+        }
+        if line_length > 0 {
+            write!(f, " ")?;
+            line_length += 1;
+        }
+        write!(f, "{}", word)?;
+        line_length += word.len();
+    }
+
+    writeln!(f)
+}
+
+impl Pgn {
+    /// ## `parse_all`
+    /// Reads every game of PGN text such as a whole game archive.
+    /// ### Parameters
+    /// - `_text: &str`: The PGN text with any number of games
+    ///
+    /// ### Returns
+    /// Returns a `Vec<Pgn>` holding the games in the order they were given
+    pub fn parse_all(_text: &str) -> Vec<Pgn> {
+        let mut games = Vec::new();
+        let mut current = Pgn { tags: Vec::new(), moves: Vec::new(), result: String::from("*") };
+        let mut has_content = false;
+        let mut variation_depth = 0;
+
+        for token in tokenize(_text) {
+            match token {
+                Token::StartVariation => variation_depth += 1,
+                Token::EndVariation => variation_depth = (variation_depth - 1).max(0),
+                // Only the main line is kept
+                Token::San(_) | Token::Result(_) | Token::Comment(_) | Token::Nag(_) if variation_depth > 0 => (),
+                Token::Comment(_) | Token::Nag(_) => (),
+                Token::Tag(name, value) => {
+                    // Tags after moves start the next game, for archives where a result marker is missing
+                    if !current.moves.is_empty() {
+                        games.push(current);
+                        current = Pgn { tags: Vec::new(), moves: Vec::new(), result: String::from("*") };
+                    }
+                    current.tags.push((name, value));
+                    has_content = true;
+                }
+                Token::San(san) => {
+                    current.moves.push(san);
+                    has_content = true;
+                }
+                Token::Result(result) => {
+                    current.result = result;
+                    games.push(current);
+                    current = Pgn { tags: Vec::new(), moves: Vec::new(), result: String::from("*") };
+                    has_content = false;
+                }
+            }
+        }
+
+        if has_content {
+            if let Some((_, result)) = current.tags.iter().find(|(name, _)| name == "Result") {
+                current.result = result.clone();
+            }
+            games.push(current);
+        }
+
+        games
+    }
+
+    /// ## `parse`
+    /// Reads the first game of PGN text, `None` if there is none
+    pub fn parse(_text: &str) -> Option<Pgn> {
+        Pgn::parse_all(_text).into_iter().next()
+    }
+
+    /// Returns the value of a tag
+    pub fn get_tag(&self, _name: &str) -> Option<&str> {
+        self.tags.iter().find(|(name, _)| name == _name).map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of a tag, replacing an existing tag of the same name
+    pub fn set_tag(&mut self, _name: &str, _value: &str) {
+        match self.tags.iter_mut().find(|(name, _)| name == _name) {
+            Some(tag) => tag.1 = String::from(_value),
+            None => self.tags.push((String::from(_name), String::from(_value)))
+        }
+    }
+
+    /// ## `get_start`
+    /// Returns the starting position of the game, the standard one or the one of the `FEN` tag.
+    /// Games with the `Variant` tag `"Chess960"` are played with Chess960 castling, other `Variant` tags but `"From Position"`
+    /// are looked up with `variant::from_name`. Returns `None` for variants this crate does not know
+    pub fn get_start(&self) -> Option<Game> {
+        let tag = self.get_tag("Variant");
+        let chess960 = tag.is_some_and(|variant| variant.eq_ignore_ascii_case("chess960") || variant.eq_ignore_ascii_case("fischerandom"));
+        let rules = match tag {
+            Some(name) if !chess960 && !name.eq_ignore_ascii_case("from position") => variant::from_name(name)?,
+            _ => Arc::new(variant::Standard)
+        };
+
+        let mut game = Game::from_fen(self.get_tag("FEN").unwrap_or(rules.get_start_fen()))?;
+        if chess960 {
+            game.set_chess960(true);
+        }
+        game.set_variant(rules);
+
+        Some(game)
+    }
+
+    /// ## `to_game`
+    /// Replays the main line from the starting position.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `Game` after the last move, `None` if a move is illegal or the starting position invalid
+    pub fn to_game(&self) -> Option<Game> {
+        let mut game = self.get_start()?;
+
+        for san in &self.moves {
+            let _move = game.san_to_move(san)?;
+            game.apply_move(_move);
+        }
+
+        Some(game)
+    }
+}
+
+/// Writes the game as PGN with the seven tag roster first and the movetext wrapped at 80 characters
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_tags(f, &self.tags, &self.result)?;
+
+        // Move numbers count from the starting position, which may be Black to move
+        let start = self.get_start();
+        let mut number = start.as_ref().map_or(1, |game| game.fullmove_number);
+        let mut black = start.as_ref().is_some_and(|game| game.active_colour == Colour::Black);
+
+        let mut words: Vec<String> = Vec::with_capacity(self.moves.len() * 2 + 1);
+        for (index, san) in self.moves.iter().enumerate() {
+            if !black {
+                words.push(format!("{}.", number));
+            }
+            else if index == 0 {
+                words.push(format!("{}...", number));
+            }
+
+            words.push(san.clone());
+
+            if black {
+                number += 1;
+            }
+            black = !black;
+        }
+        words.push(self.result.clone());
+
+        write_wrapped(f, &words)
+    }
+}
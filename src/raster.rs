@@ -0,0 +1,562 @@
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece_data::Colour;
+use crate::svg::{piece_shapes, Shape, PIECE_STROKE};
+
+const LIGHT: [u8; 3] = [240, 217, 181];
+const DARK: [u8; 3] = [181, 136, 99];
+const LAST_MOVE: [u8; 3] = [205, 210, 106];
+const CHECK: [u8; 3] = [231, 0, 0];
+const WHITE: [u8; 3] = [255, 255, 255];
+const BLACK: [u8; 3] = [0, 0, 0];
+
+/// Samples per pixel along each axis when filling shapes, 4 gives 16 levels of antialiasing
+const SAMPLES: usize = 4;
+
+/// Size of the square the piece shapes are drawn in
+const PIECE_BOX: f64 = 45.0;
+
+/// ## Frame
+/// ### Type: `struct`
+/// An RGB image drawn in software.
+/// ### Members:
+/// - `width`, `height`: The size in pixels
+/// - `pixels`: The red, green and blue bytes of every pixel, row by row from the top left
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+
+/// A closed or open line of points
+type Polyline = (Vec<(f64, f64)>, bool);
+
+impl Frame {
+    /// Creates an image filled with one colour
+    pub fn new(_width: u32, _height: u32, _colour: [u8; 3]) -> Frame {
+        Frame {
+            width: _width,
+            height: _height,
+            pixels: _colour.iter().copied().cycle().take(_width as usize * _height as usize * 3).collect()
+        }
+    }
+
+    /// Returns the colour of a pixel
+    pub fn get_pixel(&self, _x: u32, _y: u32) -> [u8; 3] {
+        let index = (_y as usize * self.width as usize + _x as usize) * 3;
+        [self.pixels[index], self.pixels[index + 1], self.pixels[index + 2]]
+    }
+
+    /// Mixes a colour into a pixel, `_alpha` from 0 (unchanged) to 1 (replaced)
+    fn blend(&mut self, _x: usize, _y: usize, _colour: [u8; 3], _alpha: f64) {
+        let index = (_y * self.width as usize + _x) * 3;
+        for (pixel, channel) in self.pixels[index..index + 3].iter_mut().zip(_colour) {
+            let old = *pixel as f64;
+            *pixel = (old + (channel as f64 - old) * _alpha).round() as u8;
+        }
+    }
+
+    /// Fills a rectangle of whole pixels, clipped to the image
+    fn fill_rect(&mut self, _x: u32, _y: u32, _width: u32, _height: u32, _colour: [u8; 3], _alpha: f64) {
+        for y in _y..(_y + _height).min(self.height) {
+            for x in _x..(_x + _width).min(self.width) {
+                self.blend(x as usize, y as usize, _colour, _alpha);
+            }
+        }
+    }
+
+    /// ## `fill`
+    /// Fills the union of polygons with antialiased edges, each polygon with the non-zero winding rule
+    fn fill(&mut self, _polygons: &[Vec<(f64, f64)>], _colour: [u8; 3], _alpha: f64) {
+        let points = _polygons.iter().flatten();
+        let (mut left, mut top, mut right, mut bottom) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for (x, y) in points {
+            left = left.min(*x);
+            top = top.min(*y);
+            right = right.max(*x);
+            bottom = bottom.max(*y);
+        }
+
+        let x0 = left.floor().max(0.0) as usize;
+        let y0 = top.floor().max(0.0) as usize;
+        let x1 = (right.ceil().max(0.0) as usize).min(self.width as usize);
+        let y1 = (bottom.ceil().max(0.0) as usize).min(self.height as usize);
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+
+        let columns = (x1 - x0) * SAMPLES;
+        let mut covered = vec![false; columns * (y1 - y0) * SAMPLES];
+        let mut crossings: Vec<(f64, i32)> = Vec::new();
+
+        for polygon in _polygons.iter().filter(|polygon| polygon.len() > 2) {
+            for sample_row in 0..(y1 - y0) * SAMPLES {
+                let y = y0 as f64 + (sample_row as f64 + 0.5) / SAMPLES as f64;
+
+                crossings.clear();
+                for (index, a) in polygon.iter().enumerate() {
+                    let b = polygon[(index + 1) % polygon.len()];
+                    if (a.1 <= y) != (b.1 <= y) {
+                        let x = a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0);
+                        crossings.push((x, if b.1 > a.1 {1} else {-1}));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut winding = 0;
+                for pair in crossings.windows(2) {
+                    winding += pair[0].1;
+                    if winding == 0 {
+                        continue;
+                    }
+
+                    // Samples whose centre lies between the crossings
+                    let first = ((pair[0].0 - x0 as f64) * SAMPLES as f64 - 0.5).ceil().max(0.0) as usize;
+                    let last = ((pair[1].0 - x0 as f64) * SAMPLES as f64 - 0.5).ceil().max(0.0) as usize;
+                    for sample in first..last.min(columns) {
+                        covered[sample_row * columns + sample] = true;
+                    }
+                }
+            }
+        }
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut count = 0;
+                for sample_y in 0..SAMPLES {
+                    let row = ((y - y0) * SAMPLES + sample_y) * columns + (x - x0) * SAMPLES;
+                    count += covered[row..row + SAMPLES].iter().filter(|covered| **covered).count();
+                }
+                if count > 0 {
+                    self.blend(x, y, _colour, _alpha * count as f64 / (SAMPLES * SAMPLES) as f64);
+                }
+            }
+        }
+    }
+
+    /// Draws lines of a width with round joins and ends
+    fn stroke(&mut self, _lines: &[Polyline], _width: f64, _colour: [u8; 3]) {
+        let radius = _width / 2.0;
+        let mut polygons = Vec::new();
+
+        for (points, closed) in _lines {
+            let segments = if *closed {points.len()} else {points.len().saturating_sub(1)};
+            for index in 0..segments {
+                let (a, b) = (points[index], points[(index + 1) % points.len()]);
+                let length = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+                if length > 0.0 {
+                    let normal = (-(b.1 - a.1) / length * radius, (b.0 - a.0) / length * radius);
+                    polygons.push(vec![
+                        (a.0 + normal.0, a.1 + normal.1),
+                        (b.0 + normal.0, b.1 + normal.1),
+                        (b.0 - normal.0, b.1 - normal.1),
+                        (a.0 - normal.0, a.1 - normal.1)
+                    ]);
+                }
+            }
+            for point in points {
+                polygons.push(ellipse(*point, radius, radius));
+            }
+        }
+
+        self.fill(&polygons, _colour, 1.0);
+    }
+
+    /// Writes a number or lowercase letter from a 3 by 5 pixel font, each font pixel `_scale` pixels wide
+    fn draw_char(&mut self, _char: char, _x: u32, _y: u32, _scale: u32, _colour: [u8; 3]) {
+        let glyph: [&str; 5] = match _char {
+            '1' => [".#.", "##.", ".#.", ".#.", "###"],
+            '2' => ["##.", "..#", ".#.", "#..", "###"],
+            '3' => ["##.", "..#", ".#.", "..#", "##."],
+            '4' => ["#.#", "#.#", "###", "..#", "..#"],
+            '5' => ["###", "#..", "##.", "..#", "##."],
+            '6' => [".##", "#..", "###", "#.#", "###"],
+            '7' => ["###", "..#", ".#.", ".#.", ".#."],
+            '8' => ["###", "#.#", "###", "#.#", "###"],
+            'a' => ["...", ".##", "#.#", "#.#", ".##"],
+            'b' => ["#..", "##.", "#.#", "#.#", "##."],
+            'c' => ["...", ".##", "#..", "#..", ".##"],
+            'd' => ["..#", ".##", "#.#", "#.#", ".##"],
+            'e' => ["...", ".#.", "###", "#..", ".##"],
+            'f' => [".##", "#..", "###", "#..", "#.."],
+            'g' => ["...", ".##", "#.#", ".##", "##."],
+            'h' => ["#..", "##.", "#.#", "#.#", "#.#"],
+            _ => return
+        };
+
+        for (row, line) in glyph.iter().enumerate() {
+            for (col, pixel) in line.chars().enumerate() {
+                if pixel == '#' {
+                    self.fill_rect(_x + col as u32 * _scale, _y + row as u32 * _scale, _scale, _scale, _colour, 1.0);
+                }
+            }
+        }
+    }
+
+    /// Draws a piece into a square whose top left corner and width are given
+    fn draw_piece(&mut self, _colour: Colour, _shapes: &[Shape], _x: f64, _y: f64, _size: f64) {
+        let scale = _size / PIECE_BOX;
+        let place = |lines: Vec<Polyline>| -> Vec<Polyline> {
+            lines.into_iter()
+                .map(|(points, closed)| (points.into_iter().map(|(x, y)| (_x + x * scale, _y + y * scale)).collect(), closed))
+                .collect()
+        };
+        let (fill, detail) = if _colour == Colour::White {(WHITE, BLACK)} else {(BLACK, WHITE)};
+
+        for shape in _shapes {
+            let (lines, colour, width, filled) = match shape {
+                Shape::Path(path) => (parse_path(path), fill, PIECE_STROKE, true),
+                Shape::Circle(x, y, radius) => (vec![(ellipse((*x, *y), *radius, *radius), true)], fill, PIECE_STROKE, true),
+                Shape::Ellipse(x, y, radius_x, radius_y) => (vec![(ellipse((*x, *y), *radius_x, *radius_y), true)], fill, PIECE_STROKE, true),
+                Shape::Detail(path) => (parse_path(path), detail, PIECE_STROKE, false),
+                Shape::Dot(x, y, radius) => {
+                    let dot = place(vec![(ellipse((*x, *y), *radius, *radius), true)]);
+                    self.fill(&[dot[0].0.clone()], detail, 1.0);
+                    continue;
+                }
+                Shape::Outline(path, width) => (parse_path(path), BLACK, *width, false)
+            };
+
+            let lines = place(lines);
+            if filled {
+                let polygons: Vec<Vec<(f64, f64)>> = lines.iter().map(|(points, _)| points.clone()).collect();
+                self.fill(&polygons, colour, 1.0);
+                self.stroke(&lines, width * scale, BLACK);
+            }
+            else {
+                self.stroke(&lines, width * scale, colour);
+            }
+        }
+    }
+
+    /// ## `from_board`
+    /// Draws a board with its pieces the way `SvgRenderer` does, without arrows.
+    /// ### Parameters
+    /// - `_board: &Board`: The position to draw
+    /// - `_size: u32`: The width and height of the image, rounded down to a multiple of 8
+    /// - `_orientation: Colour`: The colour at the bottom
+    /// - `_coordinates: bool`: Whether ranks and files are written in the corners of the edge squares
+    /// - `_last_move: Option<Move>`: A move whose squares are highlighted
+    /// - `_check: Option<(i8, i8)>`: The square of a king in check, highlighted in red
+    pub fn from_board(_board: &Board, _size: u32, _orientation: Colour, _coordinates: bool, _last_move: Option<Move>, _check: Option<(i8, i8)>) -> Frame {
+        let square = (_size / 8).max(1);
+        let mut frame = Frame::new(square * 8, square * 8, LIGHT);
+        let corner = |(col, row): (i8, i8)| {
+            let (col, row) = if _orientation == Colour::Black {(7 - col, 7 - row)} else {(col, row)};
+            (col as u32 * square, row as u32 * square)
+        };
+
+        for row in 0..8 {
+            for col in 0..8 {
+                if (col + row) % 2 == 1 {
+                    let (x, y) = corner((col, row));
+                    frame.fill_rect(x, y, square, square, DARK, 1.0);
+                }
+            }
+        }
+
+        if let Some(last_move) = _last_move {
+            for highlighted in [last_move.from, last_move.to] {
+                let (x, y) = corner(highlighted);
+                frame.fill_rect(x, y, square, square, LAST_MOVE, 0.8);
+            }
+        }
+
+        // The same radial gradient as the SVG, full red up to a quarter of the radius fading out at 89%
+        if let Some(check) = _check {
+            let (x, y) = corner(check);
+            let radius = square as f64 / 2.0;
+            for pixel_y in y..y + square {
+                for pixel_x in x..x + square {
+                    let distance = ((pixel_x as f64 + 0.5 - x as f64 - radius).powi(2) + (pixel_y as f64 + 0.5 - y as f64 - radius).powi(2)).sqrt() / radius;
+                    let alpha = if distance <= 0.25 {1.0} else {(1.0 - (distance - 0.25) / 0.64).max(0.0)};
+                    frame.blend(pixel_x as usize, pixel_y as usize, CHECK, alpha);
+                }
+            }
+        }
+
+        if _coordinates {
+            let scale = (square / 20).max(1);
+            let margin = scale;
+            for index in 0..8i8 {
+                let (rank_square, file_square) = if _orientation == Colour::Black {((7, index), (index, 0))} else {((0, index), (index, 7))};
+
+                // Labels take the colour of the other squares so they show on both
+                let (x, y) = corner(rank_square);
+                let colour = if (rank_square.0 + rank_square.1) % 2 == 0 {DARK} else {LIGHT};
+                frame.draw_char((b'8' - rank_square.1 as u8) as char, x + margin, y + margin, scale, colour);
+
+                let (x, y) = corner(file_square);
+                let colour = if (file_square.0 + file_square.1) % 2 == 0 {DARK} else {LIGHT};
+                frame.draw_char((b'a' + file_square.0 as u8) as char, x + square - margin - 3 * scale, y + square - margin - 5 * scale, scale, colour);
+            }
+        }
+
+        for row in 0..8 {
+            for col in 0..8 {
+                if _board.is_empty((col, row)) {
+                    continue;
+                }
+                let (colour, piece_type) = _board.piece_at((col, row)).get_piece_data();
+                let (x, y) = corner((col, row));
+                frame.draw_piece(colour, piece_shapes(piece_type), x as f64, y as f64, square as f64);
+            }
+        }
+
+        frame
+    }
+
+    /// ## `to_png`
+    /// Encodes the image as a PNG file, compressed with fixed Huffman codes
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width as usize * 3 + 1) * self.height as usize);
+        for row in self.pixels.chunks(self.width as usize * 3) {
+            // Every row starts with its filter type, 0 for none
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8 bits per channel, RGB, deflate, no filter method beyond the default, not interlaced
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        write_chunk(&mut png, b"IHDR", &header);
+        write_chunk(&mut png, b"IDAT", &zlib_compress(&raw));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+/// Returns the points around an ellipse
+fn ellipse(_centre: (f64, f64), _radius_x: f64, _radius_y: f64) -> Vec<(f64, f64)> {
+    (0..32).map(|index| {
+        let angle = index as f64 * std::f64::consts::TAU / 32.0;
+        (_centre.0 + _radius_x * angle.cos(), _centre.1 + _radius_y * angle.sin())
+    }).collect()
+}
+
+/// Reads an SVG path with absolute `M`, `L`, `C` and `Z` commands into lines, curves are split into straight pieces
+fn parse_path(_path: &str) -> Vec<Polyline> {
+    let mut lines: Vec<Polyline> = Vec::new();
+    let mut tokens = _path.split(|c: char| c.is_whitespace() || c == ',').filter(|token| !token.is_empty()).peekable();
+    let mut command = 'M';
+
+    let number = |tokens: &mut std::iter::Peekable<_>| -> f64 {
+        let token: Option<&str> = tokens.next();
+        token.and_then(|token| token.parse().ok()).unwrap_or(0.0)
+    };
+
+    while let Some(token) = tokens.peek() {
+        if let Some(letter) = token.chars().next().filter(|c| c.is_ascii_alphabetic()) {
+            command = letter;
+            tokens.next();
+        }
+
+        match command {
+            'M' => {
+                let point = (number(&mut tokens), number(&mut tokens));
+                lines.push((vec![point], false));
+                command = 'L';
+            }
+            'L' => {
+                let point = (number(&mut tokens), number(&mut tokens));
+                if let Some((points, _)) = lines.last_mut() {
+                    points.push(point);
+                }
+            }
+            'C' => {
+                let controls = [
+                    (number(&mut tokens), number(&mut tokens)),
+                    (number(&mut tokens), number(&mut tokens)),
+                    (number(&mut tokens), number(&mut tokens))
+                ];
+                if let Some((points, _)) = lines.last_mut() {
+                    let start = *points.last().unwrap_or(&controls[0]);
+                    for step in 1..=12 {
+                        let t = step as f64 / 12.0;
+                        let u = 1.0 - t;
+                        let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+                        points.push((
+                            weights[0] * start.0 + weights[1] * controls[0].0 + weights[2] * controls[1].0 + weights[3] * controls[2].0,
+                            weights[0] * start.1 + weights[1] * controls[0].1 + weights[2] * controls[1].1 + weights[3] * controls[2].1
+                        ));
+                    }
+                }
+            }
+            'Z' => {
+                if let Some(line) = lines.last_mut() {
+                    line.1 = true;
+                }
+                // Numbers after Z are not valid, they are skipped
+                command = ' ';
+            }
+            _ => {
+                tokens.next();
+            }
+        }
+    }
+
+    lines
+}
+
+/// Appends a PNG chunk with its length and checksum
+fn write_chunk(_png: &mut Vec<u8>, _kind: &[u8; 4], _data: &[u8]) {
+    _png.extend_from_slice(&(_data.len() as u32).to_be_bytes());
+    let start = _png.len();
+    _png.extend_from_slice(_kind);
+    _png.extend_from_slice(_data);
+    let crc = crc32(&_png[start..]);
+    _png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// CRC-32 as used by PNG, polynomial 0xEDB88320
+fn crc32(_data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in _data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {(crc >> 1) ^ 0xEDB8_8320} else {crc >> 1};
+        }
+    }
+    !crc
+}
+
+/// Writes bits least significant first, the order of deflate and GIF
+pub(crate) struct BitWriter {
+    pub(crate) bytes: Vec<u8>,
+    buffer: u32,
+    count: u32
+}
+
+impl BitWriter {
+    pub(crate) fn new() -> BitWriter {
+        BitWriter { bytes: Vec::new(), buffer: 0, count: 0 }
+    }
+
+    /// Writes the lowest `_bits` bits of a value
+    pub(crate) fn write(&mut self, _value: u32, _bits: u32) {
+        self.buffer |= _value << self.count;
+        self.count += _bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which deflate stores most significant bit first
+    fn write_code(&mut self, _code: u32, _bits: u32) {
+        let reversed = _code.reverse_bits() >> (32 - _bits);
+        self.write(reversed, _bits);
+    }
+
+    /// Writes the last partial byte
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// Deflate length codes 257 to 285 and distance codes 0 to 29, their smallest value and extra bits
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Writes a literal or length symbol with the fixed Huffman codes of deflate
+fn write_fixed_symbol(_writer: &mut BitWriter, _symbol: u16) {
+    let symbol = _symbol as u32;
+    match symbol {
+        0..=143 => _writer.write_code(0x30 + symbol, 8),
+        144..=255 => _writer.write_code(0x190 + symbol - 144, 9),
+        256..=279 => _writer.write_code(symbol - 256, 7),
+        _ => _writer.write_code(0xC0 + symbol - 280, 8)
+    }
+}
+
+/// ## `zlib_compress`
+/// Compresses data into a zlib stream of one deflate block with fixed Huffman codes, finding repeats with a hash chain
+fn zlib_compress(_data: &[u8]) -> Vec<u8> {
+    const WINDOW: usize = 32768;
+    const MAX_LENGTH: usize = 258;
+    const HASH_SIZE: usize = 1 << 15;
+    const MAX_TRIES: usize = 32;
+
+    let mut writer = BitWriter::new();
+    // The zlib header, deflate with a 32K window and no dictionary
+    writer.write(0x78, 8);
+    writer.write(0x01, 8);
+    // One final block with fixed codes
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    let hash = |position: usize| -> usize {
+        ((_data[position] as usize) << 10 ^ (_data[position + 1] as usize) << 5 ^ _data[position + 2] as usize) % HASH_SIZE
+    };
+    let mut head = vec![usize::MAX; HASH_SIZE];
+    let mut previous = vec![usize::MAX; _data.len()];
+
+    let mut position = 0;
+    while position < _data.len() {
+        let mut best = (0, 0);
+
+        if position + 3 <= _data.len() {
+            let mut candidate = head[hash(position)];
+            let mut tries = 0;
+            while candidate != usize::MAX && position - candidate <= WINDOW && tries < MAX_TRIES {
+                let length = _data[candidate..].iter().zip(&_data[position..]).take(MAX_LENGTH).take_while(|(a, b)| a == b).count();
+                if length > best.0 {
+                    best = (length, position - candidate);
+                    if length == MAX_LENGTH {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+                tries += 1;
+            }
+        }
+
+        let advance = if best.0 >= 3 {
+            let (length, distance) = best;
+            let code = LENGTH_BASES.iter().rposition(|base| *base as usize <= length).expect("lengths start at 3");
+            write_fixed_symbol(&mut writer, 257 + code as u16);
+            writer.write((length - LENGTH_BASES[code] as usize) as u32, LENGTH_EXTRA[code] as u32);
+
+            let code = DISTANCE_BASES.iter().rposition(|base| *base as usize <= distance).expect("distances start at 1");
+            writer.write_code(code as u32, 5);
+            writer.write((distance - DISTANCE_BASES[code] as usize) as u32, DISTANCE_EXTRA[code] as u32);
+            length
+        }
+        else {
+            write_fixed_symbol(&mut writer, _data[position] as u16);
+            1
+        };
+
+        // Positions too close to the end for three bytes are not hashed
+        let end = (position + advance).min(_data.len().saturating_sub(2));
+        for (inserted, link) in previous.iter_mut().enumerate().take(end).skip(position) {
+            let key = hash(inserted);
+            *link = head[key];
+            head[key] = inserted;
+        }
+        position += advance;
+    }
+
+    write_fixed_symbol(&mut writer, 256);
+    let mut compressed = writer.finish();
+
+    // Adler-32 of the uncompressed data
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in _data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    compressed.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    compressed
+}
@@ -0,0 +1,166 @@
+use crate::chess::board::Board;
+use crate::chess::piece_data::Colour;
+use crate::Game;
+use std::fmt;
+
+/// How pieces are drawn
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Glyphs {
+    /// FEN letters, uppercase for White and lowercase for Black
+    Letters,
+    /// The Unicode chess symbols, e.g ♔ and ♚
+    Unicode
+}
+
+/// How light and dark squares are told apart
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shading {
+    /// Every empty square is drawn the same
+    None,
+    /// Empty dark squares are drawn with a shade character
+    Text,
+    /// Squares get light and dark background colours with ANSI escape codes, for terminals
+    Ansi
+}
+
+/// ## Renderer
+/// ### Type: `struct`
+/// Options for drawing a board as text. `Board` and `Game` implement `Display` with the default options.
+/// ### Members:
+/// - `glyphs`: Letters or Unicode symbols for the pieces
+/// - `shading`: How light and dark squares are told apart
+/// - `coordinates`: Whether ranks and files are labelled
+/// - `orientation`: The colour at the bottom of the board
+/// - `marked`: Squares to mark, drawn in brackets or with a highlighted background for `Shading::Ansi`
+#[derive(Clone, Debug)]
+pub struct Renderer {
+    pub glyphs: Glyphs,
+    pub shading: Shading,
+    pub coordinates: bool,
+    pub orientation: Colour,
+    pub marked: Vec<(i8, i8)>
+}
+
+// 256 colour backgrounds of `Shading::Ansi`
+const ANSI_LIGHT: u8 = 223;
+const ANSI_DARK: u8 = 137;
+const ANSI_MARKED: (u8, u8) = (151, 108);
+
+impl Renderer {
+    /// Creates the default renderer, letters from White's side with coordinates and no shading
+    pub fn new() -> Renderer {
+        Renderer {
+            glyphs: Glyphs::Letters,
+            shading: Shading::None,
+            coordinates: true,
+            orientation: Colour::White,
+            marked: Vec::new()
+        }
+    }
+
+    /// ## `render`
+    /// Draws a board, one line per rank from the top of the board. Every square is three characters wide
+    /// ### Returns
+    /// Returns the drawn board as a `String` ending with a newline
+    pub fn render(&self, _board: &Board) -> String {
+        let mut text = String::with_capacity(512);
+        let flipped = self.orientation == Colour::Black;
+
+        for line in 0..8 {
+            let row = if flipped {7 - line} else {line};
+            if self.coordinates {
+                text.push_str(&format!("{} ", 8 - row));
+            }
+
+            for column in 0..8 {
+                let col = if flipped {7 - column} else {column};
+                text.push_str(&self.render_square(_board, (col, row)));
+            }
+            text.push('\n');
+        }
+
+        if self.coordinates {
+            text.push_str("  ");
+            for column in 0..8 {
+                let file = if flipped {b'h' - column} else {b'a' + column};
+                text.push_str(&format!(" {} ", file as char));
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Draws one square as three characters, with escape codes around them for `Shading::Ansi`
+    fn render_square(&self, _board: &Board, _square: (i8, i8)) -> String {
+        let light = (_square.0 + _square.1) % 2 == 0;
+        let marked = self.marked.contains(&_square);
+
+        let icon = if _board.is_empty(_square) {
+            match (self.shading, self.glyphs, light) {
+                (Shading::Text, Glyphs::Letters, false) => '.',
+                (Shading::Text, Glyphs::Unicode, false) => '░',
+                (Shading::Text, _, true) | (Shading::Ansi, _, _) => ' ',
+                (Shading::None, Glyphs::Letters, _) => '.',
+                (Shading::None, Glyphs::Unicode, _) => '·'
+            }
+        }
+        else {
+            match self.glyphs {
+                Glyphs::Letters => _board.piece_at(_square).get_icon(),
+                Glyphs::Unicode => _board.piece_at(_square).get_unicode_icon()
+            }
+        };
+
+        match self.shading {
+            Shading::Ansi => {
+                let background = match (marked, light) {
+                    (true, true) => ANSI_MARKED.0,
+                    (true, false) => ANSI_MARKED.1,
+                    (false, true) => ANSI_LIGHT,
+                    (false, false) => ANSI_DARK
+                };
+                format!("\x1b[48;5;{};30m {} \x1b[0m", background, icon)
+            }
+            _ if marked => format!("[{}]", icon),
+            _ => format!(" {} ", icon)
+        }
+    }
+
+    /// Returns a value that draws the board when formatted with `{}`
+    pub fn display<'a>(&'a self, _board: &'a Board) -> RenderedBoard<'a> {
+        RenderedBoard { renderer: self, board: _board }
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer::new()
+    }
+}
+
+/// A board with the options to draw it, see `Renderer::display`
+pub struct RenderedBoard<'a> {
+    renderer: &'a Renderer,
+    board: &'a Board
+}
+
+impl fmt::Display for RenderedBoard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.renderer.render(self.board))
+    }
+}
+
+/// Draws the board with the default `Renderer`
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Renderer::new().render(self))
+    }
+}
+
+/// Draws the board of the game with the default `Renderer`
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.board)
+    }
+}
@@ -0,0 +1,191 @@
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece_data::PieceType;
+use crate::{Game, GameState};
+
+/// Returns the SAN letter of a piece type, pawns have none
+fn piece_letter(_piece_type: PieceType) -> Option<char> {
+    match _piece_type {
+        PieceType::Knight => Some('N'),
+        PieceType::Bishop => Some('B'),
+        PieceType::Rook => Some('R'),
+        PieceType::Queen => Some('Q'),
+        PieceType::King => Some('K'),
+        _ => None
+    }
+}
+
+/// Returns the piece type of a SAN letter
+fn letter_piece(_letter: char) -> Option<PieceType> {
+    match _letter {
+        'N' => Some(PieceType::Knight),
+        'B' => Some(PieceType::Bishop),
+        'R' => Some(PieceType::Rook),
+        'Q' => Some(PieceType::Queen),
+        'K' => Some(PieceType::King),
+        _ => None
+    }
+}
+
+impl Game {
+    /// ## `move_to_san`
+    /// Takes a legal move of the active colour and returns it in Standard Algebraic Notation, e.g `"Nbd7"`, `"exd5"` or `"e8=Q+"`
+    /// ### Parameters
+    /// - `_move: &Move`: The move to write, it should be one of `get_all_legal_moves`
+    pub fn move_to_san(&self, _move: &Move) -> String {
+        let mut san = String::with_capacity(7);
+        let piece_type = self.board.piece_at(_move.from).get_type();
+        let capture = !self.board.is_empty(_move.to)
+            || (piece_type == PieceType::Pawn && _move.from.0 != _move.to.0);
+
+        // Castling is written by the side it is done on
+        if self.board.is_castling(_move.from, _move.to) {
+            san.push_str(if _move.to.0 > _move.from.0 {"O-O"} else {"O-O-O"});
+        }
+        else {
+            san.push_str(&self.san_body(_move, piece_type, capture));
+        }
+
+        // Check or checkmate suffix
+        let mut after = self.clone();
+        after.apply_move(*_move);
+        if after.get_game_state() == GameState::Check {
+            san.push(if after.get_all_legal_moves().is_empty() {'#'} else {'+'});
+        }
+
+        san
+    }
+
+    /// Returns the SAN of a move that is not castling, without the check suffix
+    fn san_body(&self, _move: &Move, _piece_type: PieceType, _capture: bool) -> String {
+        let mut san = String::with_capacity(6);
+
+        match piece_letter(_piece_type) {
+            Some(letter) => {
+                san.push(letter);
+
+                // Disambiguate between pieces of the same type that can reach the same square, file first, then rank, then both
+                let others: Vec<Move> = self.get_all_legal_moves().into_iter()
+                    .filter(|other| other.to == _move.to && other.from != _move.from
+                        && self.board.piece_at(other.from).get_type() == _piece_type)
+                    .collect();
+
+                if !others.is_empty() {
+                    let from = Board::num_to_filerank(&_move.from);
+                    if others.iter().all(|other| other.from.0 != _move.from.0) {
+                        san.push_str(&from[0..1]);
+                    }
+                    else if others.iter().all(|other| other.from.1 != _move.from.1) {
+                        san.push_str(&from[1..2]);
+                    }
+                    else {
+                        san.push_str(&from);
+                    }
+                }
+            }
+            None => {
+                // Pawn captures are written with the file they come from
+                if _capture {
+                    san.push_str(&Board::num_to_filerank(&_move.from)[0..1]);
+                }
+            }
+        }
+
+        if _capture {
+            san.push('x');
+        }
+
+        san.push_str(&Board::num_to_filerank(&_move.to));
+
+        if let Some(letter) = _move.promotion.and_then(piece_letter) {
+            san.push('=');
+            san.push(letter);
+        }
+
+        san
+    }
+
+    /// ## `san_to_move`
+    /// Takes a move in Standard Algebraic Notation and finds the legal move of the active colour that it describes.
+    /// Check and annotation suffixes are ignored, and the `=` of promotions may be left out.
+    /// ### Parameters
+    /// - `_san: &str`: The move given in SAN, e.g `"Nf3"`, `"exd5"` or `"e8=Q"`
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping a `Move`
+    /// `
+    /// Option<Move>
+    /// `
+    /// or `None` if the SAN is malformed, illegal or ambiguous
+    pub fn san_to_move(&self, _san: &str) -> Option<Move> {
+        // Castling, with letter O or digit 0
+        let castling = _san.trim().trim_end_matches(['+', '#', '!', '?']).replace('0', "O");
+        if castling == "O-O" || castling == "O-O-O" {
+            let direction = if castling == "O-O" {1} else {-1};
+            return self.get_all_legal_moves().into_iter().find(|_move| {
+                self.board.is_castling(_move.from, _move.to) && (_move.to.0 - _move.from.0).signum() == direction
+            });
+        }
+
+        let mut san: Vec<char> = _san.trim()
+            .trim_end_matches("e.p.")
+            .trim_end_matches(['+', '#', '!', '?'])
+            .chars()
+            .filter(|character| *character != 'x' && *character != '-' && *character != ':')
+            .collect();
+
+        // Promotion, either "e8=Q" or "e8Q"
+        let mut promotion = None;
+        if let Some(position) = san.iter().position(|character| *character == '=') {
+            promotion = Some(letter_piece(*san.get(position + 1)?)?);
+            san.truncate(position);
+        }
+        else if san.len() > 2 && san[san.len() - 2].is_ascii_digit() {
+            if let Some(piece_type) = letter_piece(san[san.len() - 1]) {
+                promotion = Some(piece_type);
+                san.pop();
+            }
+        }
+
+        let piece_type = match san.first() {
+            Some(letter) if letter.is_ascii_uppercase() => {
+                let piece_type = letter_piece(*letter)?;
+                san.remove(0);
+                piece_type
+            }
+            _ => PieceType::Pawn
+        };
+
+        // The last two characters are the destination, anything before them disambiguates the starting square
+        if san.len() < 2 || san.len() > 4 {
+            return None;
+        }
+        let destination: String = san[san.len() - 2..].iter().collect();
+        let to = Move::from_uci(&format!("{}{}", destination, destination))?.to;
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for hint in &san[..san.len() - 2] {
+            match hint {
+                'a'..='h' => from_file = Some(*hint as i8 - 'a' as i8),
+                '1'..='8' => from_rank = Some('8' as i8 - *hint as i8),
+                _ => return None
+            }
+        }
+
+        let mut candidates = self.get_all_legal_moves().into_iter().filter(|_move| {
+            _move.to == to
+            && _move.promotion == promotion
+            && self.board.piece_at(_move.from).get_type() == piece_type
+            && from_file.is_none_or(|file| _move.from.0 == file)
+            && from_rank.is_none_or(|rank| _move.from.1 == rank)
+        });
+
+        let found = candidates.next()?;
+        if candidates.next().is_some() {
+            return None;
+        }
+
+        Some(found)
+    }
+}
@@ -0,0 +1,349 @@
+use crate::chess::moves::Move;
+use crate::chess::piece_data::{Colour, PieceType};
+use crate::tablebase::{Tablebase, Wdl};
+use crate::{Game, GameState};
+use std::time::{Duration, Instant};
+
+/// Score of a checkmate at the root, mates further away score lower by one per ply. Wins by a rule of the variant score the same
+pub const MATE_SCORE: i32 = 100_000;
+
+/// Scores above this are mates
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+/// Score of a position the tablebase says is won, below any mate and above any evaluation
+const TABLEBASE_WIN: i32 = MATE_THRESHOLD - 1000;
+
+/// ## SearchLimits
+/// ### Type: `struct`
+/// Limits for `Game::search`, the search stops at whichever is reached first.
+/// ### Members:
+/// - `max_depth`: The deepest iteration in plies
+/// - `time`: The time the search may take, `None` for no limit
+#[derive(Copy, Clone, Debug)]
+pub struct SearchLimits {
+    pub max_depth: u32,
+    pub time: Option<Duration>
+}
+
+impl SearchLimits {
+    /// Limits the search to a depth without a time limit
+    pub fn depth(_max_depth: u32) -> SearchLimits {
+        SearchLimits { max_depth: _max_depth, time: None }
+    }
+
+    /// Limits the search to a time with no practical depth limit
+    pub fn time(_time: Duration) -> SearchLimits {
+        SearchLimits { max_depth: 64, time: Some(_time) }
+    }
+}
+
+/// ## SearchResult
+/// ### Type: `struct`
+/// Result of the deepest completed iteration of `Game::search`.
+/// ### Members:
+/// - `best_move`: The best move found, `None` if there are no legal moves
+/// - `score`: The score in centipawns from the active colour's point of view, see `MATE_SCORE` for mates
+/// - `depth`: The depth of the deepest completed iteration
+/// - `nodes`: The number of positions visited
+#[derive(Copy, Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64
+}
+
+impl SearchResult {
+    /// Returns the number of moves to a mate if the score is a mate. Positive if the active colour mates, negative if it gets mated
+    pub fn mate_in(&self) -> Option<i32> {
+        if self.score > MATE_THRESHOLD {
+            Some((MATE_SCORE - self.score + 1) / 2)
+        }
+        else if self.score < -MATE_THRESHOLD {
+            Some(-(MATE_SCORE + self.score) / 2)
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// Material value of a piece type in centipawns
+fn piece_value(_piece_type: PieceType) -> i32 {
+    match _piece_type {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 320,
+        PieceType::Bishop => 330,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        _ => 0
+    }
+}
+
+/// Returns the distance of a square to the four centre squares, 0 in the centre and 3 on the edge
+pub(crate) fn centre_distance(_square: (i8, i8)) -> i32 {
+    (2 * _square.0 - 7).abs().max((2 * _square.1 - 7).abs()) as i32 / 2
+}
+
+/// Score of a tablebase result at a ply, wins further away score lower
+fn wdl_score(_wdl: Wdl, _ply: i32) -> i32 {
+    match _wdl {
+        Wdl::Win => TABLEBASE_WIN - _ply,
+        Wdl::Loss => -TABLEBASE_WIN + _ply,
+        // Wins and losses spoilt by the fifty move rule are only slightly better than a draw
+        Wdl::CursedWin => 1,
+        Wdl::BlessedLoss => -1,
+        Wdl::Draw => 0
+    }
+}
+
+/// Score of a position a variant rule ended the game in, scored like a mate. `None` if the game goes on
+fn outcome_score(_game: &Game, _ply: i32) -> Option<i32> {
+    let termination = _game.termination?;
+    Some(match termination.winner {
+        Some(winner) if winner == _game.active_colour => MATE_SCORE - _ply,
+        Some(_) => -MATE_SCORE + _ply,
+        None => 0
+    })
+}
+
+/// Bookkeeping shared by the whole search
+struct Searcher<'a> {
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+    tablebase: Option<&'a Tablebase>
+}
+
+impl Searcher<'_> {
+    /// Probes the tablebase for a position, only right after a capture or pawn move as tables do not know the fifty move clock
+    fn probe(&self, _game: &Game) -> Option<Wdl> {
+        if _game.halfmove_clock != 0 {
+            return None;
+        }
+        self.tablebase?.probe_wdl(&_game.board, _game.active_colour).ok()
+    }
+
+    /// Returns true once the time is up. The clock is only read every 1024 nodes
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted && self.nodes.is_multiple_of(1024) {
+            if let Some(deadline) = self.deadline {
+                self.aborted = Instant::now() >= deadline;
+            }
+        }
+        self.aborted
+    }
+
+    /// Negamax alpha-beta search returning the score from the active colour's point of view
+    fn alpha_beta(&mut self, _game: &Game, _depth: u32, _ply: i32, mut _alpha: i32, _beta: i32) -> i32 {
+        self.nodes += 1;
+
+        if let Some(score) = outcome_score(_game, _ply) {
+            return score;
+        }
+        if _game.halfmove_clock >= 100 {
+            return 0;
+        }
+        if let Some(wdl) = self.probe(_game) {
+            return wdl_score(wdl, _ply);
+        }
+        if _depth == 0 {
+            return self.quiescence(_game, _ply, _alpha, _beta);
+        }
+
+        let moves = _game.order_moves(_game.get_all_legal_moves());
+        if moves.is_empty() {
+            return if _game.get_game_state() == GameState::Check {-MATE_SCORE + _ply} else {0};
+        }
+
+        for _move in moves {
+            let mut child = _game.clone();
+            child.apply_move(_move);
+
+            let score = -self.alpha_beta(&child, _depth - 1, _ply + 1, -_beta, -_alpha);
+            if self.out_of_time() {
+                return 0;
+            }
+
+            if score >= _beta {
+                return _beta;
+            }
+            if score > _alpha {
+                _alpha = score;
+            }
+        }
+
+        _alpha
+    }
+
+    /// Searches captures only until the position is quiet, so that the evaluation is not taken in the middle of an exchange
+    fn quiescence(&mut self, _game: &Game, _ply: i32, mut _alpha: i32, _beta: i32) -> i32 {
+        self.nodes += 1;
+
+        if let Some(score) = outcome_score(_game, _ply) {
+            return score;
+        }
+
+        let stand_pat = _game.evaluate();
+        if stand_pat >= _beta {
+            return _beta;
+        }
+        if stand_pat > _alpha {
+            _alpha = stand_pat;
+        }
+
+        let captures: Vec<Move> = _game.get_all_legal_moves().into_iter()
+            .filter(|_move| (!_game.board.is_empty(_move.to) && !_game.board.is_castling(_move.from, _move.to)) || _move.promotion == Some(PieceType::Queen))
+            .collect();
+
+        for _move in _game.order_moves(captures) {
+            let mut child = _game.clone();
+            child.apply_move(_move);
+
+            let score = -self.quiescence(&child, _ply + 1, -_beta, -_alpha);
+            if self.out_of_time() {
+                return 0;
+            }
+
+            if score >= _beta {
+                return _beta;
+            }
+            if score > _alpha {
+                _alpha = score;
+            }
+        }
+
+        _alpha
+    }
+}
+
+impl Game {
+    /// ## `evaluate`
+    /// Returns a static evaluation of the position in centipawns from the active colour's point of view.
+    /// Counts material and gives a small bonus to pieces close to the centre, plus the bonus of the variant
+    pub fn evaluate(&self) -> i32 {
+        let mut score = 0;
+
+        for row in 0..8 {
+            for col in 0..8 {
+                let piece = self.board.piece_at((col, row));
+                let piece_type = piece.get_type();
+                if piece_type == PieceType::None {
+                    continue;
+                }
+
+                let centre_distance = centre_distance((col, row));
+                let centre_bonus = match piece_type {
+                    PieceType::Knight | PieceType::Bishop => 10 * (3 - centre_distance),
+                    PieceType::Pawn => 5 * (3 - centre_distance),
+                    _ => 0
+                };
+
+                let value = piece_value(piece_type) + centre_bonus;
+                score += if piece.get_colour() == Colour::White {value} else {-value};
+            }
+        }
+
+        (if self.active_colour == Colour::White {score} else {-score}) + self.variant.evaluate(self)
+    }
+
+    /// Orders moves with the most valuable victim captured by the least valuable attacker first
+    fn order_moves(&self, mut _moves: Vec<Move>) -> Vec<Move> {
+        _moves.sort_by_key(|_move| {
+            let victim = piece_value(self.board.piece_at(_move.to).get_type());
+            let attacker = piece_value(self.board.piece_at(_move.from).get_type());
+            let promotion = _move.promotion.map_or(0, piece_value);
+            -(10 * victim - attacker / 10 + promotion)
+        });
+        _moves
+    }
+
+    /// ## `search`
+    /// Searches the position with iterative deepening alpha-beta and returns the best move found within the limits.
+    /// ### Parameters
+    /// - `_limits: SearchLimits`: The depth and time limits of the search
+    ///
+    /// ### Returns
+    /// Returns the `SearchResult` of the deepest completed iteration
+    pub fn search(&self, _limits: SearchLimits) -> SearchResult {
+        self.search_with_tablebase(_limits, None)
+    }
+
+    /// ## `search_with_tablebase`
+    /// Same as `search` but uses endgame tablebases. At the root only the moves that keep the best
+    /// tablebase result are searched, and in the tree positions found in the tables are not searched further.
+    /// ### Parameters
+    /// - `_limits: SearchLimits`: The depth and time limits of the search
+    /// - `_tablebase: Option<&Tablebase>`: The tables to use, `None` searches without them
+    pub fn search_with_tablebase(&self, _limits: SearchLimits, _tablebase: Option<&Tablebase>) -> SearchResult {
+        let mut searcher = Searcher {
+            nodes: 0,
+            deadline: _limits.time.map(|time| Instant::now() + time),
+            aborted: false,
+            tablebase: _tablebase
+        };
+
+        let mut moves = self.order_moves(self.get_all_legal_moves());
+
+        // Root filtering, only when every move can be probed
+        let probed: Option<Vec<Wdl>> = _tablebase.and_then(|tablebase| moves.iter().map(|_move| {
+            let mut child = self.clone();
+            child.apply_move(*_move);
+            tablebase.probe_wdl(&child.board, child.active_colour).ok()
+        }).collect());
+        if let Some(results) = probed {
+            // The child's result is from the opponent's point of view, so the best move leaves the lowest
+            if let Some(best) = results.iter().min().copied() {
+                let mut results = results.iter();
+                moves.retain(|_| results.next() == Some(&best));
+            }
+        }
+        let mut result = SearchResult {
+            best_move: moves.first().copied(),
+            score: if self.state == GameState::Check && moves.is_empty() {-MATE_SCORE} else {0},
+            depth: 0,
+            nodes: 0
+        };
+
+        for depth in 1..=_limits.max_depth.max(1) {
+            if moves.is_empty() {
+                break;
+            }
+
+            let mut alpha = -MATE_SCORE - 1;
+            let mut best = moves[0];
+
+            for _move in &moves {
+                let mut child = self.clone();
+                child.apply_move(*_move);
+
+                let score = -searcher.alpha_beta(&child, depth - 1, 1, -MATE_SCORE - 1, -alpha);
+                if searcher.out_of_time() {
+                    break;
+                }
+
+                if score > alpha {
+                    alpha = score;
+                    best = *_move;
+                }
+            }
+
+            // Results of an interrupted iteration are not trusted
+            if searcher.aborted {
+                break;
+            }
+
+            result = SearchResult { best_move: Some(best), score: alpha, depth, nodes: searcher.nodes };
+
+            // Search the best move first in the next iteration, and stop once a forced mate is found
+            moves.retain(|_move| *_move != best);
+            moves.insert(0, best);
+            if alpha.abs() > MATE_THRESHOLD {
+                break;
+            }
+        }
+
+        result.nodes = searcher.nodes;
+        result
+    }
+}
@@ -0,0 +1,128 @@
+//! `Serialize` and `Deserialize` for the types that cannot derive them, enabled by the `serde` feature.
+//! - `Piece`: its FEN letter, e.g `"N"` or `"q"`, `null` for an empty square
+//! - `Board`: the placement, castling and en passant fields of its FEN string
+//! - `Game`: the starting position, the moves played in UCI notation and the current position, both as FEN strings
+//!
+//! Both have a `chess960` field that is only written for Chess960, a game also has a `variant` field with the PGN name of
+//! its variant if it is not standard chess and a `termination` once it was resigned, agreed, claimed or lost on time
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece::Piece;
+use crate::chess::piece_data::PieceType;
+use crate::termination::Termination;
+use crate::variant;
+use crate::Game;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.get_type() {
+            PieceType::None => serializer.serialize_none(),
+            _ => serializer.serialize_some(&self.get_icon().to_string())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Piece, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            None => Ok(Piece::from_u8(0x0)),
+            Some(letter) => {
+                let mut chars = letter.chars();
+                match (chars.next().and_then(Piece::from_icon), chars.next()) {
+                    (Some(piece), None) => Ok(piece),
+                    _ => Err(D::Error::custom(format!("invalid piece letter \"{}\"", letter)))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BoardFen {
+    placement: String,
+    castling: String,
+    en_passant: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    chess960: bool
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardFen {
+            placement: self.get_fen_placement(),
+            castling: self.get_fen_castling(),
+            en_passant: self.get_fen_en_passant(),
+            chess960: self.chess960
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let fen = BoardFen::deserialize(deserializer)?;
+        let mut board = Board::from_fen(&fen.placement, &fen.castling, &fen.en_passant)
+            .ok_or_else(|| D::Error::custom("invalid board FEN fields"))?;
+        board.chess960 |= fen.chess960;
+        Ok(board)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameRecord {
+    start_fen: String,
+    moves: Vec<String>,
+    fen: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    chess960: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variant: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    termination: Option<Termination>
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameRecord {
+            start_fen: self.start_fen.clone(),
+            moves: self.history.iter().map(Move::to_uci).collect(),
+            fen: self.to_fen(),
+            chess960: self.is_chess960(),
+            variant: if self.is_variant() {Some(String::from(self.get_variant().get_name()))} else {None},
+            termination: self.termination
+        }.serialize(serializer)
+    }
+}
+
+/// The moves are replayed from the starting position, the current position must match the one that was stored
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Game, D::Error> {
+        let record = GameRecord::deserialize(deserializer)?;
+        let mut game = Game::from_fen(&record.start_fen)
+            .ok_or_else(|| D::Error::custom(format!("invalid starting position \"{}\"", record.start_fen)))?;
+        if record.chess960 {
+            game.set_chess960(true);
+        }
+        if let Some(name) = &record.variant {
+            game.set_variant(variant::from_name(name).ok_or_else(|| D::Error::custom(format!("unknown variant \"{}\"", name)))?);
+        }
+
+        for uci in &record.moves {
+            if game.make_uci_move(uci).is_none() {
+                return Err(D::Error::custom(format!("illegal move \"{}\"", uci)));
+            }
+        }
+
+        if game.to_fen() != record.fen {
+            return Err(D::Error::custom(format!("the moves lead to \"{}\", not \"{}\"", game.to_fen(), record.fen)));
+        }
+
+        if let Some(termination) = record.termination {
+            game.terminate(termination.reason, termination.winner);
+            game.state = termination.get_game_state(game.variant.as_ref());
+        }
+
+        Ok(game)
+    }
+}
@@ -0,0 +1,807 @@
+//! An HTTP server keeping many games in memory, enabled by the `server` feature. Requests and responses are JSON:
+//!
+//! | Method and path | Body | Does |
+//! |---|---|---|
+//! | `POST /games` | `{"fen"?, "chess960"?, "time_control"?}` | Creates a game, from the standard position without a FEN and with a clock for a PGN `TimeControl` such as `"300+2"` |
+//! | `GET /games` | | Lists all games |
+//! | `GET /games/{id}` | | Returns the state of a game |
+//! | `DELETE /games/{id}` | | Removes a game |
+//! | `GET /games/{id}/moves` | | Lists the legal moves in UCI |
+//! | `POST /games/{id}/moves` | `{"move"}` | Plays a move given in SAN or UCI |
+//! | `POST /games/{id}/undo` | | Takes back the last move |
+//! | `POST /games/{id}/resign` | `{"colour"?}` | Resigns for a colour, the side to move by default |
+//! | `POST /games/{id}/draw` | `{"action"?, "colour"?}` | `"offer"` (default), `"accept"`, `"decline"` or `"claim"` a draw, by default for the side to move or the player offered one |
+//! | `GET /games/{id}/fen` | | `{"fen"}` of the current position |
+//! | `GET /games/{id}/pgn` | | `{"pgn"}` of the game |
+//! | `GET /games/{id}/ws` | | Upgrades to a WebSocket, see `GameServer::handle_message` |
+//!
+//! Failures are answered with `{"error": {"code", "message"}}` and the status of the `ServerError`.
+//!
+//! Once a WebSocket client took the seat of a colour, the `POST` routes acting for that colour need the token of the seat
+//! as an `Authorization: Bearer` header or as `"token"` in the body. A move acts for the side to move, an undo for the
+//! player of the last move, a resignation or draw for the seat of the token when no colour is given.
+//!
+//! WebSocket clients get a `snapshot` event with the whole game when they connect, then a `move`, `state` and `clock`
+//! event for every change of the game, over HTTP or WebSocket, a `players` event when a seat is taken and a
+//! `clock` event every second while a clock runs. Errors only go to the client that caused them as an `error` event
+use crate::chess::moves::Move;
+use crate::chess::piece_data::Colour;
+use crate::clock::{Clock, TimeControl};
+use crate::websocket::{self, Message};
+use crate::{Game, GameState};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Request bodies larger than this are refused
+const MAX_BODY: usize = 1 << 16;
+
+/// Reasons a request fails, each with an HTTP status and a code for the JSON error
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerError {
+    /// The request or its JSON body could not be read
+    BadRequest(String),
+    /// No game has the ID, or no route has the path
+    NotFound(String),
+    /// The path exists but not for the method
+    MethodNotAllowed,
+    /// `Game::from_fen` refused the FEN string
+    InvalidFen(String),
+    /// The move cannot be read or is not legal, `Game::play_move` and `Game::san_to_move` refused it
+    IllegalMove(String),
+    /// The game has ended, no more moves or offers can be made
+    GameOver,
+    /// Undo was asked before any move
+    NothingToUndo,
+    /// A draw was accepted or declined without an offer of the opponent
+    NoDrawOffer,
+    /// A draw was claimed but `Game::get_claimable_draw` has no reason for one
+    NoDrawClaim,
+    /// A WebSocket client played, resigned or offered a draw without a seat
+    NotSeated,
+    /// A WebSocket client played a move for the opponent
+    NotYourTurn,
+    /// A WebSocket client asked for a seat another player has
+    SeatTaken,
+    /// A WebSocket client gave a token that does not belong to a seat of the game
+    UnknownToken,
+    /// An HTTP request acted for a colour whose seat is taken without the token of that seat
+    SeatTokenRequired,
+    /// The server could not do what it had to, e.g read random bytes for a seat token
+    Internal(String)
+}
+
+impl ServerError {
+    /// Returns the HTTP status of the error
+    pub fn get_status(&self) -> u16 {
+        match self {
+            ServerError::BadRequest(_) => 400,
+            ServerError::NotFound(_) => 404,
+            ServerError::NotSeated | ServerError::SeatTokenRequired => 403,
+            ServerError::MethodNotAllowed => 405,
+            ServerError::InvalidFen(_) | ServerError::IllegalMove(_) => 422,
+            ServerError::GameOver | ServerError::NothingToUndo | ServerError::NoDrawOffer | ServerError::NoDrawClaim
+            | ServerError::NotYourTurn | ServerError::SeatTaken | ServerError::UnknownToken => 409,
+            ServerError::Internal(_) => 500
+        }
+    }
+
+    /// Returns the code of the error in JSON, e.g `"illegal_move"`
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            ServerError::BadRequest(_) => "bad_request",
+            ServerError::NotFound(_) => "not_found",
+            ServerError::MethodNotAllowed => "method_not_allowed",
+            ServerError::InvalidFen(_) => "invalid_fen",
+            ServerError::IllegalMove(_) => "illegal_move",
+            ServerError::GameOver => "game_over",
+            ServerError::NothingToUndo => "nothing_to_undo",
+            ServerError::NoDrawOffer => "no_draw_offer",
+            ServerError::NoDrawClaim => "no_draw_claim",
+            ServerError::NotSeated => "not_seated",
+            ServerError::NotYourTurn => "not_your_turn",
+            ServerError::SeatTaken => "seat_taken",
+            ServerError::UnknownToken => "unknown_token",
+            ServerError::SeatTokenRequired => "seat_token_required",
+            ServerError::Internal(_) => "internal_error"
+        }
+    }
+
+    /// Returns the error as `{"error": {"code", "message"}}`
+    pub fn to_json(&self) -> Value {
+        json!({"error": {"code": self.get_code(), "message": self.to_string()}})
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerError::BadRequest(reason) => write!(f, "bad request: {}", reason),
+            ServerError::NotFound(what) => write!(f, "{} not found", what),
+            ServerError::MethodNotAllowed => write!(f, "method not allowed"),
+            ServerError::InvalidFen(fen) => write!(f, "invalid FEN \"{}\"", fen),
+            ServerError::IllegalMove(_move) => write!(f, "illegal move \"{}\"", _move),
+            ServerError::GameOver => write!(f, "the game is over"),
+            ServerError::NothingToUndo => write!(f, "there is no move to undo"),
+            ServerError::NoDrawOffer => write!(f, "the opponent has not offered a draw"),
+            ServerError::NoDrawClaim => write!(f, "no draw can be claimed"),
+            ServerError::NotSeated => write!(f, "join the game as a player first"),
+            ServerError::NotYourTurn => write!(f, "it is the opponent's turn"),
+            ServerError::SeatTaken => write!(f, "the seat is taken"),
+            ServerError::UnknownToken => write!(f, "the token does not belong to a seat of the game"),
+            ServerError::SeatTokenRequired => write!(f, "a player is seated for this colour, give the token of the seat"),
+            ServerError::Internal(reason) => write!(f, "internal error: {}", reason)
+        }
+    }
+}
+
+/// ## Request
+/// ### Type: `struct`
+/// An HTTP request as the server sees it.
+/// ### Members:
+/// - `method`: The method, e.g `"GET"`
+/// - `path`: The path without the query string, e.g `"/games/1"`
+/// - `headers`: The headers with lowercase names
+/// - `body`: The body, empty without a `Content-Length`
+#[derive(Clone, Debug, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>
+}
+
+impl Request {
+    /// Creates a request with a JSON body, used to call `GameServer::handle` directly
+    pub fn new(_method: &str, _path: &str, _body: &str) -> Request {
+        Request { method: String::from(_method), path: String::from(_path), headers: Vec::new(), body: _body.as_bytes().to_vec() }
+    }
+
+    /// ## `read_from`
+    /// Reads a request from a connection
+    /// ### Returns
+    /// Returns `Ok(None)` if the connection was closed before a request
+    pub fn read_from<R: BufRead>(_reader: &mut R) -> io::Result<Option<Request>> {
+        let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+
+        let mut line = String::new();
+        if _reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let mut parts = line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => (method.to_string(), target),
+            _ => return Err(invalid("malformed request line"))
+        };
+        let path = target.split('?').next().unwrap_or("/").to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            if _reader.read_line(&mut line)? == 0 {
+                return Err(invalid("connection closed in the headers"));
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        let mut request = Request { method, path, headers, body: Vec::new() };
+        let length = match request.get_header("content-length") {
+            Some(length) => length.parse::<usize>().map_err(|_| invalid("invalid Content-Length"))?,
+            None => 0
+        };
+        if length > MAX_BODY {
+            return Err(invalid("body too large"));
+        }
+        request.body = vec![0; length];
+        _reader.read_exact(&mut request.body)?;
+
+        Ok(Some(request))
+    }
+
+    /// Returns the value of a header, the name is given in lowercase
+    pub fn get_header(&self, _name: &str) -> Option<&str> {
+        self.headers.iter().find(|(name, _)| name == _name).map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the seat token of the request, from an `Authorization: Bearer` header or the `token` of the body
+    fn get_token(&self, _body: &Value) -> Option<String> {
+        let header = self.get_header("authorization").and_then(|value| value.strip_prefix("Bearer ")).map(str::trim);
+        header.or_else(|| _body["token"].as_str()).map(String::from)
+    }
+
+    /// Reads the body as a JSON object, an empty body is an empty object
+    fn json(&self) -> Result<Value, ServerError> {
+        if self.body.iter().all(|byte| byte.is_ascii_whitespace()) {
+            return Ok(json!({}));
+        }
+        match serde_json::from_slice::<Value>(&self.body) {
+            Ok(body) if body.is_object() => Ok(body),
+            Ok(_) => Err(ServerError::BadRequest(String::from("the body must be a JSON object"))),
+            Err(error) => Err(ServerError::BadRequest(error.to_string()))
+        }
+    }
+}
+
+/// ## Response
+/// ### Type: `struct`
+/// An HTTP response with a JSON body.
+/// ### Members:
+/// - `status`: The status code, e.g `200`
+/// - `body`: The JSON body, `Value::Null` for none
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value
+}
+
+impl Response {
+    fn ok(_body: Value) -> Response {
+        Response { status: 200, body: _body }
+    }
+
+    /// Writes the response and asks to close the connection
+    pub fn write_to<W: Write>(&self, mut _writer: W) -> io::Result<()> {
+        let body = if self.body.is_null() {String::new()} else {self.body.to_string()};
+        write!(_writer, "HTTP/1.1 {} {}\r\n", self.status, reason_phrase(self.status))?;
+        write!(_writer, "Content-Type: application/json\r\nContent-Length: {}\r\n", body.len())?;
+        write!(_writer, "Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, DELETE, OPTIONS\r\n")?;
+        write!(_writer, "Access-Control-Allow-Headers: Content-Type, Authorization\r\nConnection: close\r\n\r\n{}", body)?;
+        _writer.flush()
+    }
+}
+
+impl From<ServerError> for Response {
+    fn from(_error: ServerError) -> Response {
+        Response { status: _error.get_status(), body: _error.to_json() }
+    }
+}
+
+/// Returns a new seat token, 16 bytes from the random number generator of the operating system as hexadecimal.
+/// Tokens must not be guessable, so they are not taken from `websocket` which only needs bytes that differ
+fn new_token() -> io::Result<String> {
+    let mut bytes = [0u8; 16];
+    File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn reason_phrase(_status: u16) -> &'static str {
+    match _status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        _ => "Internal Server Error"
+    }
+}
+
+/// A game with the players seated at it and the WebSocket clients following it
+struct Room {
+    id: u64,
+    game: Game,
+    /// The tokens of the players seated as White and Black, they let a player take their seat again after reconnecting
+    seats: [Option<String>; 2],
+    subscribers: Vec<(u64, Sender<Message>)>
+}
+
+impl Room {
+    fn new(_id: u64, _game: Game) -> Room {
+        Room { id: _id, game: _game, seats: [None, None], subscribers: Vec::new() }
+    }
+
+    /// Returns the `players` event, which seats are taken
+    fn players(&self) -> Value {
+        json!({"type": "players", "white": self.seats[0].is_some(), "black": self.seats[1].is_some()})
+    }
+
+    /// Returns the `snapshot` event with everything a client needs to show the game, sent when it connects or asks
+    fn snapshot(&self) -> Value {
+        json!({"type": "snapshot", "game": snapshot(self.id, &self.game), "players": self.players()})
+    }
+
+    /// Sends events to every subscriber, forgetting the ones that have gone
+    fn publish(&mut self, _events: &[Value]) {
+        self.subscribers.retain(|(_, sender)| _events.iter().all(|event| sender.send(Message::Text(event.to_string())).is_ok()));
+    }
+
+    /// Publishes what changed after an update of the game: the `move` that was played if there is one, the new
+    /// `state` and the `clock`
+    fn publish_update(&mut self, _moves_before: usize) {
+        let mut events = Vec::with_capacity(3);
+
+        let history = self.game.get_history();
+        if history.len() == _moves_before + 1 {
+            events.push(json!({
+                "type": "move",
+                "uci": history[history.len() - 1].to_uci(),
+                "san": self.game.to_pgn().moves.last(),
+                "colour": self.game.get_active_colour().opposite()
+            }));
+        }
+        events.push(json!({"type": "state", "game": snapshot(self.id, &self.game)}));
+        if let Some(clock) = clock(&self.game) {
+            events.push(json!({"type": "clock", "clock": clock}));
+        }
+
+        self.publish(&events);
+    }
+}
+
+/// ## GameServer
+/// ### Type: `struct`
+/// Games kept in memory by ID and the HTTP routes to play them. `handle` answers a request, `serve` answers the
+/// connections of a listener, one thread each, and upgrades `/games/{id}/ws` to a WebSocket for live play.
+pub struct GameServer {
+    rooms: Mutex<HashMap<u64, Room>>,
+    next_id: AtomicU64
+}
+
+impl GameServer {
+    /// Creates a server without games, the first game gets ID 1
+    pub fn new() -> GameServer {
+        GameServer { rooms: Mutex::new(HashMap::new()), next_id: AtomicU64::new(1) }
+    }
+
+    /// ## `serve`
+    /// Answers every connection of a listener on its own thread, one request per connection or a WebSocket.
+    /// Every second the clocks of the games followed over WebSockets are published. Only returns if accepting a connection fails
+    pub fn serve(self: Arc<Self>, _listener: TcpListener) -> io::Result<()> {
+        let ticker = Arc::clone(&self);
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(1));
+            ticker.tick();
+        });
+
+        for stream in _listener.incoming() {
+            let stream = stream?;
+            let server = Arc::clone(&self);
+            thread::spawn(move || server.handle_connection(stream));
+        }
+        Ok(())
+    }
+
+    /// Publishes the clocks of running games that have subscribers, and the new state of games lost on time
+    fn tick(&self) {
+        for room in self.rooms.lock().unwrap().values_mut() {
+            if room.subscribers.is_empty() || room.game.get_clock().is_none() || room.game.is_over() {
+                continue;
+            }
+
+            if let GameState::TimeForfeit { .. } = room.game.check_time() {
+                room.publish_update(room.game.get_history().len());
+            }
+            else if let Some(clock) = clock(&room.game) {
+                room.publish(&[json!({"type": "clock", "clock": clock})]);
+            }
+        }
+    }
+
+    fn handle_connection(&self, _stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(_stream.try_clone()?);
+        let response = match Request::read_from(&mut reader) {
+            Ok(Some(request)) => match (request.get_header("upgrade"), request.get_header("sec-websocket-key")) {
+                (Some(upgrade), Some(key)) if upgrade.eq_ignore_ascii_case("websocket") => {
+                    match websocket_game(&request.path) {
+                        Ok(id) => return self.run_websocket(id, key, _stream, reader),
+                        Err(error) => error.into()
+                    }
+                }
+                _ => self.handle(&request)
+            },
+            Ok(None) => return Ok(()),
+            Err(error) => ServerError::BadRequest(error.to_string()).into()
+        };
+        response.write_to(&_stream)
+    }
+
+    /// ## `handle`
+    /// Answers a request, see the module documentation for the routes
+    pub fn handle(&self, _request: &Request) -> Response {
+        self.route(_request).unwrap_or_else(Response::from)
+    }
+
+    fn route(&self, _request: &Request) -> Result<Response, ServerError> {
+        let segments: Vec<&str> = _request.path.split('/').filter(|segment| !segment.is_empty()).collect();
+        let method = _request.method.as_str();
+
+        if method == "OPTIONS" {
+            return Ok(Response { status: 204, body: Value::Null });
+        }
+
+        match segments.as_slice() {
+            ["games"] => match method {
+                "GET" => {
+                    let rooms = self.rooms.lock().unwrap();
+                    let mut ids: Vec<&u64> = rooms.keys().collect();
+                    ids.sort();
+                    Ok(Response::ok(json!({"games": ids.into_iter().map(|id| snapshot(*id, &rooms[id].game)).collect::<Vec<Value>>()})))
+                }
+                "POST" => self.create_game(&_request.json()?),
+                _ => Err(ServerError::MethodNotAllowed)
+            },
+            ["games", id] => {
+                let id = parse_id(id)?;
+                match method {
+                    "GET" => self.with_game(id, |game| Ok(snapshot(id, game))),
+                    "DELETE" => match self.rooms.lock().unwrap().remove(&id) {
+                        Some(_) => Ok(Response { status: 204, body: Value::Null }),
+                        None => Err(ServerError::NotFound(format!("game {}", id)))
+                    },
+                    _ => Err(ServerError::MethodNotAllowed)
+                }
+            }
+            ["games", id, action] => {
+                let id = parse_id(id)?;
+                match (method, *action) {
+                    ("GET", "moves") => self.with_game(id, |game| Ok(json!({"moves": legal_moves(game)}))),
+                    ("GET", "fen") => self.with_game(id, |game| Ok(json!({"fen": game.to_fen()}))),
+                    ("GET", "pgn") => self.with_game(id, |game| Ok(json!({"pgn": game.to_pgn().to_string()}))),
+                    ("GET", "ws") => Err(ServerError::BadRequest(String::from("expected a WebSocket upgrade"))),
+                    ("POST", "moves") => {
+                        let body = _request.json()?;
+                        let text = body["move"].as_str().ok_or_else(|| ServerError::BadRequest(String::from("missing \"move\"")))?;
+                        self.update_game(id, _request.get_token(&body), |game, seats| {
+                            seats.check(game.get_active_colour())?;
+                            play(game, text).map(|_| ())
+                        })
+                    }
+                    ("POST", "undo") => {
+                        let body = _request.json()?;
+                        self.update_game(id, _request.get_token(&body), |game, seats| {
+                            // Only the player who made the last move may take it back
+                            let before = game.take_back().ok_or(ServerError::NothingToUndo)?;
+                            seats.check(before.get_active_colour())?;
+                            *game = before;
+                            Ok(())
+                        })
+                    }
+                    ("POST", "resign") => {
+                        let body = _request.json()?;
+                        self.update_game(id, _request.get_token(&body), |game, seats| {
+                            let colour = parse_colour(&body, seats.get_seat().unwrap_or_else(|| game.get_active_colour()))?;
+                            seats.check(colour)?;
+                            resign(game, colour)
+                        })
+                    }
+                    ("POST", "draw") => {
+                        let body = _request.json()?;
+                        let action = body["action"].as_str().unwrap_or("offer");
+                        self.update_game(id, _request.get_token(&body), |game, seats| {
+                            // Without a colour an offer is answered by the opponent of the player who made it
+                            let colour = match (body["colour"].is_null(), seats.get_seat(), game.get_draw_offer()) {
+                                (true, Some(seat), _) => seat,
+                                (true, None, Some(offered)) if action == "accept" || action == "decline" => offered.opposite(),
+                                _ => parse_colour(&body, game.get_active_colour())?
+                            };
+                            seats.check(colour)?;
+                            draw(game, action, colour)
+                        })
+                    }
+                    (_, "moves") | (_, "fen") | (_, "pgn") | (_, "ws") | (_, "undo") | (_, "resign") | (_, "draw") => Err(ServerError::MethodNotAllowed),
+                    _ => Err(ServerError::NotFound(format!("route {}", _request.path)))
+                }
+            }
+            _ => Err(ServerError::NotFound(format!("route {}", _request.path)))
+        }
+    }
+
+    fn create_game(&self, _body: &Value) -> Result<Response, ServerError> {
+        let mut game = match _body["fen"].as_str() {
+            Some(fen) => Game::from_fen(fen).ok_or_else(|| ServerError::InvalidFen(fen.to_string()))?,
+            None => Game::new()
+        };
+        if _body["chess960"].as_bool() == Some(true) {
+            game.set_chess960(true);
+        }
+        if let Some(tag) = _body["time_control"].as_str() {
+            let control = TimeControl::from_pgn_tag(tag).ok_or_else(|| ServerError::BadRequest(format!("invalid time control \"{}\"", tag)))?;
+            game.set_clock(Clock::new(control));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = snapshot(id, &game);
+        self.rooms.lock().unwrap().insert(id, Room::new(id, game));
+        Ok(Response { status: 201, body })
+    }
+
+    /// Runs a function on the game with an ID while holding the lock, its value is the body of the response
+    fn with_game<F: FnOnce(&mut Game) -> Result<Value, ServerError>>(&self, _id: u64, _action: F) -> Result<Response, ServerError> {
+        let mut rooms = self.rooms.lock().unwrap();
+        let room = rooms.get_mut(&_id).ok_or_else(|| ServerError::NotFound(format!("game {}", _id)))?;
+        _action(&mut room.game).map(Response::ok)
+    }
+
+    /// Changes the game with an ID for a request with a seat token and publishes the change to its WebSocket clients,
+    /// the response is the new state of the game
+    fn update_game<F: FnOnce(&mut Game, &SeatCheck) -> Result<(), ServerError>>(&self, _id: u64, _token: Option<String>, _action: F) -> Result<Response, ServerError> {
+        let mut rooms = self.rooms.lock().unwrap();
+        let room = rooms.get_mut(&_id).ok_or_else(|| ServerError::NotFound(format!("game {}", _id)))?;
+
+        let moves_before = room.game.get_history().len();
+        let seats = SeatCheck { seats: room.seats.clone(), token: _token };
+        _action(&mut room.game, &seats)?;
+        room.publish_update(moves_before);
+        Ok(Response::ok(snapshot(_id, &room.game)))
+    }
+
+    /// ## `run_websocket`
+    /// Plays a game over a WebSocket until the client leaves. The client gets a `snapshot` event first and the events
+    /// of the game as they happen, its messages are answered by `handle_message`. A thread writes the events so a slow
+    /// client does not hold up the game
+    fn run_websocket(&self, _id: u64, _key: &str, _stream: TcpStream, mut _reader: BufReader<TcpStream>) -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel::<Message>();
+        let subscriber = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        // Writing to the socket can block, so the room is only joined under the lock and the handshake is written after it.
+        // Events queue up in the channel until the writing thread starts, the snapshot first
+        let joined = match self.rooms.lock().unwrap().get_mut(&_id) {
+            Some(room) => {
+                room.subscribers.push((subscriber, sender.clone()));
+                sender.send(Message::Text(room.snapshot().to_string())).ok();
+                true
+            }
+            None => false
+        };
+        if !joined {
+            return Response::from(ServerError::NotFound(format!("game {}", _id))).write_to(&_stream);
+        }
+        if let Err(error) = websocket::accept(&_stream, _key) {
+            self.leave(_id, subscriber);
+            return Err(error);
+        }
+
+        let mut writer = _stream.try_clone()?;
+        let writing = thread::spawn(move || {
+            for message in receiver {
+                if websocket::write_message(&mut writer, &message, false).is_err() || message == Message::Close {
+                    break;
+                }
+            }
+        });
+
+        let mut seat: Option<Colour> = None;
+        loop {
+            match websocket::read_message(&mut _reader) {
+                Ok(Message::Text(text)) => {
+                    if let Err(error) = self.handle_message(_id, &text, &mut seat, &sender) {
+                        let event = json!({"type": "error", "code": error.get_code(), "message": error.to_string()});
+                        sender.send(Message::Text(event.to_string())).ok();
+                    }
+                }
+                Ok(Message::Ping(data)) => {
+                    sender.send(Message::Pong(data)).ok();
+                }
+                Ok(Message::Close) | Err(_) => break,
+                Ok(_) => ()
+            }
+        }
+
+        self.leave(_id, subscriber);
+        sender.send(Message::Close).ok();
+        writing.join().ok();
+        _stream.shutdown(Shutdown::Both).ok();
+        Ok(())
+    }
+
+    /// Removes a subscriber from the room of a game, if the room is still there
+    fn leave(&self, _id: u64, _subscriber: u64) {
+        if let Some(room) = self.rooms.lock().unwrap().get_mut(&_id) {
+            room.subscribers.retain(|(id, _)| *id != _subscriber);
+        }
+    }
+
+    /// ## `handle_message`
+    /// Answers a JSON message of a WebSocket client:
+    /// - `{"type": "join", "colour"}` takes a free seat, answered with `{"type": "joined", "colour", "token"}`
+    /// - `{"type": "join", "token"}` takes back the seat of a token after reconnecting
+    /// - `{"type": "move", "move"}` plays a move in SAN or UCI for the seated player
+    /// - `{"type": "resign"}` and `{"type": "draw", "action"?}` like the HTTP routes, for the seated player
+    /// - `{"type": "snapshot"}` sends the `snapshot` event again
+    fn handle_message(&self, _id: u64, _text: &str, _seat: &mut Option<Colour>, _sender: &Sender<Message>) -> Result<(), ServerError> {
+        let message: Value = serde_json::from_str(_text).map_err(|error| ServerError::BadRequest(error.to_string()))?;
+        let mut rooms = self.rooms.lock().unwrap();
+        let room = rooms.get_mut(&_id).ok_or_else(|| ServerError::NotFound(format!("game {}", _id)))?;
+        let reply = |event: Value| _sender.send(Message::Text(event.to_string())).ok();
+
+        let kind = message["type"].as_str().unwrap_or("");
+        if kind == "snapshot" {
+            reply(room.snapshot());
+            return Ok(());
+        }
+        if kind == "join" {
+            let (colour, token) = match message["token"].as_str() {
+                Some(token) => {
+                    let index = room.seats.iter().position(|seat| seat.as_deref() == Some(token)).ok_or(ServerError::UnknownToken)?;
+                    (if index == 0 {Colour::White} else {Colour::Black}, token.to_string())
+                }
+                None => {
+                    let colour = parse_colour(&message, Colour::White)?;
+                    let seat = &mut room.seats[colour as usize];
+                    if seat.is_some() {
+                        return Err(ServerError::SeatTaken);
+                    }
+                    let token = new_token().map_err(|error| ServerError::Internal(format!("cannot create a seat token: {}", error)))?;
+                    *seat = Some(token.clone());
+                    (colour, token)
+                }
+            };
+
+            *_seat = Some(colour);
+            reply(json!({"type": "joined", "colour": colour, "token": token}));
+            reply(room.snapshot());
+            let players = room.players();
+            room.publish(&[players]);
+            return Ok(());
+        }
+
+        let colour = _seat.ok_or(ServerError::NotSeated)?;
+        let moves_before = room.game.get_history().len();
+        match kind {
+            "move" => {
+                if !room.game.is_over() && room.game.get_active_colour() != colour {
+                    return Err(ServerError::NotYourTurn);
+                }
+                let text = message["move"].as_str().ok_or_else(|| ServerError::BadRequest(String::from("missing \"move\"")))?;
+                play(&mut room.game, text)?;
+            }
+            "resign" => resign(&mut room.game, colour)?,
+            "draw" => draw(&mut room.game, message["action"].as_str().unwrap_or("offer"), colour)?,
+            other => return Err(ServerError::BadRequest(format!("unknown message type \"{}\"", other)))
+        }
+
+        room.publish_update(moves_before);
+        Ok(())
+    }
+}
+
+impl Default for GameServer {
+    fn default() -> Self {
+        GameServer::new()
+    }
+}
+
+/// The seats of a game and the token an HTTP request gave, a request may only act for a colour whose seat is free or is its own
+struct SeatCheck {
+    seats: [Option<String>; 2],
+    token: Option<String>
+}
+
+impl SeatCheck {
+    /// Returns the colour of the seat the token belongs to
+    fn get_seat(&self) -> Option<Colour> {
+        let index = self.seats.iter().position(|seat| seat.is_some() && *seat == self.token)?;
+        Some(if index == 0 {Colour::White} else {Colour::Black})
+    }
+
+    /// Refuses to act for a colour whose seat is taken by another token
+    fn check(&self, _colour: Colour) -> Result<(), ServerError> {
+        match &self.seats[_colour as usize] {
+            Some(seat) if Some(seat) != self.token.as_ref() => Err(ServerError::SeatTokenRequired),
+            _ => Ok(())
+        }
+    }
+}
+
+fn parse_id(_segment: &str) -> Result<u64, ServerError> {
+    _segment.parse().map_err(|_| ServerError::NotFound(format!("game {}", _segment)))
+}
+
+/// Returns the game of a `/games/{id}/ws` path
+fn websocket_game(_path: &str) -> Result<u64, ServerError> {
+    let segments: Vec<&str> = _path.split('/').filter(|segment| !segment.is_empty()).collect();
+    match segments.as_slice() {
+        ["games", id, "ws"] => parse_id(id),
+        _ => Err(ServerError::NotFound(format!("route {}", _path)))
+    }
+}
+
+/// Reads the `colour` of a message or body, a default colour when it is missing
+fn parse_colour(_body: &Value, _default: Colour) -> Result<Colour, ServerError> {
+    match _body["colour"].as_str() {
+        None => Ok(_default),
+        Some("white") => Ok(Colour::White),
+        Some("black") => Ok(Colour::Black),
+        Some(other) => Err(ServerError::BadRequest(format!("unknown colour \"{}\"", other)))
+    }
+}
+
+/// Plays a move given in UCI or SAN
+fn play(_game: &mut Game, _text: &str) -> Result<GameState, ServerError> {
+    if _game.is_over() {
+        return Err(ServerError::GameOver);
+    }
+
+    let parsed = Move::from_uci(_text).or_else(|| _game.san_to_move(_text));
+    match parsed.and_then(|_move| _game.play_move(_move)) {
+        Some(GameState::TimeForfeit { .. }) => Err(ServerError::GameOver),
+        Some(state) => Ok(state),
+        None => Err(ServerError::IllegalMove(_text.to_string()))
+    }
+}
+
+fn resign(_game: &mut Game, _colour: Colour) -> Result<(), ServerError> {
+    if _game.resign(_colour).is_none() {
+        return Err(ServerError::GameOver);
+    }
+    Ok(())
+}
+
+/// Offers, accepts, declines or claims a draw for a colour
+fn draw(_game: &mut Game, _action: &str, _colour: Colour) -> Result<(), ServerError> {
+    if _game.is_over() {
+        return Err(ServerError::GameOver);
+    }
+
+    match _action {
+        "offer" if _game.offer_draw(_colour) => Ok(()),
+        "offer" => Err(ServerError::GameOver),
+        "accept" => _game.accept_draw(_colour).map(|_| ()).ok_or(ServerError::NoDrawOffer),
+        "decline" if _game.decline_draw(_colour) => Ok(()),
+        "decline" => Err(ServerError::NoDrawOffer),
+        "claim" => _game.claim_draw().map(|_| ()).ok_or(ServerError::NoDrawClaim),
+        other => Err(ServerError::BadRequest(format!("unknown draw action \"{}\"", other)))
+    }
+}
+
+/// The time left of both players in milliseconds and the colour whose clock runs, `None` for games without a clock
+fn clock(_game: &Game) -> Option<Value> {
+    let clock = _game.get_clock()?;
+    Some(json!({
+        "white": clock.get_time_left(Colour::White).as_millis() as u64,
+        "black": clock.get_time_left(Colour::Black).as_millis() as u64,
+        "running": clock.get_running()
+    }))
+}
+
+/// The legal moves of the side to move in UCI, none once the game has ended
+fn legal_moves(_game: &Game) -> Vec<String> {
+    if _game.is_over() {
+        return Vec::new();
+    }
+    _game.get_all_legal_moves().iter().map(Move::to_uci).collect()
+}
+
+/// ## `snapshot`
+/// Describes a game as JSON: its ID, positions, side to move, state, result, how it ended, the moves played in UCI
+/// and SAN, a standing draw offer and the clock
+fn snapshot(_id: u64, _game: &Game) -> Value {
+    let state = match _game.get_game_state() {
+        GameState::InProgress => "in_progress",
+        GameState::Check => "check",
+        GameState::GameOver => "game_over",
+        GameState::TimeForfeit { .. } => "time_forfeit",
+        GameState::KingOfTheHill { .. } => "king_of_the_hill"
+    };
+
+    json!({
+        "id": _id,
+        "fen": _game.to_fen(),
+        "start_fen": _game.get_start_fen(),
+        "turn": _game.get_active_colour(),
+        "state": state,
+        "result": _game.get_result(),
+        "termination": _game.get_termination(),
+        "moves": _game.get_history().iter().map(Move::to_uci).collect::<Vec<String>>(),
+        "san": _game.to_pgn().moves,
+        "draw_offer": _game.get_draw_offer(),
+        "clock": clock(_game)
+    })
+}
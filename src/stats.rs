@@ -0,0 +1,273 @@
+//! Statistics of match results: the Elo difference with a confidence interval, the likelihood of superiority and a
+//! sequential probability ratio test deciding between two Elo hypotheses as the games come in.
+//!
+//! Results are counted either per game (`Trinomial`, wins, draws and losses) or per pair of games played from the same
+//! opening with the colours reversed (`Pentanomial`), which accounts for the correlation between the two games and gives
+//! tighter intervals. Both are normal approximations of the score, Elo is logistic: a score of `s` is `-400 log10(1/s - 1)`
+use std::fmt;
+
+/// ## Results
+/// ### Type: `trait`
+/// Results of a match from the point of view of the first player
+pub trait Results {
+    /// Returns the scores of the samples between 0 and 1, a game or a pair of games, with how often each occurred
+    fn distribution(&self) -> Vec<(f64, u32)>;
+
+    /// ## `estimate`
+    /// Estimates the Elo difference of the first player
+    /// ### Parameters
+    /// - `_confidence: f64`: The probability the interval holds the true difference, e.g `0.95`
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `EloEstimate`, `None` without results. A player who scored everything or nothing
+    /// has an infinite difference
+    fn estimate(&self, _confidence: f64) -> Option<EloEstimate> {
+        let distribution: Vec<(f64, f64)> = self.distribution().into_iter().map(|(score, count)| (score, count as f64)).collect();
+        let (samples, mean, variance) = moments(&distribution)?;
+        let error = (variance / samples).sqrt();
+        let z = normal_quantile(0.5 + _confidence / 2.0);
+
+        let los = if error > 0.0 {
+            normal_cdf((mean - 0.5) / error)
+        }
+        else {
+            match mean.partial_cmp(&0.5) {
+                Some(std::cmp::Ordering::Greater) => 1.0,
+                Some(std::cmp::Ordering::Less) => 0.0,
+                _ => 0.5
+            }
+        };
+
+        Some(EloEstimate {
+            elo: score_to_elo(mean),
+            lower: score_to_elo(mean - z * error),
+            upper: score_to_elo(mean + z * error),
+            los
+        })
+    }
+}
+
+/// ## Trinomial
+/// ### Type: `struct`
+/// Results counted per game.
+/// ### Members:
+/// - `wins`, `draws`, `losses`: The games won, drawn and lost by the first player
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Trinomial {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+impl Trinomial {
+    pub fn new(_wins: u32, _draws: u32, _losses: u32) -> Trinomial {
+        Trinomial { wins: _wins, draws: _draws, losses: _losses }
+    }
+
+    /// Returns the number of games
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Counts a game from its score for the first player, 1 for a win, 0.5 for a draw and 0 for a loss
+    pub fn add(&mut self, _score: f64) {
+        match _score {
+            score if score > 0.75 => self.wins += 1,
+            score if score < 0.25 => self.losses += 1,
+            _ => self.draws += 1
+        }
+    }
+}
+
+impl Results for Trinomial {
+    fn distribution(&self) -> Vec<(f64, u32)> {
+        vec![(0.0, self.losses), (0.5, self.draws), (1.0, self.wins)]
+    }
+}
+
+/// ## Pentanomial
+/// ### Type: `struct`
+/// Results counted per pair of games, the same opening played once with each colour.
+/// ### Members:
+/// - `pairs`: How many pairs the first player scored 0, 0.5, 1, 1.5 and 2 points in
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Pentanomial {
+    pub pairs: [u32; 5]
+}
+
+impl Pentanomial {
+    pub fn new(_pairs: [u32; 5]) -> Pentanomial {
+        Pentanomial { pairs: _pairs }
+    }
+
+    /// Counts a pair from the points the first player scored in it, between 0 and 2
+    pub fn add(&mut self, _points: f64) {
+        let index = (_points * 2.0).round().clamp(0.0, 4.0) as usize;
+        self.pairs[index] += 1;
+    }
+}
+
+impl Results for Pentanomial {
+    fn distribution(&self) -> Vec<(f64, u32)> {
+        self.pairs.iter().enumerate().map(|(index, count)| (index as f64 / 4.0, *count)).collect()
+    }
+}
+
+/// ## EloEstimate
+/// ### Type: `struct`
+/// An Elo difference estimated from results.
+/// ### Members:
+/// - `elo`: The most likely difference
+/// - `lower`, `upper`: The bounds of the confidence interval
+/// - `los`: The likelihood of superiority, the probability that the first player is the stronger
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub los: f64
+}
+
+impl EloEstimate {
+    /// Returns half the width of the confidence interval, the `±` of the estimate
+    pub fn get_error(&self) -> f64 {
+        (self.upper - self.lower) / 2.0
+    }
+}
+
+/// Writes the estimate like `"12.3 +/- 20.1, LOS: 88.2 %"`
+impl fmt::Display for EloEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} +/- {:.1}, LOS: {:.1} %", self.elo, self.get_error(), self.los * 100.0)
+    }
+}
+
+/// What a sequential probability ratio test concluded
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SprtStatus {
+    /// More games are needed
+    Continue,
+    /// The difference is `elo0` or less, the change is rejected
+    AcceptH0,
+    /// The difference is `elo1` or more, the change is accepted
+    AcceptH1
+}
+
+/// ## Sprt
+/// ### Type: `struct`
+/// A sequential probability ratio test of `H0: elo = elo0` against `H1: elo = elo1`, run after every game or pair.
+/// The log-likelihood ratio is the generalised SPRT approximation used by Fishtest, which works for any distribution
+/// of results through their mean and variance.
+/// ### Members:
+/// - `elo0`, `elo1`: The Elo differences of the hypotheses, `elo0 < elo1`
+/// - `alpha`: The probability of accepting H1 when H0 is true
+/// - `beta`: The probability of accepting H0 when H1 is true
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64
+}
+
+impl Sprt {
+    pub fn new(_elo0: f64, _elo1: f64, _alpha: f64, _beta: f64) -> Sprt {
+        Sprt { elo0: _elo0, elo1: _elo1, alpha: _alpha, beta: _beta }
+    }
+
+    /// Returns the log-likelihood ratios below which H0 and above which H1 is accepted
+    pub fn get_bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// ## `llr`
+    /// Returns the log-likelihood ratio of H1 against H0 given results, 0 with fewer than two samples.
+    /// Like Fishtest, every score is counted a thousandth of a time more so that results that all have the
+    /// same score still have a variance
+    pub fn llr<R: Results>(&self, _results: &R) -> f64 {
+        let distribution: Vec<(f64, f64)> = _results.distribution().into_iter().map(|(score, count)| (score, count as f64 + 1e-3)).collect();
+        let (samples, mean, variance) = match moments(&distribution) {
+            Some(moments) => moments,
+            None => return 0.0
+        };
+        if samples < 2.0 {
+            return 0.0;
+        }
+
+        let score0 = elo_to_score(self.elo0);
+        let score1 = elo_to_score(self.elo1);
+        samples * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance)
+    }
+
+    /// Returns whether the results accept a hypothesis or more games are needed
+    pub fn get_status<R: Results>(&self, _results: &R) -> SprtStatus {
+        let llr = self.llr(_results);
+        let (lower, upper) = self.get_bounds();
+
+        if llr >= upper {
+            SprtStatus::AcceptH1
+        }
+        else if llr <= lower {
+            SprtStatus::AcceptH0
+        }
+        else {
+            SprtStatus::Continue
+        }
+    }
+}
+
+/// The usual test of a small improvement, `elo0 = 0`, `elo1 = 5`, `alpha = beta = 0.05`
+impl Default for Sprt {
+    fn default() -> Self {
+        Sprt::new(0.0, 5.0, 0.05, 0.05)
+    }
+}
+
+/// Returns the expected score of a player rated `_elo` above the opponent
+pub fn elo_to_score(_elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-_elo / 400.0))
+}
+
+/// Returns the Elo difference that gives an expected score, infinite for a score of 0 or 1
+pub fn score_to_elo(_score: f64) -> f64 {
+    match _score {
+        score if score <= 0.0 => f64::NEG_INFINITY,
+        score if score >= 1.0 => f64::INFINITY,
+        score => -400.0 * (1.0 / score - 1.0).log10()
+    }
+}
+
+/// Returns the number of samples, the mean score and the variance of a score, `None` without samples
+fn moments(_distribution: &[(f64, f64)]) -> Option<(f64, f64, f64)> {
+    let samples: f64 = _distribution.iter().map(|(_, count)| count).sum();
+    if samples == 0.0 {
+        return None;
+    }
+
+    let mean = _distribution.iter().map(|(score, count)| score * count).sum::<f64>() / samples;
+    let variance = _distribution.iter().map(|(score, count)| (score - mean).powi(2) * count).sum::<f64>() / samples;
+    Some((samples, mean, variance))
+}
+
+/// The cumulative distribution function of the standard normal distribution
+fn normal_cdf(_x: f64) -> f64 {
+    0.5 * (1.0 + erf(_x / std::f64::consts::SQRT_2))
+}
+
+/// The quantile function of the standard normal distribution, found by bisection on `normal_cdf`
+fn normal_quantile(_p: f64) -> f64 {
+    let (mut low, mut high) = (-10.0, 10.0);
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+        if normal_cdf(middle) < _p {low = middle} else {high = middle}
+    }
+    (low + high) / 2.0
+}
+
+/// The error function, with the approximation 7.1.26 of Abramowitz and Stegun (error below 1.5e-7)
+fn erf(_x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * _x.abs());
+    let polynomial = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let value = 1.0 - polynomial * (-_x * _x).exp();
+    if _x < 0.0 {-value} else {value}
+}
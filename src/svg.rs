@@ -0,0 +1,314 @@
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece_data::{Colour, PieceType};
+use crate::game_tree::{Arrow, HighlightColour, SquareHighlight};
+use crate::{Game, GameState};
+use std::fmt::Write;
+
+/// Width of a square in the coordinates of the drawing, the pieces are drawn for this size
+const SQUARE: f64 = 45.0;
+
+/// Room left and below the board for the coordinates
+const MARGIN: f64 = 18.0;
+
+const LIGHT: &str = "#f0d9b5";
+const DARK: &str = "#b58863";
+const LAST_MOVE: &str = "#cdd26a";
+
+/// ## SvgRenderer
+/// ### Type: `struct`
+/// Options for drawing a board as a self-contained SVG image, the pieces are embedded as vector shapes.
+/// ### Members:
+/// - `size`: The width and height of the image in pixels
+/// - `coordinates`: Whether ranks and files are labelled
+/// - `orientation`: The colour at the bottom of the board
+/// - `squares`: Squares to highlight
+/// - `arrows`: Arrows to draw
+/// - `last_move`: A move whose squares are highlighted
+/// - `check`: The square of a king in check, highlighted in red
+#[derive(Clone, Debug)]
+pub struct SvgRenderer {
+    pub size: u32,
+    pub coordinates: bool,
+    pub orientation: Colour,
+    pub squares: Vec<SquareHighlight>,
+    pub arrows: Vec<Arrow>,
+    pub last_move: Option<Move>,
+    pub check: Option<(i8, i8)>
+}
+
+impl SvgRenderer {
+    /// Creates the default renderer, 400 pixels from White's side with coordinates and nothing highlighted
+    pub fn new() -> SvgRenderer {
+        SvgRenderer {
+            size: 400,
+            coordinates: true,
+            orientation: Colour::White,
+            squares: Vec::new(),
+            arrows: Vec::new(),
+            last_move: None,
+            check: None
+        }
+    }
+
+    /// ## `render`
+    /// Draws a board with the squares, highlights, pieces, arrows and coordinates in that order
+    /// ### Returns
+    /// Returns the SVG document as a `String`
+    pub fn render(&self, _board: &Board) -> String {
+        let margin = if self.coordinates {MARGIN} else {0.0};
+        let extent = 8.0 * SQUARE + margin;
+        let mut svg = String::with_capacity(16384);
+
+        // Writing to a String cannot fail, the results of write! are ignored
+        let _ = write!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            self.size, self.size, extent, extent);
+        svg.push_str("<defs>");
+        for colour in [Colour::White, Colour::Black] {
+            for piece_type in [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King] {
+                svg.push_str(&piece_definition(colour, piece_type));
+            }
+        }
+        svg.push_str(r##"<radialGradient id="check"><stop offset="0%" stop-color="#ff0000"/><stop offset="25%" stop-color="#e70000"/><stop offset="89%" stop-color="#a90000" stop-opacity="0"/><stop offset="100%" stop-color="#9e0000" stop-opacity="0"/></radialGradient>"##);
+        svg.push_str("</defs>");
+
+        let _ = write!(svg, r#"<g transform="translate({} 0)">"#, margin);
+
+        for row in 0..8 {
+            for col in 0..8 {
+                let (x, y) = self.square_position((col, row));
+                let fill = if (col + row) % 2 == 0 {LIGHT} else {DARK};
+                let _ = write!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#, x, y, SQUARE, SQUARE, fill);
+            }
+        }
+
+        if let Some(last_move) = self.last_move {
+            for square in [last_move.from, last_move.to] {
+                let (x, y) = self.square_position(square);
+                let _ = write!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.8"/>"#, x, y, SQUARE, SQUARE, LAST_MOVE);
+            }
+        }
+        for highlight in &self.squares {
+            let (x, y) = self.square_position(highlight.square);
+            let _ = write!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.5"/>"#, x, y, SQUARE, SQUARE, highlight_colour(highlight.colour));
+        }
+        if let Some(check) = self.check {
+            let (x, y) = self.square_position(check);
+            let _ = write!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#check)"/>"#, x, y, SQUARE, SQUARE);
+        }
+
+        for row in 0..8 {
+            for col in 0..8 {
+                if _board.is_empty((col, row)) {
+                    continue;
+                }
+                let (x, y) = self.square_position((col, row));
+                let (colour, piece_type) = _board.piece_at((col, row)).get_piece_data();
+                let _ = write!(svg, r##"<use xlink:href="#{}" x="{}" y="{}"/>"##, piece_id(colour, piece_type), x, y);
+            }
+        }
+
+        for arrow in &self.arrows {
+            svg.push_str(&self.arrow(arrow));
+        }
+
+        svg.push_str("</g>");
+
+        if self.coordinates {
+            for index in 0..8 {
+                let flipped = self.orientation == Colour::Black;
+                let rank = if flipped {index + 1} else {8 - index};
+                let file = (if flipped {b'h' - index as u8} else {b'a' + index as u8}) as char;
+                let centre = (index as f64 + 0.5) * SQUARE;
+
+                let _ = write!(svg, r##"<text x="{}" y="{}" font-family="sans-serif" font-size="12" fill="#555" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                    MARGIN / 2.0, centre, rank);
+                let _ = write!(svg, r##"<text x="{}" y="{}" font-family="sans-serif" font-size="12" fill="#555" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                    margin + centre, 8.0 * SQUARE + MARGIN / 2.0, file);
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// ## `render_game`
+    /// Draws the board of a game with its last move and a king in check highlighted, unless `last_move` or `check` are already set
+    pub fn render_game(&self, _game: &Game) -> String {
+        let mut renderer = self.clone();
+
+        if renderer.last_move.is_none() {
+            renderer.last_move = _game.get_history().last().copied();
+        }
+        if renderer.check.is_none() && _game.get_game_state() == GameState::Check {
+            renderer.check = Some(_game.get_board().get_king(_game.get_active_colour()));
+        }
+
+        renderer.render(_game.get_board())
+    }
+
+    /// Returns the top left corner of a square in the drawing
+    fn square_position(&self, _square: (i8, i8)) -> (f64, f64) {
+        let (col, row) = if self.orientation == Colour::Black {(7 - _square.0, 7 - _square.1)} else {_square};
+        (col as f64 * SQUARE, row as f64 * SQUARE)
+    }
+
+    /// Draws an arrow from the centre of a square to the centre of another, the head ends at the target's centre
+    fn arrow(&self, _arrow: &Arrow) -> String {
+        let centre = |square| {
+            let (x, y) = self.square_position(square);
+            (x + SQUARE / 2.0, y + SQUARE / 2.0)
+        };
+        let (from, to) = (centre(_arrow.from), centre(_arrow.to));
+
+        let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        if length == 0.0 {
+            return String::new();
+        }
+        let direction = ((to.0 - from.0) / length, (to.1 - from.1) / length);
+        let normal = (-direction.1, direction.0);
+
+        let head_length = SQUARE * 0.45;
+        let head_width = SQUARE * 0.3;
+        let base = (to.0 - direction.0 * head_length, to.1 - direction.1 * head_length);
+        let colour = highlight_colour(_arrow.colour);
+
+        format!(r#"<g fill="{colour}" stroke="{colour}" opacity="0.8"><line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke-width="{:.2}" stroke-linecap="round"/><polygon points="{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}" stroke="none"/></g>"#,
+            from.0, from.1, base.0, base.1, SQUARE * 0.2,
+            to.0, to.1,
+            base.0 + normal.0 * head_width, base.1 + normal.1 * head_width,
+            base.0 - normal.0 * head_width, base.1 - normal.1 * head_width,
+            colour = colour)
+    }
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        SvgRenderer::new()
+    }
+}
+
+impl Board {
+    /// Returns the board as an SVG image drawn with the default `SvgRenderer`
+    pub fn to_svg(&self) -> String {
+        SvgRenderer::new().render(self)
+    }
+}
+
+impl Game {
+    /// Returns the board as an SVG image with the last move and a king in check highlighted, see `SvgRenderer::render_game`
+    pub fn to_svg(&self) -> String {
+        SvgRenderer::new().render_game(self)
+    }
+}
+
+fn highlight_colour(_colour: HighlightColour) -> &'static str {
+    match _colour {
+        HighlightColour::Green => "#15781b",
+        HighlightColour::Red => "#882020",
+        HighlightColour::Yellow => "#e68f00",
+        HighlightColour::Blue => "#003088"
+    }
+}
+
+/// Returns the id of the definition of a piece, e.g `"wk"` for the white king
+fn piece_id(_colour: Colour, _type: PieceType) -> String {
+    let colour = if _colour == Colour::White {'w'} else {'b'};
+    let piece = match _type {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        _ => 'k'
+    };
+    format!("{}{}", colour, piece)
+}
+
+/// A part of a piece drawn in a 45 by 45 square, shared by the SVG and raster renderers
+pub(crate) enum Shape {
+    /// A path in the piece's colour with a black outline, made of absolute `M`, `L`, `C` and `Z` commands
+    Path(&'static str),
+    /// A circle in the piece's colour with a black outline, centre and radius
+    Circle(f64, f64, f64),
+    /// An ellipse in the piece's colour with a black outline, centre and radii
+    Ellipse(f64, f64, f64, f64),
+    /// A line in the detail colour, black on white pieces and white on black ones
+    Detail(&'static str),
+    /// A dot in the detail colour, centre and radius
+    Dot(f64, f64, f64),
+    /// A black line of the given width
+    Outline(&'static str, f64)
+}
+
+/// Width of the outlines and details of the pieces
+pub(crate) const PIECE_STROKE: f64 = 1.5;
+
+/// Returns the shapes of a piece, drawn in order
+pub(crate) fn piece_shapes(_type: PieceType) -> &'static [Shape] {
+    use Shape::*;
+
+    match _type {
+        PieceType::Pawn => &[
+            Circle(22.5, 14.0, 5.5),
+            Path("M 16,34 L 18.5,22 L 26.5,22 L 29,34 Z"),
+            Ellipse(22.5, 21.5, 6.0, 2.0),
+            Path("M 12,33 L 33,33 L 33,38 L 12,38 Z")
+        ],
+        PieceType::Knight => &[
+            Path("M 13,37 L 33,37 L 33,30 C 33,21 30,14 24,10 L 22,5 L 19.5,9.5 L 16.5,11 L 9.5,20 L 11,25 L 15.5,23.5 L 19.5,20.5 L 20.5,24 L 13,31 Z"),
+            Dot(17.0, 15.0, 1.3),
+            Detail("M 24.5,13 C 28.5,17 30,23 30,30")
+        ],
+        PieceType::Bishop => &[
+            Path("M 10,34 L 35,34 L 35,38 L 10,38 Z"),
+            Path("M 15,34 C 15,29 17,26.5 18,24.5 L 27,24.5 C 28,26.5 30,29 30,34 Z"),
+            Path("M 17.5,24.5 C 14,19 17,13 22.5,9 C 28,13 31,19 27.5,24.5 Z"),
+            Circle(22.5, 6.5, 2.5),
+            Detail("M 22.5,13 L 22.5,20 M 19.5,16.5 L 25.5,16.5")
+        ],
+        PieceType::Rook => &[
+            Path("M 10,34 L 35,34 L 35,38 L 10,38 Z"),
+            Path("M 14,34 L 15,17 L 30,17 L 31,34 Z"),
+            Path("M 11,9 L 11,17 L 34,17 L 34,9 L 29,9 L 29,12 L 25,12 L 25,9 L 20,9 L 20,12 L 16,12 L 16,9 Z"),
+            Detail("M 15,20 L 30,20 M 14.5,31 L 30.5,31")
+        ],
+        PieceType::Queen => &[
+            Path("M 10,34 L 35,34 L 35,38 L 10,38 Z"),
+            Path("M 11,34 L 9,14 L 13,25 L 15.5,11 L 19,24 L 22.5,10 L 26,24 L 29.5,11 L 32,25 L 36,14 L 34,34 Z"),
+            Circle(9.0, 12.5, 2.0),
+            Circle(15.5, 9.5, 2.0),
+            Circle(22.5, 8.5, 2.0),
+            Circle(29.5, 9.5, 2.0),
+            Circle(36.0, 12.5, 2.0)
+        ],
+        _ => &[
+            Path("M 10,34 L 35,34 L 35,38 L 10,38 Z"),
+            Path("M 12,34 C 8.5,27 10.5,20 17,19 C 19.5,19 21.5,21 22.5,23.5 C 23.5,21 25.5,19 28,19 C 34.5,20 36.5,27 33,34 Z"),
+            Outline("M 22.5,17 L 22.5,23.5", PIECE_STROKE),
+            Outline("M 22.5,5 L 22.5,17 M 18.5,9.5 L 26.5,9.5", 2.5),
+            Detail("M 13,30 C 19,28 26,28 32,30")
+        ]
+    }
+}
+
+/// Returns the definition of a piece drawn in a 45 by 45 square. White pieces are white with black outlines,
+/// black pieces are black with their inner details in white
+fn piece_definition(_colour: Colour, _type: PieceType) -> String {
+    let (fill, detail) = if _colour == Colour::White {("#fff", "#000")} else {("#000", "#fff")};
+
+    let mut shapes = String::with_capacity(512);
+    for shape in piece_shapes(_type) {
+        let _ = match shape {
+            Shape::Path(path) => write!(shapes, r#"<path d="{}"/>"#, path),
+            Shape::Circle(x, y, radius) => write!(shapes, r#"<circle cx="{}" cy="{}" r="{}"/>"#, x, y, radius),
+            Shape::Ellipse(x, y, radius_x, radius_y) => write!(shapes, r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"/>"#, x, y, radius_x, radius_y),
+            Shape::Detail(path) => write!(shapes, r#"<path d="{}" fill="none" stroke="{}"/>"#, path, detail),
+            Shape::Dot(x, y, radius) => write!(shapes, r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="none"/>"#, x, y, radius, detail),
+            Shape::Outline(path, width) => write!(shapes, r#"<path d="{}" fill="none" stroke-width="{}"/>"#, path, width)
+        };
+    }
+
+    format!(r##"<g id="{}" fill="{}" stroke="#000" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round">{}</g>"##,
+        piece_id(_colour, _type), fill, PIECE_STROKE, shapes)
+}
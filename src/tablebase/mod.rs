@@ -0,0 +1,361 @@
+mod table;
+
+use crate::chess::board::Board;
+use crate::chess::piece_data::{Colour, PieceType};
+use crate::Game;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use table::{Kind, Table};
+
+/// First bytes of Syzygy WDL (`.rtbw`) and DTZ (`.rtbz`) files
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+/// Pieces in the order Syzygy uses in table names
+const NAME_ORDER: [(PieceType, char); 6] = [
+    (PieceType::King, 'K'),
+    (PieceType::Queen, 'Q'),
+    (PieceType::Rook, 'R'),
+    (PieceType::Bishop, 'B'),
+    (PieceType::Knight, 'N'),
+    (PieceType::Pawn, 'P')
+];
+
+/// Win/draw/loss from the point of view of the side to move. Cursed wins and blessed losses are wins and losses
+/// that the fifty move rule turns into draws
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win
+}
+
+/// Reasons a position cannot be probed
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProbeError {
+    /// Tablebases do not hold positions where castling is still possible
+    CastlingRights,
+    /// The position has more pieces than the largest table found
+    TooManyPieces,
+    /// No table with the material of the position was found, holds the table name, e.g `"KQvKR"`
+    MissingTable(String),
+    /// The table could not be read or is not a valid Syzygy table
+    InvalidTable(PathBuf)
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProbeError::CastlingRights => write!(f, "positions with castling rights are not in tablebases"),
+            ProbeError::TooManyPieces => write!(f, "too many pieces for the available tables"),
+            ProbeError::MissingTable(name) => write!(f, "missing table {}", name),
+            ProbeError::InvalidTable(path) => write!(f, "{} is not a valid table", path.display())
+        }
+    }
+}
+
+/// ## Tablebase
+/// ### Type: `struct`
+/// Syzygy endgame tablebases read from local directories.
+/// ### Members:
+/// - `wdl_tables`: Paths of the WDL tables found, keyed by table name such as `"KQvK"`
+/// - `dtz_tables`: Paths of the DTZ tables found, keyed by table name
+/// - `max_pieces`: The number of pieces of the largest table found
+/// - `loaded`: The tables read so far, a table is only read the first time a position needs it
+pub struct Tablebase {
+    wdl_tables: HashMap<String, PathBuf>,
+    dtz_tables: HashMap<String, PathBuf>,
+    max_pieces: usize,
+    loaded: Mutex<HashMap<PathBuf, Arc<Table>>>
+}
+
+impl Tablebase {
+    /// Creates a tablebase without any tables
+    pub fn new() -> Tablebase {
+        Tablebase {
+            wdl_tables: HashMap::new(),
+            dtz_tables: HashMap::new(),
+            max_pieces: 0,
+            loaded: Mutex::new(HashMap::new())
+        }
+    }
+
+    /// ## `add_directory`
+    /// Adds every Syzygy table in a directory. Files whose first bytes are not the Syzygy magic are skipped.
+    /// ### Returns
+    /// Returns `io::Result<>` wrapping the number of tables added
+    pub fn add_directory<P: AsRef<Path>>(&mut self, _path: P) -> io::Result<usize> {
+        let mut added = 0;
+
+        for entry in fs::read_dir(_path)? {
+            let path = entry?.path();
+
+            let (name, magic, tables) = match (path.file_stem().and_then(|stem| stem.to_str()), path.extension().and_then(|extension| extension.to_str())) {
+                (Some(name), Some("rtbw")) => (String::from(name), WDL_MAGIC, &mut self.wdl_tables),
+                (Some(name), Some("rtbz")) => (String::from(name), DTZ_MAGIC, &mut self.dtz_tables),
+                _ => continue
+            };
+
+            let mut header = [0; 4];
+            if fs::File::open(&path).and_then(|mut file| file.read_exact(&mut header)).is_err() || header != magic {
+                continue;
+            }
+
+            self.max_pieces = self.max_pieces.max(name.chars().filter(|piece| *piece != 'v').count());
+            tables.insert(name, path);
+            added += 1;
+        }
+
+        Ok(added)
+    }
+
+    /// Returns the number of pieces, kings included, of the largest table found
+    pub fn get_max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    /// ## `get_table_name`
+    /// Returns the Syzygy name of the material on the board with the stronger side first, e.g `"KRPvKR"`
+    pub fn get_table_name(_board: &Board) -> String {
+        let side = |colour: Colour| -> String {
+            NAME_ORDER.iter()
+                .flat_map(|(piece_type, letter)| {
+                    let count = _board.board.iter().flatten().filter(|piece| piece.get_piece_data() == (colour, *piece_type)).count();
+                    std::iter::repeat_n(*letter, count)
+                })
+                .collect()
+        };
+
+        let (white, black) = (side(Colour::White), side(Colour::Black));
+
+        // The stronger side has more pieces, or the more valuable first differing piece
+        let strength = |name: &str| -> (usize, Vec<usize>) {
+            (name.len(), name.chars().map(|letter| 6 - NAME_ORDER.iter().position(|(_, order)| *order == letter).unwrap()).collect())
+        };
+
+        if strength(&white) >= strength(&black) {
+            format!("{}v{}", white, black)
+        }
+        else {
+            format!("{}v{}", black, white)
+        }
+    }
+
+    /// ## `probe_wdl`
+    /// Returns the win/draw/loss value of a position for the side to move.
+    /// Material that can never be won, two bare kings or a king and one minor piece against a king, is a draw without any table
+    /// ### Parameters
+    /// - `_board: &Board`: The position to probe
+    /// - `_turn: Colour`: The side to move
+    pub fn probe_wdl(&self, _board: &Board, _turn: Colour) -> Result<Wdl, ProbeError> {
+        let game = self.check_probe(_board, _turn)?;
+        let (wdl, _) = self.search(&game, false)?;
+
+        Ok(Tablebase::to_wdl(wdl))
+    }
+
+    /// ## `probe_dtz`
+    /// Returns the distance to zeroing, the number of halfmoves to the next capture or pawn move with best play,
+    /// positive when the side to move wins and negative when it loses. Drawn positions are 0, cursed wins and
+    /// blessed losses are 100 further away than the fifty move rule allows
+    /// ### Parameters
+    /// - `_board: &Board`: The position to probe
+    /// - `_turn: Colour`: The side to move
+    pub fn probe_dtz(&self, _board: &Board, _turn: Colour) -> Result<i32, ProbeError> {
+        let game = self.check_probe(_board, _turn)?;
+
+        self.get_dtz(&game)
+    }
+
+    /// Checks that a position can be in a table and returns it as a game to search from
+    fn check_probe(&self, _board: &Board, _turn: Colour) -> Result<Game, ProbeError> {
+        if _board.get_fen_castling() != "-" {
+            return Err(ProbeError::CastlingRights);
+        }
+
+        let name = Tablebase::get_table_name(_board);
+        let pieces = name.len() - 1;
+
+        // The trivially drawn material needs no table
+        if pieces > self.max_pieces.max(3) {
+            return Err(ProbeError::TooManyPieces);
+        }
+
+        let fen = format!("{} {} - {}", _board.get_fen_placement(), if _turn == Colour::White {"w"} else {"b"}, _board.get_fen_en_passant());
+        Game::from_fen(&fen).ok_or(ProbeError::MissingTable(name))
+    }
+
+    /// Returns the WDL value, from -2 for a loss to 2 for a win, and whether a capture or pawn move reaches it.
+    /// The tables do not hold the value of captures that are the best move, and of en passant captures, so the
+    /// captures are searched first, and with `_zeroing_moves` the pawn moves as well
+    fn search(&self, _game: &Game, _zeroing_moves: bool) -> Result<(i32, bool), ProbeError> {
+        let moves = _game.get_all_legal_moves();
+        let mut best = -2;
+        let mut searched = 0;
+
+        for _move in &moves {
+            let pawn_move = _game.board.piece_at(_move.from).get_type() == PieceType::Pawn;
+            if _game.get_captured(*_move).is_none() && !(_zeroing_moves && pawn_move) {
+                continue;
+            }
+            searched += 1;
+
+            let mut child = _game.clone();
+            child.apply_move(*_move);
+            let (value, _) = self.search(&child, false)?;
+
+            if -value > best {
+                best = -value;
+                if best == 2 {
+                    return Ok((best, true));
+                }
+            }
+        }
+
+        // Without any other move there is nothing left to find in the table
+        let all_searched = searched > 0 && searched == moves.len();
+        let value = if all_searched {best} else {self.probe_table(_game)?};
+
+        if best >= value {
+            Ok((best, best > 0 || all_searched))
+        }
+        else {
+            Ok((value, false))
+        }
+    }
+
+    /// Returns the WDL value the table of the position holds
+    fn probe_table(&self, _game: &Game) -> Result<i32, ProbeError> {
+        let name = Tablebase::get_table_name(&_game.board);
+        if matches!(name.as_str(), "KvK" | "KBvK" | "KNvK") {
+            return Ok(0);
+        }
+
+        let (table, path) = self.get_table(name, Kind::Wdl)?;
+        match table.probe(&Tablebase::get_position(&_game.board), _game.active_colour == Colour::Black) {
+            Some(Some((value, _))) => Ok(value as i32 - 2),
+            _ => Err(ProbeError::InvalidTable(path))
+        }
+    }
+
+    /// Returns the distance to zeroing of a position, from the best zeroing move or from the DTZ table
+    fn get_dtz(&self, _game: &Game) -> Result<i32, ProbeError> {
+        let (wdl, zeroing) = self.search(_game, true)?;
+        if wdl == 0 {
+            return Ok(0);
+        }
+        if zeroing {
+            return Ok(Tablebase::dtz_before_zeroing(wdl));
+        }
+
+        let (table, path) = self.get_table(Tablebase::get_table_name(&_game.board), Kind::Dtz)?;
+        match table.probe(&Tablebase::get_position(&_game.board), _game.active_colour == Colour::Black) {
+            Some(Some((value, file))) => {
+                let dtz = table.get_dtz(value, file, wdl).ok_or(ProbeError::InvalidTable(path))?;
+                let cursed = if wdl.abs() == 1 {100} else {0};
+                return Ok((dtz + cursed) * wdl.signum());
+            },
+            Some(None) => (),
+            None => return Err(ProbeError::InvalidTable(path))
+        }
+
+        // The table holds the other side to move, the distance is one more than the best reply.
+        // Moves that zero the clock are worth the distance right before zeroing, and mating is always the fastest win
+        let mut best = None;
+        for _move in _game.get_all_legal_moves() {
+            let zeroing = _game.get_captured(_move).is_some() || _game.board.piece_at(_move.from).get_type() == PieceType::Pawn;
+
+            let mut child = _game.clone();
+            child.apply_move(_move);
+
+            let mut dtz = if zeroing {-Tablebase::dtz_before_zeroing(self.search(&child, false)?.0)} else {-self.get_dtz(&child)?};
+            if dtz == 1 && child.get_all_legal_moves().is_empty() && Board::king_in_check(&mut child.board, child.active_colour) {
+                best = Some(1);
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz.signum() == wdl.signum() && best.is_none_or(|best| dtz < best) {
+                best = Some(dtz);
+            }
+        }
+
+        Ok(best.unwrap_or(-1))
+    }
+
+    /// Returns the table of a name, reading it the first time
+    fn get_table(&self, _name: String, _kind: Kind) -> Result<(Arc<Table>, PathBuf), ProbeError> {
+        let tables = if _kind == Kind::Wdl {&self.wdl_tables} else {&self.dtz_tables};
+        let path = tables.get(&_name).ok_or_else(|| ProbeError::MissingTable(_name.clone()))?;
+
+        let mut loaded = self.loaded.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(table) = loaded.get(path) {
+            return Ok((table.clone(), path.clone()));
+        }
+
+        let table = fs::read(path).ok()
+            .and_then(|bytes| Table::read(bytes, &_name, _kind))
+            .ok_or_else(|| ProbeError::InvalidTable(path.clone()))?;
+        let table = Arc::new(table);
+        loaded.insert(path.clone(), table.clone());
+
+        Ok((table, path.clone()))
+    }
+
+    /// Returns every piece and its square as the tables number them
+    fn get_position(_board: &Board) -> Vec<(u8, u8)> {
+        let mut position = Vec::new();
+
+        for (row, pieces) in _board.board.iter().enumerate() {
+            for (col, piece) in pieces.iter().enumerate() {
+                let code = match piece.get_type() {
+                    PieceType::None => continue,
+                    PieceType::Pawn => 1,
+                    PieceType::Knight => 2,
+                    PieceType::Bishop => 3,
+                    PieceType::Rook => 4,
+                    PieceType::Queen => 5,
+                    PieceType::King => table::KING
+                };
+                let colour = if piece.get_colour() == Colour::Black {table::BLACK} else {0};
+                position.push((code | colour, (col + 8 * (7 - row)) as u8));
+            }
+        }
+
+        position
+    }
+
+    /// Returns the distance to zeroing of a position whose best move zeroes the clock
+    fn dtz_before_zeroing(_wdl: i32) -> i32 {
+        match _wdl {
+            2 => 1,
+            1 => 101,
+            -1 => -101,
+            -2 => -1,
+            _ => 0
+        }
+    }
+
+    /// Turns a WDL value from -2 to 2 into a `Wdl`
+    fn to_wdl(_wdl: i32) -> Wdl {
+        match _wdl {
+            2 => Wdl::Win,
+            1 => Wdl::CursedWin,
+            -1 => Wdl::BlessedLoss,
+            -2 => Wdl::Loss,
+            _ => Wdl::Draw
+        }
+    }
+}
+
+impl Default for Tablebase {
+    fn default() -> Self {
+        Tablebase::new()
+    }
+}
@@ -0,0 +1,784 @@
+//! The Syzygy file format: the header giving the order of the pieces, the index of a position in the table and the
+//! compressed values. Squares are numbered from a1 = 0 to h8 = 63 and pieces as Syzygy numbers them, 1 to 6 for a pawn,
+//! knight, bishop, rook, queen and king with `BLACK` added for Black. White is the side written first in the table name
+use std::sync::OnceLock;
+
+pub(crate) const PAWN: u8 = 1;
+pub(crate) const KING: u8 = 6;
+pub(crate) const BLACK: u8 = 8;
+
+/// Flags of a table section: the side to move a DTZ table holds, DTZ values going through a map, DTZ values of wins
+/// and losses counted in plies rather than moves, maps of 16 bit values and sections holding one value only
+pub(crate) const STM: u8 = 1;
+pub(crate) const MAPPED: u8 = 2;
+pub(crate) const WIN_PLIES: u8 = 4;
+pub(crate) const LOSS_PLIES: u8 = 8;
+pub(crate) const WIDE: u8 = 16;
+pub(crate) const SINGLE_VALUE: u8 = 128;
+
+/// Index of the DTZ map of each WDL value from -2 to 2, the maps are stored for wins, losses, cursed wins and blessed losses
+const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+/// Number of ways to place three unique pieces with the first in the a1-d1-d4 triangle, see `Pairs::encode`
+const UNIQUE_PIECES_SIZE: u64 = 31332;
+/// Number of ways to place the two kings with the first in the a1-d1-d4 triangle
+const KINGS_SIZE: u64 = 462;
+
+fn file_of(_square: u8) -> usize {
+    (_square & 7) as usize
+}
+
+fn rank_of(_square: u8) -> usize {
+    (_square >> 3) as usize
+}
+
+/// Returns how far above the a1-h8 diagonal a square is, negative below it
+fn off_diagonal(_square: u8) -> i32 {
+    rank_of(_square) as i32 - file_of(_square) as i32
+}
+
+/// Mirrors a square in the a1-h8 diagonal
+fn flip_diagonal(_square: u8) -> u8 {
+    ((_square >> 3) | (_square << 3)) & 63
+}
+
+/// Tables mapping squares and groups of squares to the numbers used in indexes
+struct Maps {
+    /// Squares below the a1-h8 diagonal to 0..28
+    b1h1h7: [u64; 64],
+    /// Squares of the a1-d1-d4 triangle to 0..10, the ones on the diagonal last
+    a1d1d4: [u64; 64],
+    /// The 462 placements of two kings that are not mirrors of each other, by the `a1d1d4` of the first and the square of the second
+    kk: [[u64; 64]; 10],
+    /// `binomial[k][n]` ways to choose k squares out of n
+    binomial: [[u64; 64]; 6],
+    /// Squares a2 to h7 to 0..48, the pawn with the highest number leads
+    pawns: [u64; 64],
+    /// Index of the leading pawn on a square, by the number of leading pawns
+    lead_pawn_idx: [[u64; 64]; 6],
+    /// Number of placements of the leading pawns with the first on a file, by the number of leading pawns
+    lead_pawns_size: [[u64; 4]; 6]
+}
+
+impl Maps {
+    fn new() -> Maps {
+        let mut maps = Maps {
+            b1h1h7: [0; 64],
+            a1d1d4: [0; 64],
+            kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 6],
+            pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6]
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                maps.b1h1h7[square as usize] = code;
+                code += 1;
+            }
+        }
+
+        // The triangle is below the diagonal up to d4, the squares on the diagonal come last
+        let mut diagonal = Vec::new();
+        code = 0;
+        for square in 0..28 {
+            if off_diagonal(square) < 0 && file_of(square) <= 3 {
+                maps.a1d1d4[square as usize] = code;
+                code += 1;
+            }
+            else if off_diagonal(square) == 0 && file_of(square) <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            maps.a1d1d4[square as usize] = code;
+            code += 1;
+        }
+
+        // Kings may not touch, and with the first king on the diagonal the second is not above it. Placements with
+        // both kings on the diagonal come last
+        let mut both_on_diagonal = Vec::new();
+        code = 0;
+        for idx in 0..10 {
+            for first in 0..28u8 {
+                if maps.a1d1d4[first as usize] != idx || (idx == 0 && first != 1) {
+                    continue;
+                }
+                for second in 0..64u8 {
+                    let touching = (file_of(first) as i32 - file_of(second) as i32).abs() <= 1 && (rank_of(first) as i32 - rank_of(second) as i32).abs() <= 1;
+                    if touching || (off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    }
+                    else {
+                        maps.kk[idx as usize][second as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            maps.kk[idx as usize][second as usize] = code;
+            code += 1;
+        }
+
+        maps.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6.min(n + 1) {
+                maps.binomial[k][n] = if k > 0 {maps.binomial[k - 1][n - 1]} else {0} + if k < n {maps.binomial[k][n - 1]} else {0};
+            }
+        }
+
+        // The pawns nearest to the edge and then lowest get the highest numbers, the second half of the board mirrors the first
+        let mut available = 47;
+        for lead in 1..6 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = file + 8 * rank;
+                    if lead == 1 {
+                        maps.pawns[square] = available;
+                        maps.pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    maps.lead_pawn_idx[lead][square] = idx;
+                    idx += maps.binomial[lead - 1][maps.pawns[square] as usize];
+                }
+                maps.lead_pawns_size[lead][file] = idx;
+            }
+        }
+
+        maps
+    }
+}
+
+fn maps() -> &'static Maps {
+    static MAPS: OnceLock<Maps> = OnceLock::new();
+    MAPS.get_or_init(Maps::new)
+}
+
+fn read_u16(_bytes: &[u8], _position: usize) -> Option<u64> {
+    Some(u16::from_le_bytes([*_bytes.get(_position)?, *_bytes.get(_position + 1)?]) as u64)
+}
+
+fn read_u32(_bytes: &[u8], _position: usize) -> Option<u64> {
+    let word = _bytes.get(_position.._position + 4)?;
+    Some(u32::from_le_bytes([word[0], word[1], word[2], word[3]]) as u64)
+}
+
+/// Reads 32 bits of compressed data, bytes past the end of the file read as zeros
+fn read_bits(_bytes: &[u8], _position: usize) -> u64 {
+    (0..4).fold(0, |word, byte| word << 8 | *_bytes.get(_position + byte).unwrap_or(&0) as u64)
+}
+
+/// ## Layout
+/// ### Type: `struct`
+/// What the name of a table says about how its positions are indexed.
+/// ### Members:
+/// - `materials`: The sorted pieces of White and Black, without the colour
+/// - `symmetric`: Both sides have the same pieces, the table only holds White to move
+/// - `has_pawns`: The table is split by the file of the leading pawn
+/// - `has_unique_pieces`: Some side has exactly one piece of a type other than the king
+/// - `pawn_count`: The pawns of the leading colour, the one with fewer pawns but some, and of the other colour
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Layout {
+    pub(crate) materials: [Vec<u8>; 2],
+    pub(crate) symmetric: bool,
+    pub(crate) has_pawns: bool,
+    pub(crate) has_unique_pieces: bool,
+    pub(crate) pawn_count: [usize; 2]
+}
+
+impl Layout {
+    /// Reads the name of a table such as `"KRPvKR"`, `None` if it is not one
+    pub(crate) fn from_name(_name: &str) -> Option<Layout> {
+        let (white, black) = _name.split_once('v')?;
+        let side = |letters: &str| -> Option<Vec<u8>> {
+            let mut pieces = letters.chars().map(|letter| "PNBRQK".find(letter).map(|piece| piece as u8 + 1)).collect::<Option<Vec<u8>>>()?;
+            pieces.sort_unstable();
+            (pieces.iter().filter(|piece| **piece == KING).count() == 1).then_some(pieces)
+        };
+        let materials = [side(white)?, side(black)?];
+
+        let count = |side: usize, piece: u8| materials[side].iter().filter(|other| **other == piece).count();
+        let has_unique_pieces = (0..2).any(|side| (PAWN..KING).any(|piece| count(side, piece) == 1));
+
+        // The leading colour has fewer pawns, but some
+        let pawns = [count(0, PAWN), count(1, PAWN)];
+        let white_leads = pawns[1] == 0 || (pawns[0] > 0 && pawns[1] >= pawns[0]);
+
+        Some(Layout {
+            symmetric: materials[0] == materials[1],
+            has_pawns: pawns[0] + pawns[1] > 0,
+            has_unique_pieces,
+            pawn_count: if white_leads {pawns} else {[pawns[1], pawns[0]]},
+            materials
+        })
+    }
+
+    /// Returns the number of pieces, kings included
+    pub(crate) fn get_piece_count(&self) -> usize {
+        self.materials[0].len() + self.materials[1].len()
+    }
+
+    /// Returns true if both colours have pawns, the pawns that do not lead are then indexed as their own group
+    fn has_pawns_on_both_sides(&self) -> bool {
+        self.has_pawns && self.pawn_count[1] > 0
+    }
+}
+
+/// ## Pairs
+/// ### Type: `struct`
+/// One compressed section of a table: the values of one side to move, and for tables with pawns of one file of the leading pawn.
+/// ### Members:
+/// - `pieces`: The pieces in the order the index places them
+/// - `group_len`: The number of pieces of each group placed together, the leading pieces first
+/// - `group_idx`: The factor of each group in the index, one more than the groups with the size of the section last
+/// - `flags`: The flags of the section such as `SINGLE_VALUE`
+/// - The rest are offsets in the file and the Huffman code of the values, see `decompress`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Pairs {
+    pub(crate) pieces: Vec<u8>,
+    pub(crate) group_len: Vec<usize>,
+    pub(crate) group_idx: Vec<u64>,
+    pub(crate) flags: u8,
+    block_size: usize,
+    span: u64,
+    sparse_index_size: u64,
+    block_length_size: usize,
+    num_blocks: usize,
+    min_sym_len: u8,
+    lowest_sym: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u32>,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+    map_idx: [usize; 4]
+}
+
+impl Pairs {
+    /// ## `new`
+    /// Splits the pieces of a section into groups and finds the factor of each group in the index
+    /// ### Parameters
+    /// - `_pieces: Vec<u8>`: The pieces in the order of the section
+    /// - `_order: [usize; 2]`: The place of the leading group and of the pawns that do not lead among the factors, 15 when there are no such pawns
+    /// - `_file: usize`: The file of the leading pawn, 0 for tables without pawns
+    /// - `_layout: &Layout`: The layout of the table
+    pub(crate) fn new(_pieces: Vec<u8>, _order: [usize; 2], _file: usize, _layout: &Layout) -> Pairs {
+        let maps = maps();
+
+        // The first three unique pieces or the two kings are placed together, then pieces of one kind
+        let mut first_len: i32 = if _layout.has_pawns {0} else if _layout.has_unique_pieces {3} else {2};
+        let mut group_len = vec![1];
+        for i in 1.._pieces.len() {
+            first_len -= 1;
+            if first_len > 0 || _pieces[i] == _pieces[i - 1] {
+                *group_len.last_mut().unwrap() += 1;
+            }
+            else {
+                group_len.push(1);
+            }
+        }
+
+        // The groups are multiplied in the order of the table, the leading group and the pawns that do not lead at
+        // their place in `_order` and the other pieces on the squares still free
+        let groups = group_len.len();
+        let both_pawns = _layout.has_pawns_on_both_sides();
+        let mut next = if both_pawns {2} else {1};
+        let mut free_squares = 64 - group_len[0] - if both_pawns {group_len[1]} else {0};
+        let mut group_idx = vec![0; groups + 1];
+        let mut idx: u64 = 1;
+        let mut k = 0;
+        while next < groups || k == _order[0] || k == _order[1] {
+            if k == _order[0] {
+                group_idx[0] = idx;
+                idx *= if _layout.has_pawns {maps.lead_pawns_size[group_len[0].min(5)][_file]}
+                    else if _layout.has_unique_pieces {UNIQUE_PIECES_SIZE}
+                    else {KINGS_SIZE};
+            }
+            else if k == _order[1] {
+                group_idx[1] = idx;
+                idx *= maps.binomial[group_len[1].min(5)][48 - group_len[0]];
+            }
+            else {
+                group_idx[next] = idx;
+                idx *= maps.binomial[group_len[next].min(5)][free_squares];
+                free_squares -= group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        group_idx[groups] = idx;
+
+        Pairs { pieces: _pieces, group_len, group_idx, ..Pairs::default() }
+    }
+
+    /// Returns the number of positions of the section
+    pub(crate) fn get_size(&self) -> u64 {
+        *self.group_idx.last().unwrap()
+    }
+
+    /// ## `encode`
+    /// Returns the index of a position in the section, mirroring the board so the leading piece is in the part of it that
+    /// is indexed. The pieces are given in table colours with the leading pawns first, as `arrange` returns them
+    /// ### Returns
+    /// Returns `Option<>` wrapping the index, `None` if the pieces are not the ones of the section
+    pub(crate) fn encode(&self, _pieces: &mut [u8], _squares: &mut [u8], _lead_pawns: usize, _layout: &Layout) -> Option<u64> {
+        let maps = maps();
+        let size = _pieces.len();
+        if size != self.pieces.len() || size < 3 {
+            return None;
+        }
+
+        // Put the other pieces in the order of the section
+        for i in _lead_pawns..size - 1 {
+            if let Some(j) = (i + 1..size).find(|j| _pieces[*j] == self.pieces[i]) {
+                _pieces.swap(i, j);
+                _squares.swap(i, j);
+            }
+        }
+        if _pieces != self.pieces.as_slice() {
+            return None;
+        }
+
+        if file_of(_squares[0]) > 3 {
+            _squares.iter_mut().for_each(|square| *square ^= 7);
+        }
+
+        let mut idx;
+        if _layout.has_pawns {
+            idx = maps.lead_pawn_idx[_lead_pawns.min(5)][_squares[0] as usize];
+            _squares[1.._lead_pawns].sort_by_key(|square| maps.pawns[*square as usize]);
+            for (i, square) in _squares[1.._lead_pawns].iter().enumerate() {
+                idx += maps.binomial[(i + 1).min(5)][maps.pawns[*square as usize] as usize];
+            }
+        }
+        else {
+            // Without pawns the leading piece is also mirrored below the fifth rank and below the a1-h8 diagonal
+            if rank_of(_squares[0]) > 3 {
+                _squares.iter_mut().for_each(|square| *square ^= 56);
+            }
+            for i in 0..self.group_len[0] {
+                match off_diagonal(_squares[i]) {
+                    0 => continue,
+                    off if off > 0 => _squares[i..].iter_mut().for_each(|square| *square = flip_diagonal(*square)),
+                    _ => ()
+                }
+                break;
+            }
+
+            idx = if _layout.has_unique_pieces {
+                let (first, second, third) = (_squares[0] as u64, _squares[1] as u64, _squares[2] as u64);
+                let adjust1 = (second > first) as u64;
+                let adjust2 = (third > first) as u64 + (third > second) as u64;
+                let rank = |square: u8| rank_of(square) as u64;
+
+                if off_diagonal(_squares[0]) != 0 {
+                    (maps.a1d1d4[first as usize] * 63 + second - adjust1) * 62 + third - adjust2
+                }
+                else if off_diagonal(_squares[1]) != 0 {
+                    (6 * 63 + rank(_squares[0]) * 28 + maps.b1h1h7[second as usize]) * 62 + third - adjust2
+                }
+                else if off_diagonal(_squares[2]) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62 + rank(_squares[0]) * 7 * 28 + (rank(_squares[1]) - adjust1) * 28 + maps.b1h1h7[third as usize]
+                }
+                else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(_squares[0]) * 7 * 6 + (rank(_squares[1]) - adjust1) * 6 + rank(_squares[2]) - adjust2
+                }
+            }
+            else {
+                maps.kk[maps.a1d1d4[_squares[0] as usize] as usize][_squares[1] as usize]
+            };
+        }
+        idx *= self.group_idx[0];
+
+        // Every other group is a set of squares, counted among the squares the groups before it left free
+        let mut start = self.group_len[0];
+        let mut remaining_pawns = _layout.has_pawns_on_both_sides();
+        for next in 1..self.group_len.len() {
+            let end = start + self.group_len[next];
+            _squares[start..end].sort_unstable();
+            let mut n = 0;
+            for i in start..end {
+                let adjust = _squares[..start].iter().filter(|square| _squares[i] > **square).count();
+                let free = (_squares[i] as usize).checked_sub(adjust + if remaining_pawns {8} else {0})?;
+                n += maps.binomial[(i - start + 1).min(5)][free];
+            }
+            remaining_pawns = false;
+            idx += n * self.group_idx[next];
+            start = end;
+        }
+
+        Some(idx)
+    }
+
+    /// Reads the sizes of the compressed values of the section and returns the position after them
+    fn read_sizes(&mut self, _bytes: &[u8], mut _position: usize) -> Option<usize> {
+        self.flags = *_bytes.get(_position)?;
+        if self.flags & SINGLE_VALUE != 0 {
+            // The one value is kept as the minimal symbol length
+            self.min_sym_len = *_bytes.get(_position + 1)?;
+            return Some(_position + 2);
+        }
+
+        self.block_size = 1usize.checked_shl(*_bytes.get(_position + 1)? as u32)?;
+        self.span = 1u64.checked_shl(*_bytes.get(_position + 2)? as u32)?;
+        self.sparse_index_size = self.get_size().div_ceil(self.span);
+        let padding = *_bytes.get(_position + 3)? as usize;
+        self.num_blocks = read_u32(_bytes, _position + 4)? as usize;
+        self.block_length_size = self.num_blocks + padding;
+        let max_sym_len = *_bytes.get(_position + 8)?;
+        self.min_sym_len = *_bytes.get(_position + 9)?;
+        if max_sym_len < self.min_sym_len || max_sym_len > 32 {
+            return None;
+        }
+        _position += 10;
+
+        // Longer Huffman codes have lower values, `base64[i]` is the lowest code of the length `min_sym_len + i`
+        // padded to 64 bits, so the length of a code is the first whose base it is not below
+        self.lowest_sym = _position;
+        let lengths = (max_sym_len - self.min_sym_len) as usize + 1;
+        self.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let count = read_u16(_bytes, self.lowest_sym + 2 * i)?.wrapping_sub(read_u16(_bytes, self.lowest_sym + 2 * i + 2)?);
+            self.base64[i] = self.base64[i + 1].wrapping_add(count) / 2;
+        }
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base = base.checked_shl((64 - i - self.min_sym_len as usize) as u32).unwrap_or(0);
+        }
+        _position += 2 * lengths;
+
+        // Every symbol is a value or a pair of symbols, `symlen` is the number of values it stands for less one
+        let symbols = read_u16(_bytes, _position)? as usize;
+        self.btree = _position + 2;
+        _bytes.get(self.btree + 3 * symbols - 1)?;
+        self.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            self.set_symlen(_bytes, symbol, &mut visited)?;
+        }
+
+        Some(self.btree + 3 * symbols + (symbols & 1))
+    }
+
+    fn set_symlen(&mut self, _bytes: &[u8], _symbol: usize, _visited: &mut [bool]) -> Option<()> {
+        if _visited[_symbol] {
+            return Some(());
+        }
+        _visited[_symbol] = true;
+
+        let (left, right) = self.get_children(_bytes, _symbol);
+        if right == 0xfff {
+            return Some(());
+        }
+        if left >= self.symlen.len() || right >= self.symlen.len() {
+            return None;
+        }
+        self.set_symlen(_bytes, left, _visited)?;
+        self.set_symlen(_bytes, right, _visited)?;
+        self.symlen[_symbol] = self.symlen[left] + self.symlen[right] + 1;
+        Some(())
+    }
+
+    /// Returns the two symbols a symbol stands for, 12 bits each, a value has it on the left and 0xfff on the right
+    fn get_children(&self, _bytes: &[u8], _symbol: usize) -> (usize, usize) {
+        let node = &_bytes[self.btree + 3 * _symbol..self.btree + 3 * _symbol + 3];
+        ((((node[1] & 0xf) as usize) << 8) | node[0] as usize, ((node[2] as usize) << 4) | (node[1] >> 4) as usize)
+    }
+
+    /// ## `decompress`
+    /// Returns the value at an index of the section. The values are cut into blocks of Huffman codes, each code a
+    /// symbol standing for one or more values. The sparse index gives the block and the place in it of every `span`-th
+    /// value, the length of the blocks leads from there to the block holding the index
+    /// ### Returns
+    /// Returns `Option<>` wrapping the value, `None` if the data does not hold the index
+    pub(crate) fn decompress(&self, _bytes: &[u8], _index: u64) -> Option<u32> {
+        if self.flags & SINGLE_VALUE != 0 {
+            return Some(self.min_sym_len as u32);
+        }
+
+        let k = _index / self.span;
+        if k >= self.sparse_index_size {
+            return None;
+        }
+        let entry = self.sparse_index + 6 * k as usize;
+        let mut block = read_u32(_bytes, entry)? as usize;
+        let mut offset = read_u16(_bytes, entry + 4)? as i64 + (_index % self.span) as i64 - (self.span / 2) as i64;
+
+        let block_length = |block: usize| -> Option<i64> {
+            if block >= self.block_length_size {
+                return None;
+            }
+            Some(read_u16(_bytes, self.block_length + 2 * block)? as i64)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+        if block >= self.num_blocks {
+            return None;
+        }
+
+        // Find the symbol holding the value, the codes are read 64 bits at a time from the start of the block
+        let mut position = self.data + block * self.block_size;
+        let mut buffer = read_bits(_bytes, position) << 32 | read_bits(_bytes, position + 4);
+        let mut buffer_size = 64;
+        position += 8;
+        let mut symbol;
+        loop {
+            let mut length = 0;
+            while buffer < self.base64[length] {
+                length += 1;
+                if length == self.base64.len() {
+                    return None;
+                }
+            }
+            let code_length = length + self.min_sym_len as usize;
+            symbol = ((buffer - self.base64[length]).checked_shr(64 - code_length as u32).unwrap_or(0) + read_u16(_bytes, self.lowest_sym + 2 * length)?) as usize;
+            let values = *self.symlen.get(symbol)? as i64 + 1;
+            if offset < values {
+                break;
+            }
+            offset -= values;
+            buffer = buffer.checked_shl(code_length as u32).unwrap_or(0);
+            buffer_size -= code_length as i32;
+            if buffer_size <= 0 {
+                return None;
+            }
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= read_bits(_bytes, position) << (64 - buffer_size);
+                position += 4;
+            }
+        }
+
+        // Pairs of symbols are next to each other in the values, go down to the value at the offset
+        while self.symlen[symbol] != 0 {
+            let (left, right) = self.get_children(_bytes, symbol);
+            if offset < self.symlen[left] as i64 + 1 {
+                symbol = left;
+            }
+            else {
+                offset -= self.symlen[left] as i64 + 1;
+                symbol = right;
+            }
+        }
+
+        Some(self.get_children(_bytes, symbol).0 as u32)
+    }
+}
+
+/// ## `arrange`
+/// Turns a position into the pieces and squares of a table: the colours and squares are flipped when the table has Black
+/// as White, the leading pawns come first with the one leading the others in front
+/// ### Parameters
+/// - `_position: &[(u8, u8)]`: The pieces and their squares in table colours
+/// - `_lead: Option<u8>`: The leading pawn of the table, `None` without pawns
+///
+/// ### Returns
+/// Returns the pieces, their squares, the number of leading pawns and the file of the section
+pub(crate) fn arrange(_position: &[(u8, u8)], _lead: Option<u8>) -> (Vec<u8>, Vec<u8>, usize, usize) {
+    let maps = maps();
+    let (mut leading, others) = _position.iter().partition::<Vec<(u8, u8)>, _>(|(piece, _)| Some(*piece) == _lead);
+
+    if let Some(first) = (0..leading.len()).max_by_key(|i| (maps.pawns[leading[*i].1 as usize], std::cmp::Reverse(*i))) {
+        leading.swap(0, first);
+    }
+    let file = leading.first().map_or(0, |(_, square)| file_of(*square).min(7 - file_of(*square)));
+    let lead_pawns = leading.len();
+
+    let (pieces, squares) = leading.into_iter().chain(others).unzip();
+    (pieces, squares, lead_pawns, file)
+}
+
+/// The kind of values a table holds
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Kind {
+    Wdl,
+    Dtz
+}
+
+/// ## Table
+/// ### Type: `struct`
+/// A Syzygy WDL or DTZ file read into memory.
+/// ### Members:
+/// - `bytes`: The contents of the file
+/// - `kind`: Whether the values are WDL or DTZ
+/// - `layout`: What the name of the table says
+/// - `pairs`: The sections by side to move and file of the leading pawn
+/// - `map`: Where the DTZ maps start
+pub(crate) struct Table {
+    bytes: Vec<u8>,
+    kind: Kind,
+    layout: Layout,
+    pairs: Vec<Vec<Pairs>>,
+    map: usize
+}
+
+impl Table {
+    /// ## `read`
+    /// Reads the header of a table
+    /// ### Parameters
+    /// - `_bytes: Vec<u8>`: The contents of the file, starting with the magic
+    /// - `_name: &str`: The name of the table such as `"KQvK"`
+    /// - `_kind: Kind`: Whether it is a WDL or a DTZ file
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping the table, `None` if the file is not a valid table of that name
+    pub(crate) fn read(_bytes: Vec<u8>, _name: &str, _kind: Kind) -> Option<Table> {
+        let layout = Layout::from_name(_name)?;
+        let piece_count = layout.get_piece_count();
+        let both_pawns = layout.has_pawns_on_both_sides();
+
+        let flags = *_bytes.get(4)?;
+        if (flags & 2 != 0) != layout.has_pawns {
+            return None;
+        }
+
+        // Split WDL tables hold both sides to move, DTZ tables and tables of the same material on both sides one of them
+        let sides = if _kind == Kind::Wdl && flags & 1 != 0 {2} else {1};
+        let files = if layout.has_pawns {4} else {1};
+        let mut pairs: Vec<Vec<Pairs>> = vec![Vec::new(); sides];
+        let mut position = 5;
+
+        for file in 0..files {
+            let order = *_bytes.get(position)? as usize;
+            let pawns_order = if both_pawns {*_bytes.get(position + 1)? as usize} else {0xff};
+            let orders = [[order & 0xf, pawns_order & 0xf], [order >> 4, pawns_order >> 4]];
+            position += if both_pawns {2} else {1};
+
+            let pieces = _bytes.get(position..position + piece_count)?;
+            position += piece_count;
+            for (side, sections) in pairs.iter_mut().enumerate() {
+                let side_pieces = pieces.iter().map(|piece| if side == 0 {piece & 0xf} else {piece >> 4}).collect();
+                sections.push(Pairs::new(side_pieces, orders[side], file, &layout));
+            }
+        }
+        position += position & 1;
+
+        for file in 0..files {
+            for sections in pairs.iter_mut() {
+                position = sections[file].read_sizes(&_bytes, position)?;
+            }
+        }
+
+        // DTZ values may go through a map for each kind of result
+        let map = position;
+        if _kind == Kind::Dtz {
+            for section in pairs[0].iter_mut() {
+                if section.flags & MAPPED == 0 {
+                    continue;
+                }
+                if section.flags & WIDE != 0 {
+                    position += position & 1;
+                    for i in 0..4 {
+                        section.map_idx[i] = (position - map) / 2 + 1;
+                        position += 2 * read_u16(&_bytes, position)? as usize + 2;
+                    }
+                }
+                else {
+                    for i in 0..4 {
+                        section.map_idx[i] = position - map + 1;
+                        position += *_bytes.get(position)? as usize + 1;
+                    }
+                }
+            }
+            position += position & 1;
+        }
+
+        for file in 0..files {
+            for sections in pairs.iter_mut() {
+                sections[file].sparse_index = position;
+                position += 6 * sections[file].sparse_index_size as usize;
+            }
+        }
+        for file in 0..files {
+            for sections in pairs.iter_mut() {
+                sections[file].block_length = position;
+                position += 2 * sections[file].block_length_size;
+            }
+        }
+        for file in 0..files {
+            for sections in pairs.iter_mut() {
+                position = (position + 0x3f) & !0x3f;
+                sections[file].data = position;
+                position += sections[file].num_blocks * sections[file].block_size;
+            }
+        }
+
+        if position > _bytes.len() {
+            return None;
+        }
+        Some(Table { bytes: _bytes, kind: _kind, layout, pairs, map })
+    }
+
+    /// ## `probe`
+    /// Looks up the value of a position
+    /// ### Parameters
+    /// - `_position: &[(u8, u8)]`: Every piece and its square
+    /// - `_black_to_move: bool`: True if Black is to move
+    ///
+    /// ### Returns
+    /// Returns `Option<>` wrapping the raw value of the position and the file of its section, `Some(None)` if this DTZ
+    /// table holds the other side to move and `None` if the position is not in the table
+    pub(crate) fn probe(&self, _position: &[(u8, u8)], _black_to_move: bool) -> Option<Option<(u32, usize)>> {
+        // The table holds the stronger side as White, and White to move only when both sides have the same pieces
+        let mut white: Vec<u8> = _position.iter().filter(|(piece, _)| *piece & BLACK == 0).map(|(piece, _)| *piece).collect();
+        white.sort_unstable();
+        let flip = (self.layout.symmetric && _black_to_move) || white != self.layout.materials[0];
+        let side = (flip != _black_to_move) as usize;
+
+        let flipped: Vec<(u8, u8)> = _position.iter()
+            .map(|(piece, square)| if flip {(piece ^ BLACK, square ^ 56)} else {(*piece, *square)})
+            .collect();
+        let lead = if self.layout.has_pawns {Some(self.pairs[0][0].pieces[0])} else {None};
+        let (mut pieces, mut squares, lead_pawns, file) = arrange(&flipped, lead);
+
+        let stored = (self.pairs[0][file].flags & STM) as usize == side || (self.layout.symmetric && !self.layout.has_pawns);
+        if self.kind == Kind::Dtz && !stored {
+            return Some(None);
+        }
+
+        let section = &self.pairs[side % self.pairs.len()][file];
+        let index = section.encode(&mut pieces, &mut squares, lead_pawns, &self.layout)?;
+        Some(Some((section.decompress(&self.bytes, index)?, file)))
+    }
+
+    /// ## `get_dtz`
+    /// Turns a raw DTZ value into plies, through the map of its section if there is one
+    /// ### Parameters
+    /// - `_value: u32`: The value `probe` returned
+    /// - `_file: usize`: The file of the section `probe` returned
+    /// - `_wdl: i32`: The WDL value of the position, from -2 for a loss to 2 for a win
+    pub(crate) fn get_dtz(&self, _value: u32, _file: usize, _wdl: i32) -> Option<i32> {
+        let section = &self.pairs[0][_file];
+        let mut value = _value as usize;
+
+        if section.flags & MAPPED != 0 {
+            let idx = section.map_idx[WDL_MAP[(_wdl + 2) as usize]];
+            value = if section.flags & WIDE != 0 {
+                read_u16(&self.bytes, self.map + 2 * (idx + value))? as usize
+            }
+            else {
+                *self.bytes.get(self.map + idx + value)? as usize
+            };
+        }
+
+        let in_moves = match _wdl {
+            2 => section.flags & WIN_PLIES == 0,
+            -2 => section.flags & LOSS_PLIES == 0,
+            _ => true
+        };
+        Some(if in_moves {value as i32 * 2 + 1} else {value as i32 + 1})
+    }
+}
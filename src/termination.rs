@@ -0,0 +1,243 @@
+use crate::chess::piece_data::Colour;
+use crate::pgn::Pgn;
+use crate::variant::Variant;
+use crate::{Game, GameState};
+
+/// Ways a game can end
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum TerminationReason {
+    Checkmate,
+    Stalemate,
+    Resignation,
+    DrawAgreement,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    TimeForfeit,
+    /// A win or draw by a rule of the variant the game is played with
+    VariantEnd
+}
+
+/// ## Termination
+/// ### Type: `struct`
+/// How and by whom a game was won, or that it was drawn.
+/// ### Members:
+/// - `reason`: Why the game ended
+/// - `winner`: The colour that won, `None` for a draw
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Termination {
+    pub reason: TerminationReason,
+    pub winner: Option<Colour>
+}
+
+impl Termination {
+    /// Returns the PGN game result, `"1-0"`, `"0-1"` or `"1/2-1/2"`
+    pub fn get_result(&self) -> &'static str {
+        match self.winner {
+            Some(Colour::White) => "1-0",
+            Some(Colour::Black) => "0-1",
+            None => "1/2-1/2"
+        }
+    }
+
+    /// Returns the value of the PGN `Termination` tag, `"time forfeit"` for games lost on time and `"normal"` otherwise
+    pub fn get_pgn_termination(&self) -> &'static str {
+        match self.reason {
+            TerminationReason::TimeForfeit => "time forfeit",
+            _ => "normal"
+        }
+    }
+
+    /// Returns the `GameState` of a game that ended this way, the variant of the game names its own endings
+    pub(crate) fn get_game_state(&self, _variant: &dyn Variant) -> GameState {
+        match self.reason {
+            TerminationReason::TimeForfeit => GameState::TimeForfeit { winner: self.winner },
+            TerminationReason::VariantEnd => _variant.get_end_state(self.winner),
+            _ => GameState::GameOver
+        }
+    }
+}
+
+impl Game {
+    /// Returns how the game ended, `None` while it is still going on. Checkmate and stalemate are found from the position
+    pub fn get_termination(&self) -> Option<Termination> {
+        if self.termination.is_some() {
+            return self.termination;
+        }
+
+        if self.get_all_legal_moves().is_empty() {
+            return Some(match self.state {
+                GameState::Check => Termination { reason: TerminationReason::Checkmate, winner: Some(self.active_colour.opposite()) },
+                _ => Termination { reason: TerminationReason::Stalemate, winner: None }
+            });
+        }
+
+        None
+    }
+
+    /// Returns the PGN result of the game, `"*"` while it is still going on
+    pub fn get_result(&self) -> &'static str {
+        self.get_termination().map_or("*", |termination| termination.get_result())
+    }
+
+    /// ## `is_over`
+    /// Returns true if the game has ended in any way. Unlike `is_finished` this also covers checkmate and stalemate,
+    /// which are found from the position rather than stored in the game state
+    pub fn is_over(&self) -> bool {
+        self.is_finished() || self.get_termination().is_some()
+    }
+
+    /// Ends the game and clears any draw offer
+    pub(crate) fn terminate(&mut self, _reason: TerminationReason, _winner: Option<Colour>) {
+        self.termination = Some(Termination { reason: _reason, winner: _winner });
+        self.draw_offer = None;
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+    }
+
+    /// ## `resign`
+    /// Resigns the game for a colour, the opponent wins.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the new `GameState`, `GameState::GameOver`, or `None` if the game is already over
+    pub fn resign(&mut self, _colour: Colour) -> Option<GameState> {
+        if self.is_over() {
+            return None;
+        }
+
+        self.terminate(TerminationReason::Resignation, Some(_colour.opposite()));
+        self.state = GameState::GameOver;
+        Some(self.state)
+    }
+
+    /// ## `offer_draw`
+    /// Offers a draw to the opponent of a colour. The offer stands until the opponent accepts or declines it or makes a move.
+    /// Offering a draw when the opponent has offered one accepts it
+    /// ### Returns
+    /// Returns false if the game is already over
+    pub fn offer_draw(&mut self, _colour: Colour) -> bool {
+        if self.is_over() {
+            return false;
+        }
+
+        if self.draw_offer == Some(_colour.opposite()) {
+            self.accept_draw(_colour);
+        }
+        else {
+            self.draw_offer = Some(_colour);
+        }
+        true
+    }
+
+    /// Returns the colour whose draw offer stands, if any
+    pub fn get_draw_offer(&self) -> Option<Colour> {
+        self.draw_offer
+    }
+
+    /// ## `accept_draw`
+    /// Accepts the draw offered by the opponent of a colour.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the new `GameState`, `GameState::GameOver`, or `None` if the opponent has not offered a draw
+    pub fn accept_draw(&mut self, _colour: Colour) -> Option<GameState> {
+        if self.is_over() || self.draw_offer != Some(_colour.opposite()) {
+            return None;
+        }
+
+        self.terminate(TerminationReason::DrawAgreement, None);
+        self.state = GameState::GameOver;
+        Some(self.state)
+    }
+
+    /// Declines the draw offered by the opponent of a colour, returns false if there was no such offer
+    pub fn decline_draw(&mut self, _colour: Colour) -> bool {
+        if self.draw_offer != Some(_colour.opposite()) {
+            return false;
+        }
+
+        self.draw_offer = None;
+        true
+    }
+
+    /// ## `get_claimable_draw`
+    /// Returns the reason a draw can be claimed in the current position, `None` if it cannot.
+    /// A draw can be claimed when the same position, with the same player to move and the same castling and en passant
+    /// possibilities, occurred three times, or when no capture or pawn move was made in the last fifty moves
+    pub fn get_claimable_draw(&self) -> Option<TerminationReason> {
+        if self.is_over() {
+            return None;
+        }
+
+        if self.get_repetitions() >= 3 {
+            return Some(TerminationReason::ThreefoldRepetition);
+        }
+        if self.halfmove_clock >= 100 {
+            return Some(TerminationReason::FiftyMoveRule);
+        }
+
+        None
+    }
+
+    /// Returns how many times the current position occurred in the game, counting itself
+    pub fn get_repetitions(&self) -> usize {
+        let key = self.get_polyglot_key();
+
+        // Positions before the last capture or pawn move cannot repeat, only the last halfmove clock moves are replayed
+        let replayed = (self.halfmove_clock as usize).min(self.history.len());
+        let mut game = self.get_start();
+        for _move in &self.history[..self.history.len() - replayed] {
+            game.apply_move(*_move);
+        }
+
+        let mut repetitions = usize::from(game.get_polyglot_key() == key);
+        for _move in &self.history[self.history.len() - replayed..] {
+            game.apply_move(*_move);
+            if game.get_polyglot_key() == key {
+                repetitions += 1;
+            }
+        }
+
+        repetitions
+    }
+
+    /// ## `claim_draw`
+    /// Ends the game in a draw if one can be claimed, see `get_claimable_draw`.
+    /// ### Returns
+    /// Returns `Option<>` wrapping the new `GameState`, `GameState::GameOver`, or `None` if no draw can be claimed
+    pub fn claim_draw(&mut self) -> Option<GameState> {
+        let reason = self.get_claimable_draw()?;
+
+        self.terminate(reason, None);
+        self.state = GameState::GameOver;
+        Some(self.state)
+    }
+
+    /// ## `to_pgn`
+    /// Returns the game as PGN, with the moves in SAN and the `Result` and `Termination` tags filled in once the game is over.
+    /// Games that did not start from the standard position get the `SetUp` and `FEN` tags, Chess960 games and games of a variant the `Variant` tag
+    pub fn to_pgn(&self) -> Pgn {
+        let mut pgn = Pgn { tags: Vec::new(), moves: Vec::with_capacity(self.history.len()), result: String::from(self.get_result()) };
+
+        if self.is_chess960() {
+            pgn.set_tag("Variant", "Chess960");
+        }
+        if self.is_variant() {
+            pgn.set_tag("Variant", self.variant.get_name());
+        }
+        if self.start_fen != Game::new().to_fen() {
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &self.start_fen);
+        }
+        if let Some(termination) = self.get_termination() {
+            pgn.set_tag("Termination", termination.get_pgn_termination());
+        }
+
+        let mut game = self.get_start();
+        for _move in &self.history {
+            pgn.moves.push(game.move_to_san(_move));
+            game.apply_move(*_move);
+        }
+
+        pgn
+    }
+}
@@ -3,6 +3,16 @@
 // --------------------------
 
 use super::*;
+use epd::Epd;
+use search::SearchLimits;
+use book::{Book, BookBuilder};
+use pgn::Pgn;
+use render::{Glyphs, Renderer, Shading};
+use svg::SvgRenderer;
+use animation::Animation;
+use raster::Frame;
+use game_tree::{Arrow, GameTree, HighlightColour, SquareHighlight};
+use tablebase::{ProbeError, Tablebase, Wdl};
 
 // check test framework
 #[test]
@@ -17,7 +27,7 @@ fn game_in_progress_after_init() {
 
     let game = Game::new();
 
-    println!("{:?}", game);
+    println!("{}", game);
 
     assert_eq!(game.get_game_state(), GameState::InProgress);
 }
@@ -60,7 +70,7 @@ fn knight_moves() {
         ._and_add_at("d4", Colour::White, PieceType::Knight)
         ._and_add_at("b4", Colour::White, PieceType::Pawn);
 
-    println!("{:?}", game);
+    println!("{}", game);
 
     assert_eq!(game.get_possible_moves(String::from("e6")).unwrap(), vec!["d8", "c7", "d4", "c5", "f8", "g7", "f4", "g5"]);
     assert_eq!(game.get_possible_moves(String::from("d4")).unwrap(), vec!["c6", "b5", "c2", "b3", "e6", "f5", "e2", "f3"]);
@@ -78,7 +88,7 @@ fn bishop_moves() {
         ._and_add_at("a7", Colour::White, PieceType::Pawn)
         ._and_add_at("d6", Colour::Black, PieceType::Pawn);
 
-    println!("{:?}", game);
+    println!("{}", game);
 
     assert_eq!(game.get_possible_moves(String::from("b7")).unwrap(), vec!["a8", "c8", "a6", "c6", "d5", "e4", "f3", "g2", "h1"]);
 
@@ -95,7 +105,7 @@ fn rook_moves() {
     game._and_add_at("c6", Colour::Black, PieceType::Rook)
         ._and_add_at("a5", Colour::White, PieceType::Rook);
 
-    println!("{:?}", game);
+    println!("{}", game);
 
     assert_eq!(game.get_possible_moves(String::from("a5")).unwrap(), vec!["b5", "c5", "d5", "e5", "f5", "g5", "h5", "a6", "a7", "a8", "a4", "a3", "a2", "a1"]);
 
@@ -114,7 +124,7 @@ fn rook_moves() {
 fn print_board() {
     let game = Game::new();
 
-    println!("{:?}", game);
+    println!("{}", game);
 }
 
 #[test]
@@ -124,7 +134,7 @@ fn queen_moves_and_check() {
     game._and_add_at("d1", Colour::White, PieceType::Queen)
         ._and_add_at("f5", Colour::Black, PieceType::Queen);
 
-    println!("{:?}", game);
+    println!("{}", game);
 
     game._then("d1", "d4");
 
@@ -194,7 +204,7 @@ fn promotion_and_check() {
 
     game._and_add_at("a7", Colour::White, PieceType::Pawn);
 
-    println!("{:?}", game);
+    println!("{}", game);
 
     assert_eq!(game.get_possible_moves(String::from("a7")).unwrap(), vec!["a8"]);
 
@@ -251,4 +261,1317 @@ fn turn_checker_panic_on_wrong(){
 
     // Black moves : should panic!
     game.make_move(String::from("e7"), String::from("e6"));
-}
\ No newline at end of file
+}
+#[test]
+fn uci_move_round_trip() {
+    assert_eq!(Move::from_uci("e2e4").unwrap(), Move::new((4, 6), (4, 4)));
+    assert_eq!(Move::from_uci("e2e4").unwrap().to_uci(), "e2e4");
+    assert_eq!(Move::from_uci("e7e8q").unwrap().promotion, Some(PieceType::Queen));
+    assert_eq!(Move::from_uci("a2a1n").unwrap().to_uci(), "a2a1n");
+
+    assert_eq!(Move::from_uci("e2e9"), None);
+    assert_eq!(Move::from_uci("e7e8k"), None);
+    assert_eq!(Move::from_uci("e2"), None);
+}
+
+#[test]
+fn uci_moves_in_game() {
+    let mut game = Game::new();
+
+    assert_eq!(game.make_uci_move("e2e4"), Some(GameState::InProgress));
+    assert_eq!(game.make_uci_move("e2e4"), None); // Not black's piece
+    assert_eq!(game.make_uci_move("e7e4"), None); // Illegal
+    assert_eq!(game.make_uci_move("e7e5q"), None); // Promotion away from last rank
+    assert_eq!(game.make_uci_move("e7e5"), Some(GameState::InProgress));
+}
+
+#[test]
+fn uci_promotion_and_check() {
+    let mut game = Game::new_empty();
+
+    game._and_add_at("a7", Colour::White, PieceType::Pawn);
+
+    // A pawn cannot stay a pawn on the last rank
+    assert_eq!(game.make_uci_move("a7a8"), None);
+    assert_eq!(game.play_move(Move::new((0, 1), (0, 0))), None);
+    assert_eq!(game.make_uci_move("a7a8q"), Some(GameState::Check));
+    assert_eq!(game.board.piece_at((0, 0)).get_piece_data(), (Colour::White, PieceType::Queen));
+
+    // Nor can it become a king or nothing at all
+    let mut game = Game::from_fen("8/P7/8/8/8/8/k7/7K w - - 0 1").unwrap();
+    for piece_type in [PieceType::King, PieceType::Pawn, PieceType::None] {
+        assert_eq!(game.play_move(Move { from: (0, 1), to: (0, 0), promotion: Some(piece_type) }), None);
+    }
+    assert_eq!(game.board.piece_at((0, 1)).get_piece_data(), (Colour::White, PieceType::Pawn));
+    assert_eq!(game.to_fen(), "8/P7/8/8/8/8/k7/7K w - - 0 1");
+    assert_eq!(game.play_move(Move { from: (0, 1), to: (0, 0), promotion: Some(PieceType::Knight) }), Some(GameState::InProgress));
+    assert_eq!(game.board.piece_at((0, 0)).get_piece_data(), (Colour::White, PieceType::Knight));
+}
+
+#[test]
+fn fen_round_trip() {
+    assert_eq!(Game::new().to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(Game::from_fen(fen).unwrap().to_fen(), fen);
+
+    let mut game = Game::new();
+    game._then("e2", "e4");
+    assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(Game::from_fen(&game.to_fen()).unwrap().to_fen(), game.to_fen());
+
+    assert!(Game::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_none()); // No kings
+    assert!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1").is_none());
+}
+
+#[test]
+fn en_passant_capture_removes_pawn() {
+    let mut game = Game::new();
+
+    game._then("e2", "e4")
+        ._then("a7", "a6")
+        ._then("e4", "e5")
+        ._then("d7", "d5")
+        ._then("e5", "d6");
+
+    assert!(game.board.is_empty(Board::filerank_to_num(&String::from("d5"))));
+    assert_eq!(game.to_fen(), "rnbqkbnr/1pp1pppp/p2P4/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+
+    // The chance to capture en passant is gone after one move
+    let mut game = Game::new();
+    game._then("e2", "e4")
+        ._then("a7", "a6")
+        ._then("e4", "e5")
+        ._then("d7", "d5")
+        ._then("h2", "h3")
+        ._then("h7", "h6");
+
+    assert_eq!(game.get_possible_moves(String::from("e5")).unwrap(), vec!["e6"]);
+}
+
+#[test]
+fn san_moves() {
+    let game = Game::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4").unwrap();
+
+    assert_eq!(game.san_to_move("Nxe5").unwrap().to_uci(), "f3e5");
+    assert_eq!(game.san_to_move("Nd5").unwrap().to_uci(), "c3d5");
+    assert_eq!(game.move_to_san(&Move::from_uci("f3e5").unwrap()), "Nxe5");
+    assert_eq!(game.move_to_san(&Move::from_uci("f1b5").unwrap()), "Bb5");
+    assert_eq!(game.san_to_move("Nge2"), None); // No such knight
+    assert_eq!(game.san_to_move("Nce2").unwrap().to_uci(), "c3e2");
+
+    let game = Game::from_fen("4k3/8/8/R7/8/8/4K3/R6R w - - 0 1").unwrap();
+    assert_eq!(game.san_to_move("Rd1"), None); // Ambiguous
+    assert_eq!(game.san_to_move("Rhd1").unwrap().to_uci(), "h1d1");
+    assert_eq!(game.move_to_san(&Move::from_uci("a1d1").unwrap()), "Rad1");
+    assert_eq!(game.move_to_san(&Move::from_uci("a1a3").unwrap()), "R1a3");
+    assert_eq!(game.san_to_move("R5a3").unwrap().to_uci(), "a5a3");
+
+    let game = Game::from_fen("6k1/P4ppp/8/8/8/8/8/6K1 w - - 0 1").unwrap();
+    assert_eq!(game.move_to_san(&Move::from_uci("a7a8q").unwrap()), "a8=Q#");
+    assert_eq!(game.san_to_move("a8=N").unwrap().to_uci(), "a7a8n");
+    assert_eq!(game.san_to_move("a8Q+").unwrap().to_uci(), "a7a8q");
+}
+
+#[test]
+fn epd_parsing() {
+    let epd = Epd::parse("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"back rank\"; c0 \"mate in one\"; dm 1;").unwrap();
+
+    assert_eq!(epd.position, "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - -");
+    assert_eq!(epd.get_id(), Some("back rank"));
+    assert_eq!(epd.get_comment(0), Some("mate in one"));
+    assert_eq!(epd.get_direct_mate(), Some(1));
+    assert_eq!(epd.get_best_moves().unwrap(), vec![Move::from_uci("a1a8").unwrap()]);
+    assert_eq!(epd.to_string(), "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"back rank\"; c0 \"mate in one\"; dm 1;");
+
+    assert_eq!(epd.is_solved_by(&Move::from_uci("a1a8").unwrap(), Some(1)), Some(true));
+    assert_eq!(epd.is_solved_by(&Move::from_uci("a1a7").unwrap(), None), Some(false));
+
+    assert!(Epd::parse("8/8/8 w").is_none());
+
+    // Evaluations and other numbers stay unquoted
+    let line = "4k3/8/8/8/8/8/8/4K2R w K - ce 35; acd 12; c1 \"cf 2\";";
+    assert_eq!(Epd::parse(line).unwrap().to_string(), line);
+}
+
+#[test]
+fn search_finds_mate_and_material() {
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+    let result = game.search(SearchLimits::depth(3));
+    assert_eq!(result.best_move.unwrap().to_uci(), "a1a8");
+    assert_eq!(result.mate_in(), Some(1));
+
+    // A hanging queen is taken
+    let game = Game::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    assert_eq!(game.search(SearchLimits::depth(2)).best_move.unwrap().to_uci(), "d2d5");
+
+    // Checkmated side has no move
+    let game = Game::from_fen("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+    assert_eq!(game.search(SearchLimits::depth(2)).best_move, None);
+}
+
+#[test]
+fn castling() {
+    let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+    assert_eq!(game.get_possible_moves(String::from("e1")).unwrap(), vec!["d2", "d1", "e2", "f2", "f1", "g1", "c1"]);
+
+    game._then("e1", "g1");
+    assert_eq!(game.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    assert_eq!(game.san_to_move("O-O-O").unwrap().to_uci(), "e8c8");
+
+    game._then("e8", "c8");
+    assert_eq!(game.to_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+
+    // No castling through an attacked square or out of check
+    let game = Game::from_fen("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(game.san_to_move("O-O"), None);
+    assert_eq!(game.san_to_move("O-O-O").unwrap().to_uci(), "e1c1");
+    let game = Game::from_fen("4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1").unwrap();
+    assert_eq!(game.san_to_move("O-O-O"), None);
+
+    // Moving the rook loses the right on its side only
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    game._then("h1", "h2");
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/7R/R3K3 b Q - 1 1");
+}
+
+#[test]
+fn polyglot_keys() {
+    let reference = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+        ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+        ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3", 0x652a607ca3f242c1),
+        ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4", 0x00fdd303c946bdd9),
+        ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+        ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4", 0x5c3f9b829b279560)
+    ];
+
+    for (fen, key) in reference {
+        assert_eq!(Game::from_fen(fen).unwrap().get_polyglot_key(), key, "{}", fen);
+    }
+
+    // Castling is encoded as the king capturing its rook
+    let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let castle = game.san_to_move("O-O").unwrap();
+    assert_eq!(game.encode_polyglot_move(&castle), 7 | (4 << 6)); // e1h1
+    assert_eq!(game.decode_polyglot_move(game.encode_polyglot_move(&castle)), Some(castle));
+}
+
+#[test]
+fn polyglot_book() {
+    let book = Book::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/book.bin")).unwrap();
+    let mut game = Game::new();
+
+    let moves: Vec<(String, u16)> = book.probe(&game).iter().map(|(m, weight)| (m.to_uci(), *weight)).collect();
+    assert_eq!(moves, vec![(String::from("e2e4"), 100), (String::from("d2d4"), 50), (String::from("g1f3"), 10)]);
+
+    // Weighted choice, the rolls fall in the ranges [0, 100), [100, 150) and [150, 160)
+    assert_eq!(book.choose_move_with(&game, 99).unwrap().to_uci(), "e2e4");
+    assert_eq!(book.choose_move_with(&game, 100).unwrap().to_uci(), "d2d4");
+    assert_eq!(book.choose_move_with(&game, 159).unwrap().to_uci(), "g1f3");
+    assert_eq!(book.choose_move_with(&game, 160).unwrap().to_uci(), "e2e4");
+    assert!(book.choose_move(&game).is_some());
+
+    game.make_uci_move("e2e4");
+    assert_eq!(book.probe(&game).len(), 2);
+
+    for uci in ["e7e5", "g1f3", "b8c6", "f1c4", "g8f6"] {
+        game.make_uci_move(uci);
+    }
+    let moves: Vec<String> = book.probe(&game).iter().map(|(m, _)| m.to_uci()).collect();
+    assert_eq!(moves, vec!["e1g1", "d2d4"]);
+
+    let game = Game::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap();
+    let moves: Vec<String> = book.probe(&game).iter().map(|(m, _)| m.to_uci()).collect();
+    assert_eq!(moves, vec!["a7a8q", "a7a8n"]);
+
+    // Positions outside the book
+    assert!(book.probe(&Game::new_empty()).is_empty());
+    assert_eq!(book.choose_move(&Game::new_empty()), None);
+
+    assert!(Book::from_bytes(&[0; 15]).is_err());
+}
+
+const ARCHIVE: &str = r#"[Event "Club"]
+[White "A"]
+[Black "B"]
+[WhiteElo "2100"]
+[BlackElo "1900"]
+[Result "1-0"]
+
+1. e4 {best by test} e5 2. Nf3 (2. f4 exf4) Nc6 $1 3. Bc4 Nf6 4. 0-0 1-0
+
+[Event "Club"]
+[White "C"]
+[Black "D"]
+[WhiteElo "1500"]
+[Result "1/2-1/2"]
+
+1.e4 c5 2.Nf3 1/2-1/2
+
+[Event "Club"]
+[Result "0-1"]
+
+1. d4 d5 ; rest of line
+2. c4 0-1
+"#;
+
+#[test]
+fn pgn_reading_and_writing() {
+    let games = Pgn::parse_all(ARCHIVE);
+    assert_eq!(games.len(), 3);
+
+    assert_eq!(games[0].moves, vec!["e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "0-0"]);
+    assert_eq!(games[0].get_tag("WhiteElo"), Some("2100"));
+    assert_eq!(games[0].result, "1-0");
+    assert_eq!(games[0].to_game().unwrap().to_fen(), "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4");
+    assert_eq!(games[1].moves, vec!["e4", "c5", "Nf3"]);
+    assert_eq!(games[2].moves, vec!["d4", "d5", "c4"]);
+
+    let written = games[1].to_string();
+    assert_eq!(written, "[Event \"Club\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \"C\"]\n[Black \"D\"]\n[Result \"1/2-1/2\"]\n[WhiteElo \"1500\"]\n\n1. e4 c5 2. Nf3 1/2-1/2\n");
+    assert_eq!(Pgn::parse(&written).unwrap().moves, games[1].moves);
+
+    // Games from a position with Black to move
+    let mut pgn = Pgn::parse("1. e4 *").unwrap();
+    pgn.set_tag("FEN", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    pgn.moves = vec![String::from("e5"), String::from("Nf3")];
+    assert!(pgn.to_string().ends_with("1... e5 2. Nf3 *\n"));
+
+    assert!(Pgn::parse("1. e4 e4 *").unwrap().to_game().is_none());
+}
+
+#[test]
+fn book_builder() {
+    let mut builder = BookBuilder::new();
+    for pgn in Pgn::parse_all(ARCHIVE) {
+        builder.add_game(&pgn).unwrap();
+    }
+
+    let start = Game::new();
+    let e4 = start.encode_polyglot_move(&Move::from_uci("e2e4").unwrap());
+    let statistics = builder.get_statistics(start.get_polyglot_key(), e4).unwrap();
+    assert_eq!((statistics.count, statistics.wins, statistics.draws, statistics.losses), (2, 1, 1, 0));
+    assert_eq!(statistics.get_average_rating(), Some(1800));
+
+    // Black lost with e5 and drew with c5, so c5 scores higher
+    let book = Book::from_entries(builder.build());
+    let mut game = Game::new();
+    game.make_uci_move("e2e4");
+    let moves: Vec<(String, u16)> = book.probe(&game).iter().map(|(m, weight)| (m.to_uci(), *weight)).collect();
+    assert_eq!(moves, vec![(String::from("c7c5"), 1), (String::from("e7e5"), 0)]);
+
+    // Castling is written as the king taking the rook and read back as castling
+    let game = Game::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4").unwrap();
+    assert_eq!(book.probe(&game)[0].0.to_uci(), "e1g1");
+
+    // Filters
+    let mut builder = BookBuilder::new();
+    builder.min_games = 2;
+    builder.max_ply = 1;
+    for pgn in Pgn::parse_all(ARCHIVE) {
+        builder.add_game(&pgn);
+    }
+    assert_eq!(builder.build().len(), 1);
+
+    let mut builder = BookBuilder::new();
+    builder.min_rating = 2000;
+    for pgn in Pgn::parse_all(ARCHIVE) {
+        builder.add_game(&pgn);
+    }
+    assert_eq!(builder.build().len(), 4); // Only the white moves of the first game
+
+    let path = std::env::temp_dir().join("azeezd_chess_book_builder.bin");
+    builder.write(&path).unwrap();
+    assert_eq!(Book::open(&path).unwrap().get_entries(), builder.build().as_slice());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn tablebase_names_and_trivial_material() {
+    let board = |fen: &str| Game::from_fen(fen).unwrap().board;
+
+    assert_eq!(Tablebase::get_table_name(&board("8/8/8/4k3/8/8/8/4K2Q w - - 0 1")), "KQvK");
+    assert_eq!(Tablebase::get_table_name(&board("8/8/8/4k3/8/8/8/r3K3 w - - 0 1")), "KRvK");
+    assert_eq!(Tablebase::get_table_name(&board("8/8/3r4/4k3/8/8/4P3/4K2R w - - 0 1")), "KRPvKR");
+    assert_eq!(Tablebase::get_table_name(&board("8/8/3q4/4k3/8/8/8/4K2R w - - 0 1")), "KQvKR");
+
+    let tablebase = Tablebase::new();
+    assert_eq!(tablebase.probe_wdl(&board("8/8/8/4k3/8/8/8/4K3 w - - 0 1"), Colour::White), Ok(Wdl::Draw));
+    assert_eq!(tablebase.probe_dtz(&board("8/8/8/4k3/8/8/8/3NK3 b - - 0 1"), Colour::Black), Ok(0));
+    assert_eq!(tablebase.probe_wdl(&board("8/8/8/4k3/8/8/8/4K2Q w - - 0 1"), Colour::White), Err(ProbeError::MissingTable(String::from("KQvK"))));
+    assert_eq!(tablebase.probe_wdl(&board("8/8/8/4k3/8/8/3PP3/4K3 w - - 0 1"), Colour::White), Err(ProbeError::TooManyPieces));
+    assert_eq!(tablebase.probe_wdl(&Game::new().board, Colour::White), Err(ProbeError::CastlingRights));
+
+    // Only files starting with the Syzygy magic are used
+    let directory = std::env::temp_dir().join("azeezd_chess_tablebase");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("KQvK.rtbw"), [0x71, 0xe8, 0x23, 0x5d, 0x00]).unwrap();
+    std::fs::write(directory.join("KRvK.rtbw"), [0x00, 0x00, 0x00, 0x00]).unwrap();
+
+    let mut tablebase = Tablebase::new();
+    assert_eq!(tablebase.add_directory(&directory).unwrap(), 1);
+    assert_eq!(tablebase.get_max_pieces(), 3);
+    assert!(matches!(tablebase.probe_wdl(&board("8/8/8/4k3/8/8/8/4K2Q w - - 0 1"), Colour::White), Err(ProbeError::InvalidTable(_))));
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Capturing the last piece into a known draw is found through the tables
+    let game = Game::from_fen("8/8/8/8/3k4/8/1n6/K7 w - - 0 1").unwrap();
+    let result = game.search_with_tablebase(SearchLimits::depth(2), Some(&Tablebase::new()));
+    assert_eq!(result.best_move, Move::from_uci("a1b2"));
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn tablebase_probe_fixtures() {
+    let mut tablebase = Tablebase::new();
+    assert_eq!(tablebase.add_directory(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/syzygy")).unwrap(), 8);
+    assert_eq!(tablebase.get_max_pieces(), 4);
+
+    for (fen, wdl, dtz) in [
+        // Mates in one, stalemate and captures of the last piece
+        ("k7/8/2K5/8/8/8/8/1Q6 w - - 0 1", Wdl::Win, 1),
+        ("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1", Wdl::Draw, 0),
+        ("8/8/8/8/8/2k5/3Q4/7K b - - 0 1", Wdl::Draw, 0),
+        ("k7/8/1K6/8/4b3/8/8/7R w - - 0 1", Wdl::Win, 1),
+        ("k7/8/1K6/8/4b3/8/8/7R b - - 0 1", Wdl::Draw, 0),
+        // Black holding the rook is read from the same table
+        ("7r/8/8/4B3/8/1k6/8/K7 b - - 0 1", Wdl::Win, 1),
+        ("8/8/8/4k3/8/8/8/4K2Q b - - 0 1", Wdl::Loss, -18),
+        ("8/8/8/4k3/8/8/8/R3K3 w - - 0 1", Wdl::Win, 27),
+        // The king in front of the rook pawn draws, the king on the sixth rank wins with either side to move
+        ("k7/8/8/8/8/8/P7/K7 w - - 0 1", Wdl::Draw, 0),
+        ("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Wdl::Win, 3),
+        ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Wdl::Loss, -4)
+    ] {
+        let position = Game::from_fen(fen).unwrap();
+        assert_eq!(tablebase.probe_wdl(&position.board, position.active_colour), Ok(wdl), "{}", fen);
+        assert_eq!(tablebase.probe_dtz(&position.board, position.active_colour), Ok(dtz), "{}", fen);
+    }
+
+    // Following the distance to zeroing mates in exactly that many halfmoves
+    let mut game = Game::from_fen("8/8/8/4k3/8/8/8/4K2Q w - - 0 1").unwrap();
+    let mut dtz = tablebase.probe_dtz(&game.board, game.active_colour).unwrap();
+    assert_eq!(dtz, 13);
+    loop {
+        let child = |_move: Move| {
+            let mut child = game.clone();
+            child.apply_move(_move);
+            let dtz = tablebase.probe_dtz(&child.board, child.active_colour).unwrap();
+            (child, dtz)
+        };
+        // The winner takes the fastest mate and the loser the slowest
+        let (next, next_dtz) = game.get_all_legal_moves().into_iter().map(child)
+            .max_by_key(|(_, next_dtz)| if dtz < 0 || *next_dtz < 0 {*next_dtz} else {i32::MIN})
+            .unwrap();
+        if next.get_all_legal_moves().is_empty() {
+            assert_eq!(dtz, 1);
+            assert_eq!(next.get_game_state(), GameState::Check);
+            break;
+        }
+        assert_eq!(next_dtz, if dtz > 0 {-(dtz - 1)} else {-dtz - 1});
+        game = next;
+        dtz = next_dtz;
+    }
+}
+
+#[test]
+fn binary_encoding() {
+    use termination::{Termination, TerminationReason};
+
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "8/8/8/8/8/8/8/k6K w - - 0 1"
+    ] {
+        let board = Game::from_fen(fen).unwrap().board;
+        let bytes = board.to_bytes();
+        let decoded = Board::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.get_fen_placement(), board.get_fen_placement());
+        assert_eq!(decoded.get_fen_castling(), board.get_fen_castling());
+        assert_eq!(decoded.get_fen_en_passant(), board.get_fen_en_passant());
+    }
+
+    // 8 bytes of occupancy, 16 bytes for 32 pieces, castling and en passant
+    assert_eq!(Game::new().board.to_bytes().len(), 26);
+    assert_eq!(Game::from_fen("8/8/8/8/8/8/8/k6K w - - 0 1").unwrap().board.to_bytes().len(), 11);
+    assert!(Board::from_bytes(&Game::new().board.to_bytes()[..25]).is_none());
+
+    let mut game = Game::from_fen("r3k2r/8/8/8/8/8/1p6/R3K2R b KQkq - 3 20").unwrap();
+    for uci in ["e8c8", "e1g1", "b2a1n", "f1f8", "d8f8"] {
+        game.make_uci_move(uci).unwrap();
+    }
+
+    let bytes = game.to_bytes().unwrap();
+    let decoded = Game::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.to_fen(), game.to_fen());
+    assert_eq!(decoded.get_history(), game.get_history());
+    assert_eq!(decoded.get_start_fen(), "r3k2r/8/8/8/8/8/1p6/R3K2R b KQkq - 3 20");
+
+    // One byte per move after the position and its clocks, and one for how the game ended
+    let mut game = Game::new();
+    for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6"] {
+        game.make_uci_move(uci).unwrap();
+    }
+    assert_eq!(game.to_bytes().unwrap().len(), 26 + 5 + 6 + 1);
+    assert_eq!(Game::from_bytes(&game.to_bytes().unwrap()).unwrap().to_fen(), game.to_fen());
+
+    assert!(Game::from_bytes(&[]).is_none());
+    let mut bytes = game.to_bytes().unwrap();
+    let last_move = bytes.len() - 2;
+    bytes[last_move] = 100;
+    assert!(Game::from_bytes(&bytes).is_none());
+
+    // A resigned or agreed game stays over
+    game.resign(Colour::White);
+    let decoded = Game::from_bytes(&game.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.get_termination(), Some(Termination { reason: TerminationReason::Resignation, winner: Some(Colour::Black) }));
+    assert_eq!(decoded.get_game_state(), GameState::GameOver);
+    assert_eq!(decoded.get_result(), "0-1");
+
+    let mut game = Game::new();
+    game.offer_draw(Colour::White);
+    game.accept_draw(Colour::Black);
+    let bytes = game.to_bytes().unwrap();
+    assert_eq!(Game::from_bytes(&bytes).unwrap().get_termination(), Some(Termination { reason: TerminationReason::DrawAgreement, winner: None }));
+    let mut bytes = bytes;
+    *bytes.last_mut().unwrap() = 3;
+    assert!(Game::from_bytes(&bytes).is_none());
+
+    // A pawn left on the last rank without a promotion cannot be written
+    let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    game.make_move(String::from("a7"), String::from("a8"));
+    assert!(game.to_bytes().is_none());
+    game.set_promotion(String::from("a8"), String::from("queen"));
+    assert_eq!(Game::from_bytes(&game.to_bytes().unwrap()).unwrap().to_fen(), game.to_fen());
+
+    // The variant is kept, a king on the hill still wins after reading the game back
+    let mut game = Game::new_variant(variant::from_name("King of the Hill").unwrap()).unwrap();
+    for uci in ["e2e4", "d7d5", "e1e2", "d5e4", "e2e3", "a7a6", "e3e4"] {
+        game.make_uci_move(uci).unwrap();
+    }
+    let decoded = Game::from_bytes(&game.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.get_variant().get_name(), "King of the Hill");
+    assert_eq!(decoded.get_game_state(), GameState::KingOfTheHill { winner: Colour::White });
+    assert_eq!(decoded.get_termination(), game.get_termination());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let mut game = Game::new();
+    for uci in ["e2e4", "d7d5", "e4d5", "g8f6"] {
+        game.make_uci_move(uci).unwrap();
+    }
+
+    let json = serde_json::to_string(&game).unwrap();
+    assert_eq!(json, "{\"start_fen\":\"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\",\
+        \"moves\":[\"e2e4\",\"d7d5\",\"e4d5\",\"g8f6\"],\
+        \"fen\":\"rnbqkb1r/ppp1pppp/5n2/3P4/8/8/PPPP1PPP/RNBQKBNR w KQkq - 1 3\"}");
+
+    let decoded: Game = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_fen(), game.to_fen());
+    assert_eq!(decoded.get_history(), game.get_history());
+
+    // The stored position must be the one the moves lead to
+    assert!(serde_json::from_str::<Game>(&json.replace("\"g8f6\"", "\"b8c6\"")).is_err());
+    assert!(serde_json::from_str::<Game>(&json.replace("\"e4d5\"", "\"e4e5\"")).is_err());
+
+    let board = Game::from_fen("4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 0 1").unwrap().board;
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(json, "{\"placement\":\"4k3/8/8/3pP3/8/8/8/R3K3\",\"castling\":\"Q\",\"en_passant\":\"d6\"}");
+    let decoded: Board = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.get_fen_en_passant(), "d6");
+    assert_eq!(decoded.get_fen_castling(), "Q");
+
+    assert_eq!(serde_json::to_string(&Piece::new(Colour::Black, PieceType::Knight)).unwrap(), "\"n\"");
+    assert_eq!(serde_json::to_string(&Piece::from_u8(0x0)).unwrap(), "null");
+    assert_eq!(serde_json::from_str::<Piece>("\"Q\"").unwrap().get_piece_data(), (Colour::White, PieceType::Queen));
+    assert!(serde_json::from_str::<Piece>("\"x\"").is_err());
+
+    assert_eq!(serde_json::to_string(&Colour::White).unwrap(), "\"white\"");
+    assert_eq!(serde_json::to_string(&PieceType::Bishop).unwrap(), "\"bishop\"");
+    assert_eq!(serde_json::to_string(&GameState::InProgress).unwrap(), "\"in_progress\"");
+    assert_eq!(serde_json::from_str::<GameState>("\"check\"").unwrap(), GameState::Check);
+
+    game.resign(Colour::White);
+    let json = serde_json::to_string(&game).unwrap();
+    assert!(json.ends_with(",\"termination\":{\"reason\":\"resignation\",\"winner\":\"black\"}}"));
+    let decoded: Game = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.get_game_state(), GameState::GameOver);
+    assert_eq!(decoded.get_result(), "0-1");
+}
+
+/// Counts the leaf positions of the move tree to a depth
+fn perft(_game: &Game, _depth: u32) -> u64 {
+    if _depth == 0 {
+        return 1;
+    }
+
+    _game.get_all_legal_moves().into_iter().map(|_move| {
+        let mut child = _game.clone();
+        child.apply_move(_move);
+        perft(&child, _depth - 1)
+    }).sum()
+}
+
+#[test]
+fn chess960() {
+    assert_eq!(chess960::chess960_back_rank(518).unwrap(), "RNBQKBNR");
+    assert_eq!(chess960::chess960_back_rank(0).unwrap(), "BBQNNRKR");
+    assert_eq!(chess960::chess960_back_rank(959).unwrap(), "RKRNNQBB");
+    assert_eq!(chess960::chess960_back_rank(960), None);
+
+    // Every position is different, with bishops on both colours and the king between the rooks
+    let mut ranks: Vec<String> = (0..960).map(|id| chess960::chess960_back_rank(id).unwrap()).collect();
+    for rank in &ranks {
+        let bishops: Vec<usize> = rank.match_indices('B').map(|(file, _)| file).collect();
+        assert_ne!(bishops[0] % 2, bishops[1] % 2, "{}", rank);
+        let (king, rooks) = (rank.find('K').unwrap(), rank.match_indices('R').map(|(file, _)| file).collect::<Vec<usize>>());
+        assert!(rooks[0] < king && king < rooks[1], "{}", rank);
+    }
+    ranks.sort();
+    ranks.dedup();
+    assert_eq!(ranks.len(), 960);
+
+    // Reference move counts
+    for (fen, nodes) in [
+        ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12189]),
+        ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18002])
+    ] {
+        let game = Game::from_fen(fen).unwrap();
+        assert!(game.is_chess960());
+        for (depth, count) in nodes.iter().enumerate() {
+            assert_eq!(perft(&game, depth as u32 + 1), *count, "{} depth {}", fen, depth + 1);
+        }
+    }
+
+    // Castling is the king taking its rook, the rook on a1 crosses the square of the king and the black king lands on its rook's square
+    let mut game = Game::from_fen("r3k1r1/8/8/8/8/8/8/RK5R w HAga - 0 1").unwrap();
+    assert_eq!(game.to_fen(), "r3k1r1/8/8/8/8/8/8/RK5R w KQkq - 0 1");
+    assert_eq!(game.to_shredder_fen(), "r3k1r1/8/8/8/8/8/8/RK5R w HAga - 0 1");
+
+    assert_eq!(game.san_to_move("O-O-O").unwrap().to_uci(), "b1a1");
+    assert_eq!(game.move_to_san(&Move::from_uci("b1h1").unwrap()), "O-O");
+    game.make_uci_move("b1a1").unwrap();
+    assert_eq!(game.to_fen(), "r3k1r1/8/8/8/8/8/8/2KR3R b kq - 1 1");
+    game.make_uci_move("e8g8").unwrap();
+    assert_eq!(game.to_fen(), "r4rk1/8/8/8/8/8/8/2KR3R w - - 2 2");
+
+    // An inner rook is written by its file in X-FEN
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/RR2K3 w B - 0 1").unwrap();
+    assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/RR2K3 w B - 0 1");
+
+    let mut game = Game::new_chess960(518).unwrap();
+    assert_eq!(game.to_fen(), Game::new().to_fen());
+    for uci in ["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "e1h1"] {
+        game.make_uci_move(uci).unwrap();
+    }
+    assert_eq!(game.to_fen(), "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4");
+    let decoded = Game::from_bytes(&game.to_bytes().unwrap()).unwrap();
+    assert!(decoded.is_chess960());
+    assert_eq!(decoded.to_fen(), game.to_fen());
+
+    let mut game = Game::new_chess960(0).unwrap();
+    assert_eq!(game.to_fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    assert!(game.make_uci_move("g1h1").is_none());
+}
+
+#[test]
+fn clocks() {
+    use clock::{Clock, ManualTime, Period, TimeControl, Timing};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let seconds = Duration::from_secs;
+
+    // Fischer increment is added after every move
+    let time = ManualTime::new();
+    let mut clock = Clock::with_time_source(TimeControl::fischer(seconds(60), seconds(2)), Arc::new(time.clone()));
+    clock.start(Colour::White);
+    time.advance(seconds(10));
+    assert_eq!(clock.get_time_left(Colour::White), seconds(50));
+    assert!(clock.press());
+    assert_eq!(clock.get_time_left(Colour::White), seconds(52));
+    assert_eq!(clock.get_running(), Some(Colour::Black));
+
+    // Bronstein gives back the time spent up to the delay, simple delay only starts counting after it
+    for (control, short, long) in [(TimeControl::bronstein(seconds(60), seconds(5)), 60, 55), (TimeControl::simple_delay(seconds(60), seconds(5)), 60, 55)] {
+        let time = ManualTime::new();
+        let mut clock = Clock::with_time_source(control, Arc::new(time.clone()));
+        clock.start(Colour::White);
+        time.advance(seconds(3));
+        clock.press();
+        assert_eq!(clock.get_time_left(Colour::White), seconds(short));
+        clock.press();
+        time.advance(seconds(10));
+        clock.press();
+        assert_eq!(clock.get_time_left(Colour::White), seconds(long));
+    }
+    let time = ManualTime::new();
+    let mut clock = Clock::with_time_source(TimeControl::simple_delay(seconds(60), seconds(5)), Arc::new(time.clone()));
+    clock.start(Colour::White);
+    time.advance(seconds(4));
+    assert_eq!(clock.get_time_left(Colour::White), seconds(60));
+
+    // 40 moves in 90 minutes then 30 minutes for the rest, 30 seconds added from the first move
+    let control = TimeControl::from_pgn_tag("40/5400+30:1800+30").unwrap();
+    assert_eq!(control.periods[0], Period { moves: Some(40), time: seconds(5400), timing: Some(Timing::Increment(seconds(30))) });
+    assert_eq!(control.to_string(), "40/5400+30:1800+30");
+    assert_eq!(TimeControl::from_pgn_tag("?"), None);
+
+    let time = ManualTime::new();
+    let mut clock = Clock::with_time_source(control, Arc::new(time.clone()));
+    clock.start(Colour::White);
+    for _ in 0..39 {
+        time.advance(seconds(60));
+        clock.press();
+        clock.press();
+    }
+    assert_eq!(clock.get_time_left(Colour::White), seconds(5400 - 39 * 30));
+    time.advance(seconds(60));
+    clock.press();
+    assert_eq!(clock.get_time_left(Colour::White), seconds(5400 - 40 * 30 + 1800));
+
+    // A flag fall loses the game, unless the opponent cannot checkmate
+    let time = ManualTime::new();
+    let mut game = Game::new();
+    game.set_clock(Clock::with_time_source(TimeControl::sudden_death(seconds(60)), Arc::new(time.clone())));
+    game.make_uci_move("e2e4").unwrap();
+    time.advance(seconds(59));
+    assert_eq!(game.check_time(), GameState::InProgress);
+    time.advance(seconds(1));
+    assert_eq!(game.make_uci_move("e7e5"), Some(GameState::TimeForfeit { winner: Some(Colour::White) }));
+    assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert!(game.is_finished());
+    assert_eq!(game.make_uci_move("e7e5"), None);
+
+    let time = ManualTime::new();
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/2B1K3 w - - 0 1").unwrap();
+    game.set_clock(Clock::with_time_source(TimeControl::sudden_death(seconds(60)), Arc::new(time.clone())));
+    time.advance(seconds(61));
+    assert_eq!(game.check_time(), GameState::TimeForfeit { winner: None });
+
+    let board = |fen: &str| Game::from_fen(fen).unwrap().board;
+    assert!(!board("4k3/8/8/8/8/8/8/2N1K3 w - - 0 1").has_mating_material(Colour::White));
+    assert!(board("4k3/4p3/8/8/8/8/8/2N1K3 w - - 0 1").has_mating_material(Colour::White));
+    assert!(!board("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1").has_mating_material(Colour::White));
+    assert!(board("4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1").has_mating_material(Colour::White));
+    assert!(board("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1").has_mating_material(Colour::White));
+}
+
+#[test]
+fn game_endings() {
+    use termination::{Termination, TerminationReason};
+
+    let mut game = Game::new();
+    game.make_uci_move("e2e4").unwrap();
+    assert_eq!(game.resign(Colour::Black), Some(GameState::GameOver));
+    assert_eq!(game.get_termination(), Some(Termination { reason: TerminationReason::Resignation, winner: Some(Colour::White) }));
+    assert_eq!(game.get_result(), "1-0");
+    assert_eq!(game.make_uci_move("e7e5"), None);
+    assert_eq!(game.resign(Colour::White), None);
+
+    // An offer stands through the offering player's move and lapses with the opponent's
+    let mut game = Game::new();
+    assert!(game.offer_draw(Colour::White));
+    assert_eq!(game.accept_draw(Colour::White), None);
+    game.make_uci_move("e2e4").unwrap();
+    assert_eq!(game.get_draw_offer(), Some(Colour::White));
+    game.make_uci_move("e7e5").unwrap();
+    assert_eq!(game.get_draw_offer(), None);
+    assert_eq!(game.accept_draw(Colour::Black), None);
+
+    game.offer_draw(Colour::White);
+    assert!(game.decline_draw(Colour::Black));
+    assert!(!game.decline_draw(Colour::Black));
+    game.offer_draw(Colour::White);
+    assert_eq!(game.accept_draw(Colour::Black), Some(GameState::GameOver));
+    assert_eq!(game.get_termination(), Some(Termination { reason: TerminationReason::DrawAgreement, winner: None }));
+    assert_eq!(game.get_result(), "1/2-1/2");
+
+    // The starting position occurs for the third time after two knight round trips
+    let mut game = Game::new();
+    for uci in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+        game.make_uci_move(uci).unwrap();
+        assert_eq!(game.get_claimable_draw(), None);
+    }
+    game.make_uci_move("f6g8").unwrap();
+    assert_eq!(game.get_repetitions(), 3);
+    assert_eq!(game.claim_draw(), Some(GameState::GameOver));
+    assert_eq!(game.get_termination().unwrap().reason, TerminationReason::ThreefoldRepetition);
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+    assert_eq!(game.claim_draw(), None);
+    game.make_uci_move("a1a2").unwrap();
+    assert_eq!(game.get_claimable_draw(), Some(TerminationReason::FiftyMoveRule));
+
+    // Checkmate is found from the position and the PGN is filled in
+    let mut game = Game::new();
+    for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+        game.make_uci_move(uci).unwrap();
+    }
+    assert_eq!(game.get_termination(), Some(Termination { reason: TerminationReason::Checkmate, winner: Some(Colour::Black) }));
+    let pgn = game.to_pgn();
+    assert_eq!(pgn.moves, vec!["f3", "e5", "g4", "Qh4#"]);
+    assert_eq!(pgn.result, "0-1");
+    assert_eq!(pgn.get_tag("Termination"), Some("normal"));
+    assert_eq!(pgn.get_tag("FEN"), None);
+
+    // A game ended by checkmate or stalemate can no longer be resigned or drawn
+    assert!(game.is_over());
+    assert_eq!(game.resign(Colour::White), None);
+    assert!(!game.offer_draw(Colour::White));
+    assert_eq!(game.accept_draw(Colour::Black), None);
+    assert_eq!(game.claim_draw(), None);
+    assert_eq!(game.get_termination().unwrap().reason, TerminationReason::Checkmate);
+    assert_eq!(game.get_result(), "0-1");
+
+    let mut game = Game::from_fen("k7/2Q5/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+    assert!(game.is_over());
+    assert!(!game.is_finished());
+    assert_eq!(game.resign(Colour::Black), None);
+    assert!(!game.offer_draw(Colour::Black));
+    assert_eq!(game.get_termination().unwrap().reason, TerminationReason::Stalemate);
+    assert_eq!(game.get_result(), "1/2-1/2");
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+    game.make_uci_move("e8d8").unwrap();
+    let pgn = game.to_pgn();
+    assert_eq!(pgn.get_tag("FEN"), Some("4k3/8/8/8/8/8/8/R3K3 b - - 0 1"));
+    assert_eq!(pgn.result, "*");
+    assert!(pgn.to_string().contains("\n1... Kd8 *\n"));
+}
+
+#[test]
+fn game_tree() {
+    let text = "[Event \"Tree\"]\n\n{Start} 1. e4! {[%cal Ge2e4] Best by test} (1. d4 d5 (1... Nf6 2. c4) 2. c4 $1) 1... e5 2. Nf3?! {[%csl Rf3]} Nc6 *";
+    let mut tree = GameTree::parse(text).unwrap();
+    assert_eq!(tree.tags, vec![(String::from("Event"), String::from("Tree"))]);
+    assert_eq!(tree.get_main_line().len(), 4);
+    assert_eq!(tree.get_game().to_fen(), "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+
+    let e4 = tree.get_main_line()[0];
+    let node = tree.get_node(e4).unwrap();
+    assert_eq!(node.nags, vec![1]);
+    assert_eq!(node.comment, "Best by test");
+    assert_eq!(node.arrows[0].from, Board::filerank_to_num(&String::from("e2")));
+    assert_eq!(node.arrows[0].colour, HighlightColour::Green);
+    assert_eq!(tree.get_node(tree.root()).unwrap().get_children().len(), 2);
+
+    // Writing the tree and reading it back gives the same PGN
+    let written = tree.to_string();
+    assert!(written.replace('\n', " ").contains("{Start} 1. e4 $1 {[%cal Ge2e4] Best by test} (1. d4 d5 (1... Nf6 2. c4) 2. c4 $1) 1... e5 2. Nf3 $6 {[%csl Rf3]} 2... Nc6 *"));
+    assert_eq!(GameTree::parse(&written).unwrap().to_string(), written);
+
+    // Arrows and squares that are not ASCII are left out without panicking
+    let unicode = GameTree::parse("1. e4 {[%cal Geé4,Gd2d4] [%csl Gé] Ünïcode} *").unwrap();
+    let node = unicode.get_node(unicode.get_main_line()[0]).unwrap();
+    assert_eq!(node.arrows.len(), 1);
+    assert!(node.squares.is_empty());
+    assert_eq!(node.comment, "Ünïcode");
+
+    // Promoting 1. d4 makes it the main line, deleting it leaves 1. e4 alone
+    let d4 = tree.get_node(tree.root()).unwrap().get_children()[1];
+    let d5 = tree.get_node(d4).unwrap().get_children()[0];
+    let c4 = tree.get_node(d5).unwrap().get_children()[0];
+    assert!(tree.go_to(c4));
+    assert!(tree.promote_variation(c4));
+    assert_eq!(tree.get_main_line()[0], d4);
+    assert!(!tree.promote_variation(c4));
+
+    assert!(tree.delete_variation(d4));
+    assert_eq!(tree.get_current(), tree.root());
+    assert_eq!(tree.get_main_line()[0], e4);
+    assert!(tree.get_node(c4).is_none());
+
+    // Adding a move that exists follows it, a new one becomes a variation
+    tree.go_to(e4);
+    let e5 = tree.get_main_line()[1];
+    assert_eq!(tree.add_san("e5"), Some(e5));
+    tree.go_back();
+    let c5 = tree.add_san("c5").unwrap();
+    assert_eq!(tree.get_node(e4).unwrap().get_children(), &[e5, c5]);
+    assert_eq!(tree.add_san("Ke3"), None);
+}
+
+#[test]
+fn board_rendering() {
+    let game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    println!("{}", game);
+
+    let text = Renderer::new().render(game.get_board());
+    assert_eq!(text.lines().next(), Some("8  .  .  .  .  k  .  .  . "));
+    assert_eq!(text.lines().last(), Some("   a  b  c  d  e  f  g  h "));
+    assert_eq!(text, game.to_string());
+
+    // From Black's side with Unicode pieces, shaded squares and the e-pawn marked
+    let renderer = Renderer {
+        glyphs: Glyphs::Unicode,
+        shading: Shading::Text,
+        coordinates: false,
+        orientation: Colour::Black,
+        marked: vec![Board::filerank_to_num(&String::from("e2"))]
+    };
+    let text = renderer.render(game.get_board());
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0], "    ░     ♔     ░     ░ ");
+    assert_eq!(lines[1], " ░     ░ [♙] ░     ░    ");
+    assert_eq!(renderer.display(game.get_board()).to_string(), text);
+}
+
+#[test]
+fn svg_diagrams() {
+    let svg = Board::new().to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<use ").count(), 32);
+    assert!(!svg.contains("href=\"http"));
+
+    // The last move and the checked king of a game are highlighted, arrows and squares come from the renderer
+    let mut game = Game::new();
+    for uci in ["e2e4", "f7f6", "d1h5"] {
+        game.make_uci_move(uci).unwrap();
+    }
+    let renderer = SvgRenderer {
+        orientation: Colour::Black,
+        arrows: vec![Arrow { from: (3, 0), to: (7, 4), colour: HighlightColour::Red }],
+        squares: vec![SquareHighlight { square: (5, 1), colour: HighlightColour::Green }],
+        ..SvgRenderer::new()
+    };
+    let svg = renderer.render_game(&game);
+    assert!(svg.contains("fill=\"url(#check)\""));
+    assert_eq!(svg.matches("fill-opacity=\"0.8\"").count(), 2);
+    assert!(svg.contains("<polygon "));
+    assert!(svg.contains("fill=\"#15781b\" fill-opacity=\"0.5\""));
+    // From Black's side h1 is in the top left corner
+    assert!(svg.contains(">h</text>"));
+    assert!(svg.find(">h</text>") < svg.find(">a</text>"));
+}
+
+#[test]
+fn animation_export() {
+    let mut game = Game::new();
+    for uci in ["e2e4", "e7e5", "d1h5", "b8c6", "f1c4", "g8f6", "h5f7"] {
+        game.make_uci_move(uci).unwrap();
+    }
+
+    let animation = Animation { size: 100, ..Animation::new() };
+    let frames = animation.render_frames(&game);
+    assert_eq!(frames.len(), 8);
+    assert_eq!((frames[0].width, frames[0].height), (96, 96));
+    // a8 is a light square, h8 a dark one and the last move ended on f7
+    assert_eq!(frames[0].get_pixel(11, 11), [240, 217, 181]);
+    assert_eq!(frames[0].get_pixel(95, 1), [181, 136, 99]);
+    assert_ne!(frames[7].get_pixel(61, 13), frames[6].get_pixel(61, 13));
+
+    let png = frames[0].to_png();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+    // The zlib stream of a black pixel ends with the Adler-32 of its filter byte and three zeros, before the chunk's CRC
+    let png = Frame::new(1, 1, [0, 0, 0]).to_png();
+    assert_eq!(&png[png.len() - 20..png.len() - 16], &[0, 4, 0, 1]);
+
+    let mut gif = Vec::new();
+    animation.write_gif(&game, &mut gif).unwrap();
+    assert_eq!(&gif[..6], b"GIF89a");
+    assert_eq!(&gif[6..10], &[96, 0, 96, 0]);
+    assert_eq!(gif.last(), Some(&0x3B));
+    assert_eq!(gif.windows(3).filter(|window| *window == [0x21, 0xF9, 0x04]).count(), 8);
+
+    let directory = std::env::temp_dir().join(format!("azeezd-chess-frames-{}", std::process::id()));
+    let paths = animation.write_png_sequence(&game, &directory, "game").unwrap();
+    assert_eq!(paths.len(), 8);
+    assert!(paths[7].ends_with("game_007.png"));
+    assert_eq!(std::fs::read(&paths[0]).unwrap(), frames[0].to_png());
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_bindings() {
+    use wasm::WasmGame;
+
+    let mut game = WasmGame::new();
+    assert_eq!(game.make_move("e4").unwrap(), r#"{"state":"in_progress","turn":"black","result":"*","termination":null}"#);
+    game.make_move("f7f6").unwrap();
+    game.make_move("d2d4").unwrap();
+    assert_eq!(game.legal_moves_from("g7"), r#"["g6","g5"]"#);
+    assert_eq!(game.legal_moves_from("d4"), "[]");
+    assert!(game.make_move("Ke1").is_err());
+
+    game.make_move("g5").unwrap();
+    assert_eq!(game.make_move("Qh5#").unwrap(), r#"{"state":"check","turn":"black","result":"1-0","termination":"checkmate"}"#);
+    assert_eq!(game.legal_moves(), "[]");
+    assert!(game.make_move("a6").is_err());
+    assert!(game.pgn().contains("3. Qh5# 1-0"));
+
+    assert_eq!(game.undo().unwrap(), "d1h5");
+    assert_eq!(game.history(), r#"["e2e4","f7f6","d2d4","g7g5"]"#);
+    assert_eq!(game.fen(), "rnbqkbnr/ppppp2p/5p2/6p1/3PP3/8/PPP2PPP/RNBQKBNR w KQkq g6 0 3");
+    assert_eq!(game.legal_moves().matches(',').count() + 1, Game::from_fen(&game.fen()).unwrap().get_all_legal_moves().len());
+
+    let copy = WasmGame::from_pgn(&game.pgn()).unwrap();
+    assert_eq!(copy.fen(), game.fen());
+    assert!(WasmGame::from_fen("not a fen").is_err());
+    assert!(WasmGame::new().undo().is_err());
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_api() {
+    use ffi::*;
+    use std::ffi::CStr;
+
+    unsafe {
+        let mut game: *mut ChessGame = std::ptr::null_mut();
+        assert_eq!(chess_game_from_fen(b"8/8/8/8/5k2/8/8/5K1q w - - 0 1\0".as_ptr() as *const _, &mut game), ChessError::Ok);
+
+        let mut moves = [ChessMove { uci: [0; 6] }; CHESS_MAX_MOVES];
+        let mut count = 0;
+        assert_eq!(chess_game_legal_moves(game, moves.as_mut_ptr(), moves.len(), &mut count), ChessError::Ok);
+        assert_eq!(count, 2);
+        assert!(moves[..count].iter().any(|_move| CStr::from_ptr(_move.uci.as_ptr()).to_str() == Ok("f1e2")));
+
+        let mut state = ChessState::InProgress;
+        assert_eq!(chess_game_state(game, &mut state), ChessError::Ok);
+        assert_eq!(state, ChessState::Check);
+        assert_eq!(chess_game_make_move(game, b"f1g1\0".as_ptr() as *const _), ChessError::IllegalMove);
+        assert_eq!(chess_game_make_move(game, b"f1e2\xff\0".as_ptr() as *const _), ChessError::InvalidUtf8);
+        assert_eq!(chess_game_make_move(game, b"f1e2\0".as_ptr() as *const _), ChessError::Ok);
+
+        let mut buffer = [0 as std::os::raw::c_char; 128];
+        let mut length = 0;
+        assert_eq!(chess_game_fen(game, std::ptr::null_mut(), 0, &mut length), ChessError::BufferTooSmall);
+        assert_eq!(length, 30);
+        assert_eq!(chess_game_fen(game, buffer.as_mut_ptr(), buffer.len(), &mut length), ChessError::Ok);
+        assert_eq!(CStr::from_ptr(buffer.as_ptr()).to_str(), Ok("8/8/8/8/5k2/8/4K3/7q b - - 1 1"));
+        assert_eq!(length, 30);
+
+        assert_eq!(chess_game_state(std::ptr::null(), &mut state), ChessError::NullPointer);
+        assert_eq!(CStr::from_ptr(chess_error_message(ChessError::GameOver as std::os::raw::c_int)).to_str(), Ok("the game is over"));
+        assert_eq!(CStr::from_ptr(chess_error_message(42)).to_str(), Ok("unknown error"));
+        assert_eq!(CStr::from_ptr(chess_error_message(-1)).to_str(), Ok("unknown error"));
+        chess_game_free(game);
+    }
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn http_game_server() {
+    use server::{GameServer, Request};
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    let server = GameServer::new();
+    let created = server.handle(&Request::new("POST", "/games", ""));
+    assert_eq!(created.status, 201);
+    assert_eq!(created.body["id"], 1);
+    assert_eq!(created.body["turn"], "white");
+
+    let played = server.handle(&Request::new("POST", "/games/1/moves", r#"{"move": "e4"}"#));
+    assert_eq!(played.status, 200);
+    assert_eq!(played.body["moves"], serde_json::json!(["e2e4"]));
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/moves", r#"{"move": "e7e4"}"#)).body["error"]["code"], "illegal_move");
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/moves", "{")).status, 400);
+    assert_eq!(server.handle(&Request::new("GET", "/games/1/moves", "")).body["moves"].as_array().unwrap().len(), 20);
+
+    for _move in ["f6", "d4", "g5", "Qh5#"] {
+        server.handle(&Request::new("POST", "/games/1/moves", &format!(r#"{{"move": "{}"}}"#, _move)));
+    }
+    let mate = server.handle(&Request::new("GET", "/games/1", ""));
+    assert_eq!(mate.body["result"], "1-0");
+    assert_eq!(mate.body["termination"]["reason"], "checkmate");
+    assert_eq!(mate.body["san"][4], "Qh5#");
+    let over = server.handle(&Request::new("POST", "/games/1/resign", ""));
+    assert_eq!((over.status, over.body["error"]["code"].as_str()), (409, Some("game_over")));
+
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/undo", "")).body["result"], "*");
+    assert!(server.handle(&Request::new("GET", "/games/1/pgn", "")).body["pgn"].as_str().unwrap().contains("2. d4 g5 *"));
+
+    server.handle(&Request::new("POST", "/games/1/draw", r#"{"colour": "white"}"#));
+    assert_eq!(server.handle(&Request::new("GET", "/games/1", "")).body["draw_offer"], "white");
+    assert_eq!(server.handle(&Request::new("POST", "/games/1/draw", r#"{"action": "accept", "colour": "white"}"#)).body["error"]["code"], "no_draw_offer");
+    let agreed = server.handle(&Request::new("POST", "/games/1/draw", r#"{"action": "accept"}"#));
+    assert_eq!(agreed.body["termination"], serde_json::json!({"reason": "draw_agreement", "winner": null}));
+
+    assert_eq!(server.handle(&Request::new("POST", "/games", r#"{"fen": "8/8"}"#)).body["error"]["code"], "invalid_fen");
+    assert_eq!(server.handle(&Request::new("GET", "/games/7", "")).status, 404);
+    assert_eq!(server.handle(&Request::new("PUT", "/games/1", "")).status, 405);
+    assert_eq!(server.handle(&Request::new("DELETE", "/games/1", "")).status, 204);
+    assert_eq!(server.handle(&Request::new("GET", "/games", "")).body["games"], serde_json::json!([]));
+
+    // The same routes over a connection
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || Arc::new(GameServer::new()).serve(listener));
+
+    let body = r#"{"fen": "7k/8/8/8/8/8/8/K6R w - - 0 1"}"#;
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    write!(stream, "POST /games HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 201 Created\r\n"));
+    let json: serde_json::Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(json["fen"], "7k/8/8/8/8/8/8/K6R w - - 0 1");
+}
+
+#[test]
+fn ics_server() {
+    use ics::IcsServer;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || Arc::new(IcsServer::new()).serve(listener));
+
+    // Sends a line and reads until a line containing the expected text, which is returned
+    let connect = || {
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        (stream.try_clone().unwrap(), BufReader::new(stream))
+    };
+    let talk = |(stream, reader): &mut (TcpStream, BufReader<TcpStream>), _line: &str, _expected: &str| -> String {
+        if !_line.is_empty() {
+            write!(stream, "{}\r\n", _line).unwrap();
+        }
+        let mut line = String::new();
+        loop {
+            line.clear();
+            assert!(reader.read_line(&mut line).expect(_expected) > 0, "connection closed waiting for {}", _expected);
+            if line.contains(_expected) {
+                return line.trim_end().to_string();
+            }
+        }
+    };
+
+    let mut alice = connect();
+    let mut bob = connect();
+    talk(&mut alice, "alice", "Starting session as alice");
+    talk(&mut bob, "Alice", "is already logged in");
+    talk(&mut bob, "bob", "Starting session as bob");
+
+    talk(&mut alice, "seek 5 0 white", "posted with index 1");
+    talk(&mut bob, "", "alice seeking 5 0 blitz [white] (\"play 1\" to respond)");
+    talk(&mut bob, "style 12", "Style 12 set.");
+    talk(&mut bob, "play 1", "Creating 5 0 blitz match.");
+    assert!(talk(&mut bob, "", "<12>").starts_with("<12> rnbqkbnr pppppppp -------- -------- -------- -------- PPPPPPPP RNBQKBNR W -1 1 1 1 1 0 1 alice bob -1 5 0 39 39 "));
+    talk(&mut alice, "", "    8  | *R| *N| *B| *Q| *K| *B| *N| *R|     Move # : 1 (White)");
+
+    talk(&mut bob, "e5", "It is not your move.");
+    talk(&mut alice, "e2-e4", "Last move : P/e2-e4");
+    let line = talk(&mut bob, "", "<12>");
+    assert!(line.contains(" B 4 1 1 1 1 0 1 alice bob 1 5 0 "));
+    assert!(line.ends_with(" 1 P/e2-e4 (0:00) e4 1"));
+
+    talk(&mut bob, "say good luck", "(told alice)");
+    talk(&mut alice, "", "bob says: good luck");
+    talk(&mut bob, "e7e5", "P/e7-e5");
+    talk(&mut alice, "moves", "  1.  e4        e5");
+
+    talk(&mut alice, "draw", "Draw request sent.");
+    talk(&mut bob, "", "alice offers you a draw.");
+    talk(&mut bob, "draw", "{Game 1 (alice vs. bob) Game drawn by mutual agreement} 1/2-1/2");
+    talk(&mut alice, "", "{Game 1 (alice vs. bob) Game drawn by mutual agreement} 1/2-1/2");
+    talk(&mut alice, "games", "0 games displayed.");
+    talk(&mut alice, "xyzzy", "xyzzy: Command not found.");
+}
+
+#[test]
+fn match_statistics() {
+    use stats::{elo_to_score, score_to_elo, Pentanomial, Results, Sprt, SprtStatus, Trinomial};
+
+    assert!((elo_to_score(0.0) - 0.5).abs() < 1e-12);
+    assert!((score_to_elo(elo_to_score(100.0)) - 100.0).abs() < 1e-9);
+    assert_eq!(score_to_elo(1.0), f64::INFINITY);
+    assert_eq!(Trinomial::default().estimate(0.95), None);
+
+    // 60 wins, 100 draws and 40 losses: a score of 55 %
+    let games = Trinomial::new(60, 100, 40);
+    let estimate = games.estimate(0.95).unwrap();
+    assert!((estimate.elo - 34.86).abs() < 0.01);
+    assert!((estimate.lower - 1.04).abs() < 0.05 && (estimate.upper - 69.35).abs() < 0.05);
+    assert!((estimate.los - 0.9783).abs() < 0.001);
+    assert_eq!(format!("{}", Trinomial::new(10, 0, 10).estimate(0.95).unwrap()).split(',').nth(1), Some(" LOS: 50.0 %"));
+
+    // Pairs take out the advantage of the opening, the interval is tighter than for the same score counted per game
+    let mut pairs = Pentanomial::default();
+    for points in [1.0, 1.5, 1.0, 2.0, 1.0, 1.5, 0.5, 1.0] {
+        pairs.add(points);
+    }
+    assert_eq!(pairs.pairs, [0, 1, 4, 2, 1]);
+    let paired = pairs.estimate(0.95).unwrap();
+    let unpaired = Trinomial::new(5, 9, 2).estimate(0.95).unwrap();
+    assert!(paired.get_error() < unpaired.get_error());
+
+    // The bounds of alpha = beta = 0.05 are about -2.94 and 2.94
+    let sprt = Sprt::default();
+    let (lower, upper) = sprt.get_bounds();
+    assert!((lower + 2.944).abs() < 0.001 && (upper - 2.944).abs() < 0.001);
+    assert_eq!(sprt.llr(&Pentanomial::default()), 0.0);
+    assert_eq!(sprt.get_status(&Pentanomial::new([0, 10, 40, 10, 0])), SprtStatus::Continue);
+    assert_eq!(sprt.get_status(&Pentanomial::new([100, 800, 2000, 1200, 200])), SprtStatus::AcceptH1);
+    assert_eq!(sprt.get_status(&Pentanomial::new([200, 1200, 2000, 800, 100])), SprtStatus::AcceptH0);
+    assert!(sprt.llr(&Trinomial::new(600, 1000, 400)) > sprt.llr(&Trinomial::new(500, 1000, 500)));
+}
+
+#[test]
+fn variant_rules() {
+    use chess::board::Board;
+    use std::sync::Arc;
+    use termination::TerminationReason;
+    use variant::Variant;
+
+    // Pawns only step one square, a capturing piece is removed with its victim and the first capture wins
+    struct FirstBlood;
+    impl Variant for FirstBlood {
+        fn get_name(&self) -> &str {
+            "First Blood"
+        }
+        fn get_start_fen(&self) -> &str {
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1"
+        }
+        fn get_legal_moves(&self, _game: &Game, _from: (i8, i8), _moves: Vec<(i8, i8)>) -> Vec<(i8, i8)> {
+            let pawn = _game.get_board().piece_at(_from).get_type() == PieceType::Pawn;
+            _moves.into_iter().filter(|to| !pawn || (to.1 - _from.1).abs() == 1).collect()
+        }
+        fn apply_effects(&self, _board: &mut Board, _move: Move, _captured: Option<Piece>) {
+            if _captured.is_some() {
+                *_board.mut_piece_at(_move.to) = Piece::from_u8(0x0);
+            }
+        }
+        fn get_outcome(&self, _game: &Game) -> Option<Termination> {
+            let captures = _game.get_board().board.iter().flatten().filter(|piece| piece.get_type() != PieceType::None).count();
+            (captures < 32).then(|| Termination { reason: TerminationReason::VariantEnd, winner: Some(_game.get_active_colour().opposite()) })
+        }
+    }
+
+    let mut game = Game::new_variant(Arc::new(FirstBlood)).unwrap();
+    assert!(game.is_variant());
+    assert_eq!(game.get_start_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1");
+    assert_eq!(game.get_all_legal_moves().len(), 12);
+    assert_eq!(game.make_uci_move("e2e4"), None);
+    assert_eq!(game.get_possible_moves(String::from("e2")), Some(vec![String::from("e3")]));
+
+    assert_eq!(game.make_uci_move("e2e3"), Some(GameState::InProgress));
+    assert_eq!(game.make_uci_move("d7d6"), Some(GameState::InProgress));
+    assert_eq!(game.make_uci_move("f1b5"), Some(GameState::Check));
+    assert_eq!(game.make_uci_move("c7c5"), None);
+    assert_eq!(game.make_uci_move("c7c6"), Some(GameState::InProgress));
+
+    // The bishop takes and is blown away with the pawn
+    assert_eq!(game.make_uci_move("b5c6"), Some(GameState::GameOver));
+    assert!(game.get_board().is_empty((2, 2)));
+    assert_eq!(game.get_termination(), Some(Termination { reason: TerminationReason::VariantEnd, winner: Some(Colour::White) }));
+    assert_eq!(game.make_uci_move("b7b6"), None);
+
+    // The search knows a capture wins
+    let mut position = Game::from_fen("4k3/8/8/8/3p4/8/8/3QK3 w - - 0 1").unwrap();
+    position.set_variant(Arc::new(FirstBlood));
+    assert_eq!(position.search(SearchLimits::depth(2)).best_move, Some(Move::from_uci("d1d4").unwrap()));
+
+    // Replays keep the variant, unknown variants are not read from PGN
+    let pgn = game.to_pgn();
+    assert_eq!(pgn.get_tag("Variant"), Some("First Blood"));
+    assert_eq!(game.take_back().unwrap().get_termination(), None);
+    assert!(pgn.get_start().is_none());
+    assert!(!Pgn::parse("[Variant \"Standard\"]\n\n1. e4 *").unwrap().to_game().unwrap().is_variant());
+
+    // Captures blow up the capturing piece and every piece but pawns around them, a colour without a king has lost
+    struct Explosions;
+    impl Variant for Explosions {
+        fn get_name(&self) -> &str {
+            "Explosions"
+        }
+        fn is_check(&self, _board: &mut Board, _colour: Colour) -> bool {
+            let has_king = |colour: Colour| _board.board.iter().flatten().any(|piece| piece.get_piece_data() == (colour, PieceType::King));
+            if !has_king(_colour) {
+                return true;
+            }
+            has_king(_colour.opposite()) && Board::king_in_check(_board, _colour)
+        }
+        fn apply_effects(&self, _board: &mut Board, _move: Move, _captured: Option<Piece>) {
+            if _captured.is_none() {
+                return;
+            }
+            *_board.mut_piece_at(_move.to) = Piece::from_u8(0x0);
+            for col in (_move.to.0 - 1).max(0)..=(_move.to.0 + 1).min(7) {
+                for row in (_move.to.1 - 1).max(0)..=(_move.to.1 + 1).min(7) {
+                    if _board.piece_at((col, row)).get_type() != PieceType::Pawn {
+                        *_board.mut_piece_at((col, row)) = Piece::from_u8(0x0);
+                    }
+                }
+            }
+        }
+        fn get_outcome(&self, _game: &Game) -> Option<Termination> {
+            let mover = _game.get_active_colour().opposite();
+            let has_king = _game.get_board().board.iter().flatten().any(|piece| piece.get_piece_data() == (_game.get_active_colour(), PieceType::King));
+            (!has_king).then_some(Termination { reason: TerminationReason::VariantEnd, winner: Some(mover) })
+        }
+    }
+
+    // White is in check but taking next to the black king blows it up, which only the variant's check allows
+    let fen = "r3k3/3n4/8/8/8/8/8/K2Q4 w - - 0 1";
+    assert_eq!(Game::from_fen(fen).unwrap().make_uci_move("d1d7"), None);
+    let mut game = Game::from_fen(fen).unwrap();
+    game.set_variant(Arc::new(Explosions));
+    assert_eq!(game.get_game_state(), GameState::Check);
+    assert_eq!(game.make_uci_move("d1d7"), Some(GameState::GameOver));
+    assert_eq!(game.get_termination(), Some(Termination { reason: TerminationReason::VariantEnd, winner: Some(Colour::White) }));
+
+    // A capture next to the own king is illegal
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/3p4/3QK3 w - - 0 1").unwrap();
+    game.set_variant(Arc::new(Explosions));
+    assert_eq!(game.make_uci_move("d1d2"), None);
+    assert_eq!(game.make_uci_move("e1d2"), None);
+    assert!(game.make_uci_move("e1f2").is_some());
+}
+
+#[test]
+fn king_of_the_hill() {
+    use std::sync::Arc;
+    use termination::TerminationReason;
+    use variant::KingOfTheHill;
+
+    let game = Game::new_variant(Arc::new(KingOfTheHill)).unwrap();
+    assert_eq!(game.to_fen(), Game::new().to_fen());
+    assert_eq!(game.get_variant().get_name(), "King of the Hill");
+    assert!(variant::from_name("kingOfTheHill").is_some() && variant::from_name("Atomic").is_none());
+
+    // The pawn guards d4, a king walking onto the hill while in check does not win
+    let mut game = Game::from_fen("4k3/8/8/2p5/8/3K4/8/8 w - - 0 1").unwrap();
+    game.set_variant(Arc::new(KingOfTheHill));
+    assert_eq!(game.make_uci_move("d3d4"), None);
+    assert_eq!(game.make_uci_move("d3e4"), Some(GameState::KingOfTheHill { winner: Colour::White }));
+    assert!(game.is_finished());
+    assert_eq!(game.get_termination(), Some(Termination { reason: TerminationReason::VariantEnd, winner: Some(Colour::White) }));
+    assert_eq!(game.get_result(), "1-0");
+    assert_eq!(game.make_uci_move("e8d8"), None);
+
+    // The Variant tag brings the rules back
+    let pgn = game.to_pgn();
+    assert_eq!(pgn.get_tag("Variant"), Some("King of the Hill"));
+    let replayed = Pgn::parse(&pgn.to_string()).unwrap().to_game().unwrap();
+    assert_eq!(replayed.get_game_state(), GameState::KingOfTheHill { winner: Colour::White });
+    assert_eq!(Pgn::parse("[Variant \"King of the Hill\"]\n\n1. e4 e5 2. Ke2 Ke7 3. Kd3 Kd6 *").unwrap().to_game().unwrap().make_uci_move("d3d4"), None);
+
+    // The engine walks to the hill and likes kings close to it
+    let mut position = Game::from_fen("k7/8/8/8/8/3K4/8/7q w - - 0 1").unwrap();
+    let standard = position.evaluate();
+    position.set_variant(Arc::new(KingOfTheHill));
+    assert!(position.evaluate() > standard);
+    let result = position.search(SearchLimits::depth(2));
+    assert_eq!(result.best_move, Some(Move::from_uci("d3d4").unwrap()));
+    assert_eq!(result.mate_in(), Some(1));
+}
@@ -0,0 +1,185 @@
+//! Rule sets a `Game` can be played with. The standard rules stay in `Board` and `Game`, a variant only says where
+//! it differs from them: the starting position, which moves are legal, what a move does besides moving a piece and
+//! when the game is over. Chess960 is not a variant here but a castling mode, see `Game::set_chess960`
+use crate::chess::board::Board;
+use crate::chess::moves::Move;
+use crate::chess::piece::Piece;
+use crate::chess::piece_data::Colour;
+use crate::search::centre_distance;
+use crate::termination::{Termination, TerminationReason};
+use crate::{Game, GameState};
+use std::sync::Arc;
+
+/// The starting position of standard chess
+pub const STANDARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// ## Variant
+/// ### Type: `trait`
+/// The rules of a chess variant as changes to the standard rules, every method defaults to standard chess
+pub trait Variant: Send + Sync {
+    /// Returns the name of the variant as written in the PGN `Variant` tag, e.g `"Standard"`
+    fn get_name(&self) -> &str;
+
+    /// Returns the FEN string of the position the variant starts from
+    fn get_start_fen(&self) -> &str {
+        STANDARD_FEN
+    }
+
+    /// ## `get_legal_moves`
+    /// Changes the legal moves of a piece of the active colour.
+    /// ### Parameters
+    /// - `_game: &Game`: The game before the move
+    /// - `_from: (i8, i8)`: The square of the piece
+    /// - `_moves: Vec<(i8, i8)>`: The squares the piece may move to by the standard rules after which `is_check` does not find its colour in check
+    ///
+    /// ### Returns
+    /// Returns the squares the piece may move to in the variant
+    fn get_legal_moves(&self, _game: &Game, _from: (i8, i8), _moves: Vec<(i8, i8)>) -> Vec<(i8, i8)> {
+        _moves
+    }
+
+    /// ## `is_check`
+    /// Tells if the royal piece of a colour is attacked. A move after which the mover is in check is illegal and a colour
+    /// in check without legal moves is checkmated, so variants whose pieces explode or whose kings may be left attacked change it here
+    /// ### Parameters
+    /// - `_board: &mut Board`: The board, after `apply_effects` when a move is being tried
+    /// - `_colour: Colour`: The colour that may be in check
+    fn is_check(&self, _board: &mut Board, _colour: Colour) -> bool {
+        Board::king_in_check(_board, _colour)
+    }
+
+    /// ## `apply_effects`
+    /// Changes the board after a move was played on it by the standard rules, before the check and the end of the game are looked at.
+    /// It is also called on copies of the board to find the moves that leave the mover in check
+    /// ### Parameters
+    /// - `_board: &mut Board`: The board after the move
+    /// - `_move: Move`: The move that was played
+    /// - `_captured: Option<Piece>`: The piece the move captured, including a pawn taken en passant
+    fn apply_effects(&self, _board: &mut Board, _move: Move, _captured: Option<Piece>) {}
+
+    /// ## `get_outcome`
+    /// Looks at the position after every move for a win or draw of the variant, checkmate and stalemate are still found by `Game::get_termination`
+    /// ### Returns
+    /// Returns `Option<>` wrapping how the game ended, `None` if it goes on
+    fn get_outcome(&self, _game: &Game) -> Option<Termination> {
+        None
+    }
+
+    /// ## `get_end_state`
+    /// Returns the `GameState` of a game `get_outcome` ended with `TerminationReason::VariantEnd`
+    /// ### Parameters
+    /// - `_winner: Option<Colour>`: The colour that won, `None` for a draw
+    fn get_end_state(&self, _winner: Option<Colour>) -> GameState {
+        GameState::GameOver
+    }
+
+    /// Returns a bonus in centipawns for the active colour added to the material and centre control counted by `Game::evaluate`
+    fn evaluate(&self, _game: &Game) -> i32 {
+        0
+    }
+}
+
+/// ## Standard
+/// ### Type: `struct`
+/// Standard chess, the variant of games that were not given one
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Standard;
+
+impl Variant for Standard {
+    fn get_name(&self) -> &str {
+        "Standard"
+    }
+}
+
+/// Bonus of a king by its distance to the hill, the four centre squares. A king on the hill has already won
+const HILL_BONUS: [i32; 4] = [0, 150, 50, 0];
+
+/// ## KingOfTheHill
+/// ### Type: `struct`
+/// Standard chess where a king legally reaching d4, d5, e4 or e5 wins the game at once, ending it with `GameState::KingOfTheHill`
+#[derive(Copy, Clone, Debug, Default)]
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+    fn get_name(&self) -> &str {
+        "King of the Hill"
+    }
+
+    fn get_outcome(&self, _game: &Game) -> Option<Termination> {
+        let mover = _game.get_active_colour().opposite();
+        if centre_distance(_game.get_board().get_king(mover)) == 0 {
+            return Some(Termination { reason: TerminationReason::VariantEnd, winner: Some(mover) });
+        }
+        None
+    }
+
+    fn get_end_state(&self, _winner: Option<Colour>) -> GameState {
+        _winner.map_or(GameState::GameOver, |winner| GameState::KingOfTheHill { winner })
+    }
+
+    /// Kings close to the hill threaten to win, so they are worth more than the safety they give up
+    fn evaluate(&self, _game: &Game) -> i32 {
+        let bonus = |colour: Colour| HILL_BONUS[centre_distance(_game.get_board().get_king(colour)) as usize];
+        bonus(_game.get_active_colour()) - bonus(_game.get_active_colour().opposite())
+    }
+}
+
+/// Returns every variant of this crate. The order is part of the format of `Game::to_bytes`, new variants go at the end
+pub(crate) fn get_variants() -> Vec<Arc<dyn Variant>> {
+    vec![Arc::new(Standard), Arc::new(KingOfTheHill)]
+}
+
+/// ## `from_name`
+/// Returns the variant of this crate with a PGN `Variant` tag name, `None` for unknown variants.
+/// Case, spaces and dashes are ignored, `"kingOfTheHill"` and `"king-of-the-hill"` are King of the Hill
+pub fn from_name(_name: &str) -> Option<Arc<dyn Variant>> {
+    let normalise = |name: &str| -> String {name.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase()};
+    get_variants().into_iter().find(|variant| normalise(variant.get_name()) == normalise(_name))
+}
+
+impl Game {
+    /// ## `new_variant`
+    /// Creates a game of a variant from its starting position
+    /// ### Returns
+    /// Returns `Option<>` wrapping the `Game`, `None` if the starting position of the variant is not a valid FEN
+    pub fn new_variant(_variant: Arc<dyn Variant>) -> Option<Game> {
+        let mut game = Game::from_fen(_variant.get_start_fen())?;
+        game.set_variant(_variant);
+        Some(game)
+    }
+
+    /// Returns the variant the game is played with
+    pub fn get_variant(&self) -> &dyn Variant {
+        self.variant.as_ref()
+    }
+
+    /// ## `set_variant`
+    /// Changes the rules of the game, it should be set before any move is played.
+    /// Use it to play a variant from a position given as FEN
+    pub fn set_variant(&mut self, _variant: Arc<dyn Variant>) {
+        self.variant = _variant;
+        if !self.is_finished() {
+            self.state = if self.variant.is_check(&mut self.board, self.active_colour) {GameState::Check} else {GameState::InProgress};
+        }
+    }
+
+    /// Returns true if the game is played with other rules than the standard ones
+    pub fn is_variant(&self) -> bool {
+        !self.variant.get_name().eq_ignore_ascii_case(Standard.get_name())
+    }
+
+    /// Returns the squares the piece at a square may move to by the rules of the variant
+    pub(crate) fn get_legal_move_coords(&self, _from: (i8, i8)) -> Vec<(i8, i8)> {
+        let colour = self.board.piece_at(_from).get_colour();
+        let safe = self.board.get_pseudo_legal_moves(_from).into_iter().filter(|to| {
+            let _move = Move::new(_from, *to);
+            let captured = self.get_captured(_move);
+            let mut board = self.board.clone();
+            board.play_move(_from, *to);
+            self.variant.apply_effects(&mut board, _move, captured);
+            !self.variant.is_check(&mut board, colour)
+        }).collect();
+
+        self.variant.get_legal_moves(self, _from, safe)
+    }
+}
@@ -0,0 +1,175 @@
+//! WebAssembly bindings around `Game`, enabled by the `wasm` feature. Build with
+//! `wasm-pack build --features wasm` and use the `Game` class from JavaScript:
+//! ```js
+//! const game = new Game();
+//! game.makeMove("e4");
+//! JSON.parse(game.legalMoves());   // ["b8a6", "b8c6", ...]
+//! JSON.parse(game.state());        // {"state": "in_progress", "turn": "black", "result": "*", "termination": null}
+//! ```
+//! Everything goes in and out as strings: moves in UCI or SAN, positions as FEN, games as PGN and lists or
+//! records as JSON. Errors are thrown as a string with the reason
+use crate::chess::board::Board;
+use crate::chess::piece_data::Colour;
+use crate::chess::moves::Move;
+use crate::pgn::Pgn;
+use crate::termination::TerminationReason;
+use crate::{Game, GameState};
+use wasm_bindgen::prelude::*;
+
+/// ## WasmGame
+/// ### Type: `struct`
+/// A `Game` exported to JavaScript as the class `Game`
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame {
+    game: Game
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    /// Creates a game from the standard starting position
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmGame {
+        WasmGame { game: Game::new() }
+    }
+
+    /// ## `fromFen`
+    /// Creates a game from a FEN string, throws if the FEN is invalid
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(_fen: &str) -> Result<WasmGame, String> {
+        match Game::from_fen(_fen) {
+            Some(game) => Ok(WasmGame { game }),
+            None => Err(format!("invalid FEN \"{}\"", _fen))
+        }
+    }
+
+    /// ## `fromPgn`
+    /// Creates a game from the first game of a PGN text with all of its moves played, throws if it cannot be read
+    #[wasm_bindgen(js_name = fromPgn)]
+    pub fn from_pgn(_pgn: &str) -> Result<WasmGame, String> {
+        match Pgn::parse(_pgn).and_then(|pgn| pgn.to_game()) {
+            Some(game) => Ok(WasmGame { game }),
+            None => Err(String::from("invalid PGN"))
+        }
+    }
+
+    /// ## `makeMove`
+    /// Plays a move given in UCI, e.g `"e2e4"` or `"e7e8q"`, or in SAN, e.g `"Nf3"`
+    /// ### Returns
+    /// Returns the state of the game after the move like `state`, throws if the move is malformed, illegal or the game is over
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, _move: &str) -> Result<String, String> {
+        if self.game.is_over() {
+            return Err(String::from("the game is over"));
+        }
+
+        let parsed = Move::from_uci(_move).or_else(|| self.game.san_to_move(_move));
+        match parsed.and_then(|parsed| self.game.play_move(parsed)) {
+            Some(_) => Ok(self.state()),
+            None => Err(format!("illegal move \"{}\"", _move))
+        }
+    }
+
+    /// ## `legalMoves`
+    /// Returns a JSON array with every legal move of the active colour in UCI notation
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> String {
+        if self.game.is_over() {
+            return String::from("[]");
+        }
+        json_array(self.game.get_all_legal_moves().iter().map(|_move| _move.to_uci()))
+    }
+
+    /// ## `legalMovesFrom`
+    /// Returns a JSON array with the squares the piece on a square can move to, e.g `["e3", "e4"]` for `"e2"`
+    #[wasm_bindgen(js_name = legalMovesFrom)]
+    pub fn legal_moves_from(&self, _square: &str) -> String {
+        if self.game.is_over() {
+            return String::from("[]");
+        }
+
+        let mut squares: Vec<String> = Vec::new();
+        for _move in self.game.get_all_legal_moves() {
+            let to = Board::num_to_filerank(&_move.to);
+            // Promotions give the same square once per piece
+            if Board::num_to_filerank(&_move.from) == _square && !squares.contains(&to) {
+                squares.push(to);
+            }
+        }
+        json_array(squares)
+    }
+
+    /// ## `state`
+    /// Returns a JSON object describing the game:
+    /// - `state`: `"in_progress"`, `"check"`, `"game_over"`, `"time_forfeit"` or `"king_of_the_hill"`
+    /// - `turn`: `"white"` or `"black"`
+    /// - `result`: The PGN result, `"*"` while the game is going on
+    /// - `termination`: How the game ended, e.g `"checkmate"` or `"stalemate"`, `null` while it is going on
+    pub fn state(&self) -> String {
+        let state = match self.game.get_game_state() {
+            GameState::InProgress => "in_progress",
+            GameState::Check => "check",
+            GameState::GameOver => "game_over",
+            GameState::TimeForfeit { .. } => "time_forfeit",
+            GameState::KingOfTheHill { .. } => "king_of_the_hill"
+        };
+        let turn = match self.game.get_active_colour() {
+            Colour::White => "white",
+            Colour::Black => "black"
+        };
+        let termination = match self.game.get_termination().map(|termination| termination.reason) {
+            None => "null",
+            Some(TerminationReason::Checkmate) => "\"checkmate\"",
+            Some(TerminationReason::Stalemate) => "\"stalemate\"",
+            Some(TerminationReason::Resignation) => "\"resignation\"",
+            Some(TerminationReason::DrawAgreement) => "\"draw_agreement\"",
+            Some(TerminationReason::ThreefoldRepetition) => "\"threefold_repetition\"",
+            Some(TerminationReason::FiftyMoveRule) => "\"fifty_move_rule\"",
+            Some(TerminationReason::TimeForfeit) => "\"time_forfeit\"",
+            Some(TerminationReason::VariantEnd) => "\"variant_end\""
+        };
+
+        format!("{{\"state\":\"{}\",\"turn\":\"{}\",\"result\":\"{}\",\"termination\":{}}}", state, turn, self.game.get_result(), termination)
+    }
+
+    /// ## `history`
+    /// Returns a JSON array with the moves played so far in UCI notation
+    pub fn history(&self) -> String {
+        json_array(self.game.get_history().iter().map(|_move| _move.to_uci()))
+    }
+
+    /// Returns the FEN string of the current position
+    pub fn fen(&self) -> String {
+        self.game.to_fen()
+    }
+
+    /// Returns the game as PGN text
+    pub fn pgn(&self) -> String {
+        self.game.to_pgn().to_string()
+    }
+
+    /// ## `undo`
+    /// Takes back the last move by replaying the game without it, which also undoes a resignation or agreed draw
+    /// ### Returns
+    /// Returns the move taken back in UCI notation, throws if no move was played
+    pub fn undo(&mut self) -> Result<String, String> {
+        let last = match self.game.get_history().last() {
+            Some(last) => last.to_uci(),
+            None => return Err(String::from("there is no move to undo"))
+        };
+
+        self.game = self.game.take_back().expect("a move was played");
+        Ok(last)
+    }
+}
+
+impl Default for WasmGame {
+    fn default() -> Self {
+        WasmGame::new()
+    }
+}
+
+/// Writes strings that need no escaping, like moves and squares, as a JSON array
+fn json_array<I: IntoIterator<Item = String>>(_items: I) -> String {
+    let items: Vec<String> = _items.into_iter().map(|item| format!("\"{}\"", item)).collect();
+    format!("[{}]", items.join(","))
+}
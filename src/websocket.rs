@@ -0,0 +1,262 @@
+//! The parts of the WebSocket protocol (RFC 6455) used by the game server, enabled by the `server` feature:
+//! the opening handshake on both sides and reading and writing frames. Messages split over several frames are
+//! joined, extensions and subprotocols are not supported
+use std::io::{self, BufRead, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Appended to the key of the client before hashing, see RFC 6455 section 1.3
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Messages larger than this are refused
+const MAX_MESSAGE: usize = 1 << 20;
+
+/// A WebSocket message
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close
+}
+
+/// ## `accept_key`
+/// Returns the `Sec-WebSocket-Accept` value answering the `Sec-WebSocket-Key` of a client
+pub fn accept_key(_key: &str) -> String {
+    base64(&sha1(format!("{}{}", _key.trim(), HANDSHAKE_GUID).as_bytes()))
+}
+
+/// ## `accept`
+/// Answers the upgrade request of a client, given its `Sec-WebSocket-Key`
+pub fn accept<W: Write>(mut _writer: W, _key: &str) -> io::Result<()> {
+    write!(_writer, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", accept_key(_key))?;
+    _writer.flush()
+}
+
+/// ## `connect`
+/// Opens a WebSocket on a connection as a client: sends the upgrade request for a path and checks the answer
+/// ### Returns
+/// Returns an error of kind `InvalidData` if the server refused the upgrade or answered with the wrong key
+pub fn connect<S: BufRead + Write>(_stream: &mut S, _host: &str, _path: &str) -> io::Result<()> {
+    let key = base64(&random_bytes::<16>());
+    write!(_stream, "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n", _path, _host, key)?;
+    _stream.flush()?;
+
+    let mut line = String::new();
+    _stream.read_line(&mut line)?;
+    let upgraded = line.split_whitespace().nth(1) == Some("101");
+
+    let mut accepted = false;
+    loop {
+        line.clear();
+        if _stream.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            accepted |= name.trim().eq_ignore_ascii_case("sec-websocket-accept") && value.trim() == accept_key(&key);
+        }
+    }
+
+    if !upgraded || !accepted {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "the server refused the WebSocket upgrade"));
+    }
+    Ok(())
+}
+
+/// ## `read_message`
+/// Reads the next message, joining fragmented messages and unmasking frames of clients
+/// ### Returns
+/// Returns an error of kind `InvalidData` for malformed or too large messages
+pub fn read_message<R: Read>(_reader: &mut R) -> io::Result<Message> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+    let mut message: Option<(u8, Vec<u8>)> = None;
+
+    loop {
+        let mut header = [0u8; 2];
+        _reader.read_exact(&mut header)?;
+        let finished = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let masked = header[1] & 0x80 != 0;
+
+        let length = match header[1] & 0x7F {
+            126 => {
+                let mut bytes = [0u8; 2];
+                _reader.read_exact(&mut bytes)?;
+                u16::from_be_bytes(bytes) as usize
+            }
+            127 => {
+                let mut bytes = [0u8; 8];
+                _reader.read_exact(&mut bytes)?;
+                u64::from_be_bytes(bytes).min(MAX_MESSAGE as u64 + 1) as usize
+            }
+            length => length as usize
+        };
+        if length > MAX_MESSAGE {
+            return Err(invalid("message too large"));
+        }
+
+        let mut mask = [0u8; 4];
+        if masked {
+            _reader.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0u8; length];
+        _reader.read_exact(&mut payload)?;
+        if masked {
+            for (index, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[index % 4];
+            }
+        }
+
+        // Control frames can come between the fragments of a message
+        match opcode {
+            0x8 => return Ok(Message::Close),
+            0x9 => return Ok(Message::Ping(payload)),
+            0xA => return Ok(Message::Pong(payload)),
+            _ => ()
+        }
+
+        let (kind, mut data) = match (opcode, message.take()) {
+            (0x0, Some(started)) => started,
+            (0x1, None) | (0x2, None) => (opcode, Vec::new()),
+            _ => return Err(invalid("unexpected frame"))
+        };
+        data.extend_from_slice(&payload);
+        if data.len() > MAX_MESSAGE {
+            return Err(invalid("message too large"));
+        }
+
+        if !finished {
+            message = Some((kind, data));
+            continue;
+        }
+
+        return match kind {
+            0x1 => String::from_utf8(data).map(Message::Text).map_err(|_| invalid("text message is not UTF-8")),
+            _ => Ok(Message::Binary(data))
+        };
+    }
+}
+
+/// ## `write_message`
+/// Writes a message as a single frame. Clients must mask their frames, servers must not
+pub fn write_message<W: Write>(_writer: &mut W, _message: &Message, _masked: bool) -> io::Result<()> {
+    let (opcode, payload): (u8, &[u8]) = match _message {
+        Message::Text(text) => (0x1, text.as_bytes()),
+        Message::Binary(data) => (0x2, data),
+        Message::Close => (0x8, &[]),
+        Message::Ping(data) => (0x9, data),
+        Message::Pong(data) => (0xA, data)
+    };
+
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+    let mask_bit = if _masked {0x80} else {0x00};
+    match payload.len() {
+        length if length < 126 => frame.push(mask_bit | length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+
+    if _masked {
+        let mask = random_bytes::<4>();
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(index, byte)| byte ^ mask[index % 4]));
+    }
+    else {
+        frame.extend_from_slice(payload);
+    }
+
+    _writer.write_all(&frame)?;
+    _writer.flush()
+}
+
+/// Bytes for handshake keys and masks, they only need to differ between connections and frames.
+/// They come from the time and the hasher seeds of the standard library and are not secure, never use them for secrets
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+    let mut bytes = [0u8; N];
+    for (index, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(nanos ^ index as u64);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+    }
+    bytes
+}
+
+/// ## `sha1`
+/// The SHA-1 digest of some bytes, only used for the handshake
+fn sha1(_data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Padding: a 1 bit, zeros up to 56 bytes modulo 64 and the length in bits
+    let mut message = _data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((_data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6)
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Standard base64 with padding
+fn base64(_data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut text = String::with_capacity(_data.len().div_ceil(3) * 4);
+    for chunk in _data.chunks(3) {
+        let bits = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(ALPHABET[(bits >> (18 - 6 * index) & 0x3F) as usize] as char);
+            }
+            else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
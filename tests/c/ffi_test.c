@@ -0,0 +1,105 @@
+/* Plays through the C API of azeezd-chess and checks its results, see the Makefile next to it */
+#include <stdio.h>
+#include <string.h>
+#include "azeezd_chess.h"
+
+static int failures = 0;
+
+#define CHECK(condition) do { \
+    if (!(condition)) { \
+        fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+        failures++; \
+    } \
+} while (0)
+
+static int has_move(const ChessMove *moves, size_t count, const char *uci) {
+    for (size_t i = 0; i < count; i++) {
+        if (strcmp(moves[i].uci, uci) == 0) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+static void test_new_game(void) {
+    ChessGame *game = NULL;
+    ChessMove moves[CHESS_MAX_MOVES];
+    size_t count = 0;
+    ChessState state;
+
+    CHECK(chess_game_new(&game) == CHESS_ERROR_OK);
+    CHECK(game != NULL);
+
+    CHECK(chess_game_legal_moves(game, moves, CHESS_MAX_MOVES, &count) == CHESS_ERROR_OK);
+    CHECK(count == 20);
+    CHECK(has_move(moves, count, "e2e4"));
+    CHECK(has_move(moves, count, "g1f3"));
+
+    /* A small buffer still reports how many moves there are */
+    CHECK(chess_game_legal_moves(game, moves, 4, &count) == CHESS_ERROR_BUFFER_TOO_SMALL);
+    CHECK(count == 20);
+
+    CHECK(chess_game_make_move(game, "e2e4") == CHESS_ERROR_OK);
+    CHECK(chess_game_make_move(game, "e2e4") == CHESS_ERROR_ILLEGAL_MOVE);
+    CHECK(chess_game_make_move(game, "Nf6") == CHESS_ERROR_INVALID_MOVE);
+    CHECK(chess_game_make_move(game, NULL) == CHESS_ERROR_NULL_POINTER);
+
+    CHECK(chess_game_make_move(game, "f7f6") == CHESS_ERROR_OK);
+    CHECK(chess_game_make_move(game, "d2d4") == CHESS_ERROR_OK);
+    CHECK(chess_game_make_move(game, "g7g5") == CHESS_ERROR_OK);
+    CHECK(chess_game_state(game, &state) == CHESS_ERROR_OK);
+    CHECK(state == CHESS_STATE_IN_PROGRESS);
+
+    CHECK(chess_game_make_move(game, "d1h5") == CHESS_ERROR_OK);
+    CHECK(chess_game_state(game, &state) == CHESS_ERROR_OK);
+    CHECK(state == CHESS_STATE_CHECKMATE);
+    CHECK(chess_game_legal_moves(game, moves, CHESS_MAX_MOVES, &count) == CHESS_ERROR_OK);
+    CHECK(count == 0);
+    CHECK(chess_game_make_move(game, "a7a6") == CHESS_ERROR_GAME_OVER);
+
+    chess_game_free(game);
+}
+
+static void test_fen(void) {
+    const char *fen = "7k/P7/8/8/8/8/8/K7 w - - 0 1";
+    ChessGame *game = NULL;
+    ChessMove moves[CHESS_MAX_MOVES];
+    char buffer[128];
+    size_t length = 0;
+    size_t count = 0;
+
+    CHECK(chess_game_from_fen("not a fen", &game) == CHESS_ERROR_INVALID_FEN);
+    CHECK(chess_game_from_fen(fen, NULL) == CHESS_ERROR_NULL_POINTER);
+    CHECK(chess_game_from_fen(fen, &game) == CHESS_ERROR_OK);
+
+    CHECK(chess_game_fen(game, buffer, sizeof buffer, &length) == CHESS_ERROR_OK);
+    CHECK(strcmp(buffer, fen) == 0);
+    CHECK(length == strlen(fen));
+    CHECK(chess_game_fen(game, buffer, 8, &length) == CHESS_ERROR_BUFFER_TOO_SMALL);
+    CHECK(length == strlen(fen));
+
+    /* Promotions are listed once per piece */
+    CHECK(chess_game_legal_moves(game, moves, CHESS_MAX_MOVES, &count) == CHESS_ERROR_OK);
+    CHECK(has_move(moves, count, "a7a8q"));
+    CHECK(has_move(moves, count, "a7a8n"));
+    CHECK(chess_game_make_move(game, "a7a8r") == CHESS_ERROR_OK);
+    CHECK(chess_game_fen(game, buffer, sizeof buffer, &length) == CHESS_ERROR_OK);
+    CHECK(strcmp(buffer, "R6k/8/8/8/8/8/8/K7 b - - 0 1") == 0);
+
+    chess_game_free(game);
+    chess_game_free(NULL);
+}
+
+int main(void) {
+    test_new_game();
+    test_fen();
+
+    CHECK(strcmp(chess_error_message(CHESS_ERROR_ILLEGAL_MOVE), "illegal move") == 0);
+
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("All checks passed\n");
+    return 0;
+}
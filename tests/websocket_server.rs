@@ -0,0 +1,248 @@
+//! Plays games against a local `GameServer` over WebSockets, needs the `server` feature
+#![cfg(feature = "server")]
+
+use azeezd_chess::server::GameServer;
+use azeezd_chess::websocket::{self, Message};
+use serde_json::{json, Value};
+use std::io::{BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Starts a server on a free port
+fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || Arc::new(GameServer::new()).serve(listener));
+    address
+}
+
+/// Sends an HTTP request with a JSON body and returns the status and the JSON response
+fn http(_address: SocketAddr, _method: &str, _path: &str, _body: Value) -> (u16, Value) {
+    http_with_headers(_address, _method, _path, _body, "")
+}
+
+/// Sends an HTTP request with extra header lines, each ending with CRLF
+fn http_with_headers(_address: SocketAddr, _method: &str, _path: &str, _body: Value, _headers: &str) -> (u16, Value) {
+    let body = _body.to_string();
+    let mut stream = TcpStream::connect(_address).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}", _method, _path, _headers, body.len(), body).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let json = response.split("\r\n\r\n").nth(1).filter(|body| !body.is_empty()).map_or(Value::Null, |body| serde_json::from_str(body).unwrap());
+    (status, json)
+}
+
+/// A WebSocket client of one game
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream
+}
+
+impl Client {
+    fn connect(_address: SocketAddr, _game: u64) -> Client {
+        let stream = TcpStream::connect(_address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+
+        let mut handshake = HandshakeStream { reader: &mut reader, writer: &mut writer };
+        websocket::connect(&mut handshake, "localhost", &format!("/games/{}/ws", _game)).unwrap();
+        Client { reader, writer }
+    }
+
+    fn send(&mut self, _message: Value) {
+        websocket::write_message(&mut self.writer, &Message::Text(_message.to_string()), true).unwrap();
+    }
+
+    /// Returns the next event, skipping clock events
+    fn next(&mut self) -> Value {
+        loop {
+            match websocket::read_message(&mut self.reader).unwrap() {
+                Message::Text(text) => {
+                    let event: Value = serde_json::from_str(&text).unwrap();
+                    if event["type"] != "clock" {
+                        return event;
+                    }
+                }
+                other => panic!("unexpected message {:?}", other)
+            }
+        }
+    }
+
+    /// Returns the next event of a type, skipping the others
+    fn next_of(&mut self, _type: &str) -> Value {
+        loop {
+            let event = self.next();
+            if event["type"] == _type {
+                return event;
+            }
+        }
+    }
+
+    fn close(mut self) {
+        websocket::write_message(&mut self.writer, &Message::Close, true).unwrap();
+        // Events sent before the server saw the close come first
+        while websocket::read_message(&mut self.reader).unwrap() != Message::Close {}
+    }
+}
+
+/// Joins the buffered reading side and the writing side of a connection for the handshake
+struct HandshakeStream<'a> {
+    reader: &'a mut BufReader<TcpStream>,
+    writer: &'a mut TcpStream
+}
+
+impl Read for HandshakeStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl std::io::BufRead for HandshakeStream<'_> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount)
+    }
+}
+
+impl Write for HandshakeStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[test]
+fn players_and_spectators_follow_a_game() {
+    let address = start_server();
+    let (status, created) = http(address, "POST", "/games", json!({}));
+    assert_eq!(status, 201);
+    let id = created["id"].as_u64().unwrap();
+
+    let mut white = Client::connect(address, id);
+    let mut black = Client::connect(address, id);
+    let mut spectator = Client::connect(address, id);
+    for client in [&mut white, &mut black, &mut spectator] {
+        let snapshot = client.next();
+        assert_eq!(snapshot["type"], "snapshot");
+        assert_eq!(snapshot["game"]["moves"], json!([]));
+    }
+
+    white.send(json!({"type": "join", "colour": "white"}));
+    let joined = white.next();
+    assert_eq!(joined["type"], "joined");
+    assert_eq!(joined["colour"], "white");
+    black.send(json!({"type": "join", "colour": "white"}));
+    assert_eq!(black.next_of("error")["code"], "seat_taken");
+    black.send(json!({"type": "join", "colour": "black"}));
+    let joined = black.next_of("joined");
+    assert_eq!(joined["colour"], "black");
+    let black_token = joined["token"].as_str().unwrap().to_string();
+    assert_eq!(spectator.next_of("players"), json!({"type": "players", "white": true, "black": false}));
+    assert_eq!(spectator.next_of("players"), json!({"type": "players", "white": true, "black": true}));
+
+    // Spectators and the wrong player cannot move
+    spectator.send(json!({"type": "move", "move": "e4"}));
+    assert_eq!(spectator.next_of("error")["code"], "not_seated");
+    black.send(json!({"type": "move", "move": "e5"}));
+    assert_eq!(black.next_of("error")["code"], "not_your_turn");
+    white.send(json!({"type": "move", "move": "e2e5"}));
+    assert_eq!(white.next_of("error")["code"], "illegal_move");
+
+    white.send(json!({"type": "move", "move": "e4"}));
+    for client in [&mut white, &mut black, &mut spectator] {
+        let played = client.next_of("move");
+        assert_eq!((played["uci"].as_str(), played["san"].as_str(), played["colour"].as_str()), (Some("e2e4"), Some("e4"), Some("white")));
+        assert_eq!(client.next()["game"]["turn"], "black");
+    }
+
+    // Over HTTP a taken seat needs its token, and the moves reach the WebSocket clients too
+    let (status, refused) = http(address, "POST", &format!("/games/{}/moves", id), json!({"move": "f5"}));
+    assert_eq!((status, refused["error"]["code"].as_str()), (403, Some("seat_token_required")));
+    assert_eq!(http(address, "POST", &format!("/games/{}/resign", id), json!({"colour": "white", "token": black_token})).0, 403);
+    assert_eq!(http(address, "POST", &format!("/games/{}/moves", id), json!({"move": "f6", "token": black_token})).0, 200);
+    assert_eq!(spectator.next_of("move")["san"], "f6");
+    assert_eq!(spectator.next_of("state")["game"]["turn"], "white");
+    let authorization = format!("Authorization: Bearer {}\r\n", black_token);
+    assert_eq!(http_with_headers(address, "POST", &format!("/games/{}/undo", id), json!({}), &authorization).0, 200);
+    assert_eq!(spectator.next_of("state")["game"]["turn"], "black");
+    assert_eq!(http_with_headers(address, "POST", &format!("/games/{}/moves", id), json!({"move": "f5"}), &authorization).0, 200);
+    assert_eq!(spectator.next_of("move")["san"], "f5");
+    assert_eq!(spectator.next_of("state")["game"]["turn"], "white");
+
+    black.send(json!({"type": "resign"}));
+    let state = spectator.next_of("state");
+    assert_eq!(state["game"]["result"], "1-0");
+    assert_eq!(state["game"]["termination"]["reason"], "resignation");
+
+    white.close();
+    black.close();
+    spectator.close();
+}
+
+#[test]
+fn reconnecting_players_get_a_snapshot_and_their_seat() {
+    let address = start_server();
+    let (_, created) = http(address, "POST", "/games", json!({"time_control": "300+2"}));
+    let id = created["id"].as_u64().unwrap();
+    assert!(created["clock"]["white"].as_u64().unwrap() > 299000);
+    assert_eq!(created["clock"]["running"], "white");
+
+    let mut white = Client::connect(address, id);
+    white.send(json!({"type": "join", "colour": "white"}));
+    let token = white.next_of("joined")["token"].as_str().unwrap().to_string();
+    white.send(json!({"type": "move", "move": "d4"}));
+    white.next_of("move");
+    drop(white);
+
+    let mut black = Client::connect(address, id);
+    black.next_of("snapshot");
+    black.send(json!({"type": "join", "colour": "black"}));
+    black.next_of("joined");
+    black.send(json!({"type": "move", "move": "d5"}));
+    black.next_of("move");
+
+    // The reconnected player sees the move made while it was away and plays on with its token
+    let mut white = Client::connect(address, id);
+    let snapshot = white.next_of("snapshot");
+    assert_eq!(snapshot["game"]["san"], json!(["d4", "d5"]));
+    assert_eq!(snapshot["players"], json!({"type": "players", "white": true, "black": true}));
+    assert!(snapshot["game"]["clock"]["white"].as_u64().unwrap() > 290000);
+
+    white.send(json!({"type": "move", "move": "c4"}));
+    assert_eq!(white.next_of("error")["code"], "not_seated");
+    white.send(json!({"type": "join", "token": "not a token"}));
+    assert_eq!(white.next_of("error")["code"], "unknown_token");
+    white.send(json!({"type": "join", "token": token}));
+    assert_eq!(white.next_of("joined")["colour"], "white");
+    white.send(json!({"type": "move", "move": "c4"}));
+    assert_eq!(black.next_of("move")["san"], "c4");
+
+    white.close();
+    black.close();
+}
+
+#[test]
+fn unknown_games_are_refused() {
+    let address = start_server();
+    let stream = TcpStream::connect(address).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let mut handshake = HandshakeStream { reader: &mut reader, writer: &mut writer };
+    assert!(websocket::connect(&mut handshake, "localhost", "/games/42/ws").is_err());
+}
+
+#[test]
+fn handshake_key_matches_the_rfc_example() {
+    assert_eq!(websocket::accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+}